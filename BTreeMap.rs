@@ -31,3 +31,28 @@ fn main() {
     let range: Vec<_> = map.range(1..=3).collect();
     println!("范围查询结果: {:?}", range);
 }
+
+
+
+// 同样的操作换成 space/x_library 的 OrdMap，再加上 BTreeMap 没有的顺序统计
+use x_library::ordmap::OrdMap;
+
+fn main() {
+    let mut map = OrdMap::from([(3, "three"), (1, "one"), (4, "four"), (2, "two")]);
+    map.remove(&2);
+    println!("OrdMap: {:?}", map);
+
+    // 范围查询和 BTreeMap 一样，范围里的个数不用遍历就能得到
+    let range: Vec<_> = map.range(1..=3).collect();
+    println!("范围查询结果: {:?}，共 {} 个", range, map.count_range(1..=3));
+
+    // 键 3 排第几，第 0 小的是谁，不大于 2 / 不小于 2 的最近的键
+    println!("rank(3) = {}, select(0) = {:?}", map.rank(&3), map.select(0));
+    println!("floor(2) = {:?}, ceiling(2) = {:?}", map.floor(&2), map.ceiling(&2));
+
+    // 在键 3 处切开，再拼回去
+    let mut high = map.split_off(&3);
+    println!("split_off(3): {:?} + {:?}", map, high);
+    map.append(&mut high);
+    println!("append 之后: {:?}", map);
+}
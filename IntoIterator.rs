@@ -38,3 +38,55 @@ fn main() {
         assert_eq!(i as i32, n);
     }
 }
+
+
+
+// 再给 MyCollection 加上 FromIterator 和 Extend，它就能作为 collect 的目标
+fn main() {
+    #[derive(Debug, PartialEq)]
+    struct MyCollection(Vec<i32>);
+
+    impl FromIterator<i32> for MyCollection {
+        fn from_iter<I: IntoIterator<Item = i32>>(iter: I) -> Self {
+            MyCollection(iter.into_iter().collect())
+        }
+    }
+
+    impl Extend<i32> for MyCollection {
+        fn extend<I: IntoIterator<Item = i32>>(&mut self, iter: I) {
+            self.0.extend(iter);
+        }
+    }
+
+    impl IntoIterator for MyCollection {
+        type Item = i32;
+        type IntoIter = std::vec::IntoIter<Self::Item>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.0.into_iter()
+        }
+    }
+
+    // 借用迭代：for x in &c
+    impl<'a> IntoIterator for &'a MyCollection {
+        type Item = &'a i32;
+        type IntoIter = std::slice::Iter<'a, i32>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.0.iter()
+        }
+    }
+
+    let mut c: MyCollection = (0..3).collect();
+    c.extend([3, 4]);
+    for n in &c {
+        print!("{} ", n);
+    }
+    println!();
+
+    // 收集 Result：全部成功才得到 Ok(MyCollection)
+    let ok: Result<MyCollection, String> = c.into_iter().map(Ok).collect();
+    assert_eq!(ok, Ok(MyCollection(vec![0, 1, 2, 3, 4])));
+    let err: Result<MyCollection, _> = "1 2 x".split(' ').map(str::parse::<i32>).collect();
+    assert!(err.is_err());
+}
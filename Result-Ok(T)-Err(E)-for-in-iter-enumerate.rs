@@ -1,6 +1,9 @@
-fn divide(dividend: f64, divisor: f64) -> Result<f64, String> {
+// 错误类型用 space/x_library 的 Error 代替 String
+use x_library::error::{Error, Result};
+
+fn divide(dividend: f64, divisor: f64) -> Result<f64> {
     if divisor == 0.0 {
-        Err(String::from("Division by zero error"))
+        Err(Error::DivisionByZero)
     } else {
         Ok(dividend / divisor)
     }
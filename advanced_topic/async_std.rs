@@ -36,3 +36,29 @@ fn main() -> std::io::Result<()> {
     })
 }
 
+
+
+
+// 加上准入控制的 echo 服务器：每个 IP 限流、全局最多 100 个连接、30 秒空闲超时
+use async_std::prelude::*;
+use async_std::task;
+use std::sync::Arc;
+use std::time::Duration;
+use x_library::admission::{serve_async_std, AdmissionConfig, AdmissionControl};
+
+fn main() -> std::io::Result<()> {
+    let control = Arc::new(AdmissionControl::new(AdmissionConfig {
+        max_connections: 100,
+        idle_timeout: Duration::from_secs(30),
+        ..AdmissionConfig::default()
+    }));
+    task::block_on(async {
+        let listener = async_std::net::TcpListener::bind("127.0.0.1:8080").await?;
+        serve_async_std(listener, control, |mut stream, _peer| async move {
+            let mut buffer = [0; 1024];
+            let n = stream.read(&mut buffer).await?;
+            stream.write_all(&buffer[0..n]).await
+        })
+        .await
+    })
+}
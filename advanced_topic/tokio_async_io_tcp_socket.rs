@@ -13,3 +13,39 @@ async fn main() -> std::io::Result<()> {
         });
     }
 }
+
+
+
+// 同一个监听循环，换成 space/x_library 里的 HTTP/1.1 服务器
+// curl http://127.0.0.1:8080/notes/RAII.md
+use tokio::net::TcpListener;
+use x_library::http::{Response, Router, Server, StaticFiles};
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:8080").await?;
+    let router = Router::new()
+        .get("/", |_| async { Response::text(200, "hello\n") })
+        .get("/notes/*", StaticFiles::new(".").extension("md").handler());
+    Server::new(router).serve(listener).await
+}
+
+
+
+// 加上准入控制的 echo 服务器，被拒绝的连接会收到一行 "ERR ...\n"
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use x_library::admission::{serve_tokio, AdmissionConfig, AdmissionControl};
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:8080").await?;
+    let control = Arc::new(AdmissionControl::new(AdmissionConfig::default()));
+    serve_tokio(listener, control, |mut socket, _peer| async move {
+        let mut buffer = [0; 1024];
+        let n = socket.read(&mut buffer).await?;
+        socket.write_all(&buffer[0..n]).await
+    })
+    .await
+}
@@ -57,3 +57,74 @@ fn main() {
     a.remove();
 }
 
+
+
+/** 让 IntContainer 能 collect、extend 和 for 循环（见 collect.md） */
+trait Container {
+    type Item;
+    fn add(&mut self, item: Self::Item);
+    fn remove(&mut self) -> Self::Item;
+}
+
+struct IntContainer {
+    items: Vec<i32>,
+}
+
+impl Container for IntContainer {
+    type Item = i32;
+
+    fn add(&mut self, item: Self::Item) {
+        self.items.push(item);
+    }
+
+    fn remove(&mut self) -> Self::Item {
+        self.items.pop().unwrap()
+    }
+}
+
+impl FromIterator<i32> for IntContainer {
+    fn from_iter<I: IntoIterator<Item = i32>>(iter: I) -> Self {
+        IntContainer {
+            items: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<i32> for IntContainer {
+    fn extend<I: IntoIterator<Item = i32>>(&mut self, iter: I) {
+        for item in iter {
+            self.add(item);
+        }
+    }
+}
+
+// 按值迭代交出所有元素
+impl IntoIterator for IntContainer {
+    type Item = i32;
+    type IntoIter = std::vec::IntoIter<i32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+// 借用迭代，`for x in &a` 不会消耗容器
+impl<'a> IntoIterator for &'a IntContainer {
+    type Item = &'a i32;
+    type IntoIter = std::slice::Iter<'a, i32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+fn main() {
+    let mut a: IntContainer = (1..=3).collect();
+    a.extend([1001, 1002]);
+    assert_eq!(a.remove(), 1002);
+    let sum: i32 = (&a).into_iter().sum();
+    assert_eq!(sum, 1007);
+    for item in a {
+        println!("{item}");
+    }
+}
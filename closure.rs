@@ -90,3 +90,18 @@ fn main() {
 }
 
 
+
+
+
+// 用 space/x_library 的布局检查器代替手写的指针打印：同时显示所在区域、len 和 capacity
+use x_library::layout::inspect;
+
+fn main() {
+    let mut a = "ab".to_string();
+    print!("{}", inspect("a", &a));
+
+    (move || {
+        a.push('c');
+        print!("{}", inspect("a", &a));         // String 结构体换了位置，push 之后缓冲区也可能重新分配
+    })();
+}
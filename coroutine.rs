@@ -19,3 +19,22 @@ fn main() {
     assert_eq!(pinned_coroutine.as_mut().resume(()), CoroutineState::Yielded(3));
     assert_eq!(pinned_coroutine.as_mut().resume(()), CoroutineState::Complete(()));
 }
+
+
+
+// stable 版本：用 space/x_library 里基于 async/await 的生成器实现同样的断言
+use x_library::generator::{gen, GeneratorState};
+
+fn main() {
+    let mut coroutine = gen(|co| async move {
+        co.yield_(1).await;
+        co.yield_(2).await;
+        co.yield_(3).await;
+    });
+
+    // gen 内部已经把 async 块 Box::pin 住了，不需要再手动 Pin
+    assert_eq!(coroutine.resume(()), GeneratorState::Yielded(1));
+    assert_eq!(coroutine.resume(()), GeneratorState::Yielded(2));
+    assert_eq!(coroutine.resume(()), GeneratorState::Yielded(3));
+    assert_eq!(coroutine.resume(()), GeneratorState::Complete(()));
+}
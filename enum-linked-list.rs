@@ -63,3 +63,116 @@ fn main() {
     println!("linked list has length: {}", list.len());
     println!("{}", list.stringify());
 }
+
+
+
+// 让 List 成为 collect / extend 的目标，也能用 for 遍历（约定见 collect.md）
+enum List {
+    Cons(u32, Box<List>),
+    Nil,
+}
+
+impl FromIterator<u32> for List {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> List {
+        // prepend 会把顺序倒过来，所以先收集再从后往前建
+        let items: Vec<u32> = iter.into_iter().collect();
+        items
+            .into_iter()
+            .rev()
+            .fold(List::Nil, |tail, head| List::Cons(head, Box::new(tail)))
+    }
+}
+
+impl Extend<u32> for List {
+    fn extend<I: IntoIterator<Item = u32>>(&mut self, iter: I) {
+        // 先走到末尾的 Nil，新元素按顺序接在那里
+        let mut tail = self;
+        while let List::Cons(_, next) = tail {
+            tail = next;
+        }
+        for item in iter {
+            *tail = List::Cons(item, Box::new(List::Nil));
+            if let List::Cons(_, next) = tail {
+                tail = next;
+            }
+        }
+    }
+}
+
+// 按值迭代：每次把头结点拆下来
+struct IntoIter(List);
+
+impl Iterator for IntoIter {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        match std::mem::replace(&mut self.0, List::Nil) {
+            List::Cons(head, tail) => {
+                self.0 = *tail;
+                Some(head)
+            }
+            List::Nil => None,
+        }
+    }
+}
+
+impl IntoIterator for List {
+    type Item = u32;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter(self)
+    }
+}
+
+// 借用迭代，`for x in &list` 不会消耗链表
+struct Iter<'a>(&'a List);
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a u32;
+
+    fn next(&mut self) -> Option<&'a u32> {
+        match self.0 {
+            List::Cons(head, tail) => {
+                self.0 = tail;
+                Some(head)
+            }
+            List::Nil => None,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a List {
+    type Item = &'a u32;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        Iter(self)
+    }
+}
+
+impl List {
+    fn stringify(&self) -> String {
+        match self {
+            List::Cons(head, tail) => format!("{}, {}", head, tail.stringify()),
+            List::Nil => format!("Nil"),
+        }
+    }
+}
+
+fn main() {
+    let mut list: List = (1..=3).collect();
+    println!("{}", list.stringify()); // 1, 2, 3, Nil
+
+    list.extend([4, 5]);
+    let sum: u32 = (&list).into_iter().sum();
+    println!("{}", sum); // 15
+    for item in list {
+        print!("{} ", item); // 1 2 3 4 5
+    }
+    println!();
+
+    // 解析失败时整个结果是 Err
+    let parsed: Result<List, _> = "4 5 six".split(' ').map(str::parse::<u32>).collect();
+    println!("{}", parsed.map(|l| l.stringify()).unwrap_or_else(|e| e.to_string()));
+}
@@ -54,3 +54,32 @@ fn main(){
 
     try_logon(&accounts, "j.everyman", "password123");
 }
+
+
+
+// 同样的登录流程换成 space/x_library 的开放寻址哈希表，换一个更快的哈希函数
+use x_library::openmap::{FxBuildHasher, OpenMap, Probe};
+
+#[derive(PartialEq, Eq, Hash)]
+struct Account<'a>{
+    username: &'a str,
+    password: &'a str,
+}
+
+fn main(){
+    let mut accounts: OpenMap<Account, &str, FxBuildHasher> =
+        OpenMap::with_hasher(FxBuildHasher::default()).probing(Probe::RobinHood);
+
+    accounts.insert(Account { username: "j.everyman", password: "password123" }, "John Everyman");
+
+    for password in ["psasword123", "password123"] {
+        match accounts.get(&Account { username: "j.everyman", password }) {
+            Some(name) => println!("Successful logon! Name: {}", name),
+            None => println!("Login failed!"),
+        }
+    }
+
+    // 条目 API 和 std 一样
+    *accounts.entry(Account { username: "guest", password: "" }).or_insert("Guest") = "Visitor";
+    println!("{} accounts, average probe length {:.2}", accounts.len(), accounts.average_probe_len());
+}
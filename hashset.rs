@@ -32,3 +32,31 @@ fn main() {
     println!("Symmetric Difference: {:?}",
              a.symmetric_difference(&b).collect::<Vec<&i32>>());
 }
+
+
+
+// 同样的集合运算换成 space/x_library 的 BitSet：整字做位运算，输出按从小到大排好
+use x_library::bitset::BitSet;
+
+fn main() {
+    let mut a = BitSet::from([1, 2, 3]);
+    let mut b = BitSet::from([2, 3, 4]);
+
+    assert!(a.insert(4));
+    assert!(a.contains(4));
+    assert_eq!(b.insert(4), false);
+    b.insert(5);
+
+    println!("A: {:?}", a);
+    println!("B: {:?}", b);
+
+    // 有序打印 {1, 2, 3, 4, 5}
+    println!("Union: {:?}", &a | &b);
+    println!("Difference: {:?}", &a - &b);
+    println!("Intersection: {:?}", &a & &b);
+    println!("Symmetric Difference: {:?}", &a ^ &b);
+
+    // 比 4 小的有几个；连续的元素压成游程
+    println!("rank(4) in A ∪ B: {}", (&a | &b).rank(4));
+    println!("RLE of A ∪ B: {:?}", (&a | &b).to_rle());
+}
@@ -20,3 +20,21 @@ fn main() {
 
     let _ = sum;
 }
+
+
+
+// 并行版本：用 space/x_pool 的线程池把切片分块，每块跑同样的 map/filter/sum，再把各块的和加起来
+use x_pool::ThreadPool;
+
+fn main() {
+    let numbers: Vec<i32> = (1..=1000).collect();
+    let pool = ThreadPool::work_stealing(4);
+
+    let sum: i32 = pool
+        .par_chunks(&numbers, 100, |chunk| {
+            chunk.iter().map(|x| x * 2).filter(|x| x > &5).sum::<i32>()
+        })
+        .into_iter()
+        .sum();
+    println!("{sum}");
+}
@@ -103,3 +103,18 @@ fn main() {
     //pair.destroy();
     // 试一试 ^ 将此行注释去掉
 }
+
+
+
+// 用 space/x_library 的守卫代替手动 destroy：忘了调用也会在离开作用域时清理
+use x_library::defer;
+use x_library::guard::Transaction;
+
+fn main() {
+    let pair = Pair(Box::new(1), Box::new(2));
+    defer! { println!("scope end") }
+
+    let mut tx = Transaction::new();
+    tx.on_rollback(move || pair.destroy());
+    // 没有 tx.commit()，离开作用域时先回滚（调用 destroy），再打印 scope end
+}
@@ -0,0 +1,43 @@
+// 多个生产者通过通道把消息发给一个消费者
+use std::sync::mpsc;
+use std::thread;
+
+fn main() {
+    let (tx, rx) = mpsc::channel();
+
+    for id in 0..3 {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            tx.send(format!("hello from thread {id}")).unwrap();
+        });
+    }
+    // 主线程自己的 tx 也要 drop，否则 rx 的迭代永远不会结束
+    drop(tx);
+
+    for msg in rx {
+        println!("{msg}");
+    }
+}
+
+
+
+// 同样的代码换成 space/x_library 的通道，再用 select! 同时等两个通道
+use std::time::Duration;
+use x_library::channel::{bounded, unbounded};
+use x_library::select;
+
+fn main() {
+    let (tx_num, rx_num) = unbounded();
+    let (tx_text, rx_text) = bounded(1);
+
+    thread::spawn(move || tx_num.send(42).unwrap());
+    thread::spawn(move || tx_text.send("hi").unwrap());
+
+    for _ in 0..2 {
+        select! {
+            recv(rx_num) -> msg => println!("number: {msg:?}"),
+            recv(rx_text) -> msg => println!("text: {msg:?}"),
+            default(Duration::from_secs(1)) => println!("timeout"),
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use x_library::error::Context;
+
 // 不会 `panic!` 的整数除法。
 fn checked_division(dividend: i32, divisor: i32) -> Option<i32> {
     if divisor == 0 {
@@ -11,10 +13,12 @@ fn checked_division(dividend: i32, divisor: i32) -> Option<i32> {
 
 // 此函数处理可能失败的除法
 fn try_division(dividend: i32, divisor: i32) {
-    // `Option` 值可以进行模式匹配，就和其他枚举类型一样
-    match checked_division(dividend, divisor) {
-        None => println!("{} / {} failed!", dividend, divisor),
-        Some(quotient) => {
+    // 给 None 加上说明，变成 x_library::error::Error，再连同原因链一起打印
+    match checked_division(dividend, divisor)
+        .with_context(|| format!("{} / {} failed!", dividend, divisor))
+    {
+        Err(e) => println!("{}", e.report()),
+        Ok(quotient) => {
             println!("{} / {} = {}", dividend, divisor, quotient)
         },
     }
@@ -36,3 +40,18 @@ fn main() {
     // 解包 `None` 将会引发 `panic!`。
     println!("{:?} unwraps to {:?}", none, none.unwrap());
 }
+
+
+
+// 用 space/x_library 的 panic 捕获跑同样的 unwrap：panic 变成 Err，程序继续往下走
+use x_library::panics::catch;
+
+fn main() {
+    let none: Option<i32> = None;
+
+    match catch(|| none.unwrap()) {
+        Ok(v) => println!("{:?} unwraps to {:?}", none, v),
+        Err(report) => println!("caught: {report}"),
+    }
+    println!("still running");
+}
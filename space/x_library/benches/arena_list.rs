@@ -0,0 +1,92 @@
+// 用 arena 和 Box 分别构建 enum-linked-list.rs 里的链表，比较耗时和分配次数
+//
+// cargo bench -p x_library --bench arena_list
+
+use x_library::arena::{Arena, CountingAlloc};
+
+mod common;
+
+use common::Measure;
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc::system();
+
+const LEN: u32 = 10_000;
+const ROUNDS: u32 = 200;
+static BENCH: Measure = Measure::new(&GLOBAL, ROUNDS);
+
+// enum-linked-list.rs 原样的 Box 版本
+enum BoxList {
+    Cons(u32, Box<BoxList>),
+    Nil,
+}
+
+impl BoxList {
+    fn prepend(self, elem: u32) -> BoxList {
+        BoxList::Cons(elem, Box::new(self))
+    }
+
+    // 用循环而不是递归，避免长链表爆栈
+    fn sum(&self) -> u64 {
+        let mut node = self;
+        let mut total = 0;
+        while let BoxList::Cons(v, tail) = node {
+            total += *v as u64;
+            node = tail;
+        }
+        total
+    }
+}
+
+enum ArenaList<'a> {
+    Cons(u32, &'a ArenaList<'a>),
+    Nil,
+}
+
+impl<'a> ArenaList<'a> {
+    fn prepend(&'a self, arena: &'a Arena, elem: u32) -> &'a ArenaList<'a> {
+        arena.alloc_borrowed(ArenaList::Cons(elem, self))
+    }
+
+    fn sum(&self) -> u64 {
+        let mut node = self;
+        let mut total = 0;
+        while let ArenaList::Cons(v, tail) = node {
+            total += *v as u64;
+            node = tail;
+        }
+        total
+    }
+}
+
+fn main() {
+    println!("building a {LEN}-node list, {ROUNDS} rounds");
+
+    BENCH.run("Box", || {
+        let mut list = BoxList::Nil;
+        for i in 0..LEN {
+            list = list.prepend(i);
+        }
+        list.sum()
+    });
+
+    BENCH.run("Arena", || {
+        let arena = Arena::new();
+        let mut list: &ArenaList = arena.alloc_borrowed(ArenaList::Nil);
+        for i in 0..LEN {
+            list = list.prepend(&arena, i);
+        }
+        list.sum()
+    });
+
+    // 复用同一个 arena：reset 之后只剩一个 chunk，后续轮次几乎不再分配
+    let mut arena = Arena::new();
+    BENCH.run("Arena (reset)", || {
+        arena.reset();
+        let mut list: &ArenaList = arena.alloc_borrowed(ArenaList::Nil);
+        for i in 0..LEN {
+            list = list.prepend(&arena, i);
+        }
+        list.sum()
+    });
+}
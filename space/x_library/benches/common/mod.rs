@@ -0,0 +1,40 @@
+// 几个 bench 共用的计时和分配统计：每轮跑同一个闭包，打印平均耗时和分配/释放次数。
+// 全局分配器只能在各个 bench 自己的 crate 里声明，这里通过引用拿到它的计数
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use x_library::arena::{AllocStats, CountingAlloc};
+
+pub struct Measure {
+    alloc: &'static CountingAlloc,
+    rounds: u32,
+}
+
+impl Measure {
+    pub const fn new(alloc: &'static CountingAlloc, rounds: u32) -> Self {
+        Measure { alloc, rounds }
+    }
+
+    // 预热一轮并返回它的结果，方便调用方校验不同实现算出来的东西一样
+    pub fn run<R>(&self, name: &str, mut round: impl FnMut() -> R) -> R {
+        let result = round();
+        let before = self.alloc.stats();
+        let start = Instant::now();
+        for _ in 0..self.rounds {
+            black_box(round());
+        }
+        let elapsed = start.elapsed();
+        self.report(name, elapsed, self.alloc.stats().since(before));
+        result
+    }
+
+    fn report(&self, name: &str, elapsed: Duration, stats: AllocStats) {
+        println!(
+            "  {name:<22} {:>10.1?}/round  {:>8} allocs/round  {:>8} frees/round",
+            elapsed / self.rounds,
+            stats.allocations / self.rounds as usize,
+            stats.deallocations / self.rounds as usize,
+        );
+    }
+}
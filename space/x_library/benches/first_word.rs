@@ -0,0 +1,64 @@
+// iterator.rs 的 first_word：取出每句话的第一个单词并保存下来，比较 String 和 Text
+//
+// cargo bench -p x_library --bench first_word
+
+use x_library::arena::CountingAlloc;
+use x_library::text::Text;
+
+mod common;
+
+use common::Measure;
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc::system();
+
+const SENTENCES: usize = 10_000;
+const ROUNDS: u32 = 200;
+static BENCH: Measure = Measure::new(&GLOBAL, ROUNDS);
+
+fn first_word(s: &str) -> &str {
+    let bytes = s.as_bytes();
+    for (i, &item) in bytes.iter().enumerate() {
+        if item == b' ' {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+fn main() {
+    let words = [
+        "good",
+        "morning",
+        "internationalization",
+        "a",
+        "pneumonoultramicroscopic",
+    ];
+    let sentences: Vec<String> = (0..SENTENCES)
+        .map(|i| format!("{} {} world", words[i % words.len()], i))
+        .collect();
+    println!("first_word over {SENTENCES} sentences, {ROUNDS} rounds (1 alloc/round is the Vec)");
+
+    BENCH.run("String", || {
+        sentences
+            .iter()
+            .map(|s| first_word(s).to_string())
+            .collect::<Vec<String>>()
+    });
+
+    // 借用：和 &str 一样不分配
+    BENCH.run("Text (borrowed)", || {
+        sentences
+            .iter()
+            .map(|s| Text::from(first_word(s)))
+            .collect::<Vec<Text>>()
+    });
+
+    // 脱离原句子：短单词内联，只有超过内联容量的那一个会分配
+    BENCH.run("Text (owned)", || {
+        sentences
+            .iter()
+            .map(|s| Text::from(first_word(s)).into_owned())
+            .collect::<Vec<Text<'static>>>()
+    });
+}
@@ -0,0 +1,133 @@
+// OpenMap 和 std HashMap 的对比：字符串 key 和 hashmap-key.rs 里的 Account 结构体 key，
+// 两种探测策略 × 三种哈希函数。每轮做一遍：全部插入、全部命中查找、同样多次未命中查找、删一半再插回去。
+//
+// cargo bench -p x_library --bench open_map
+
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::hint::black_box;
+use std::time::Instant;
+
+use x_library::openmap::{FnvBuildHasher, FxBuildHasher, OpenMap, Probe, SipBuildHasher};
+
+const KEYS: usize = 50_000;
+const ROUNDS: u32 = 10;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Account {
+    username: String,
+    password: String,
+}
+
+// 两种表共同的操作，方便用同一段代码计时
+trait Map<K> {
+    fn put(&mut self, key: K, value: usize);
+    fn lookup(&self, key: &K) -> Option<usize>;
+    fn delete(&mut self, key: &K) -> Option<usize>;
+}
+
+impl<K: Hash + Eq, S: BuildHasher> Map<K> for HashMap<K, usize, S> {
+    fn put(&mut self, key: K, value: usize) {
+        self.insert(key, value);
+    }
+    fn lookup(&self, key: &K) -> Option<usize> {
+        self.get(key).copied()
+    }
+    fn delete(&mut self, key: &K) -> Option<usize> {
+        self.remove(key)
+    }
+}
+
+impl<K: Hash + Eq, S: BuildHasher> Map<K> for OpenMap<K, usize, S> {
+    fn put(&mut self, key: K, value: usize) {
+        self.insert(key, value);
+    }
+    fn lookup(&self, key: &K) -> Option<usize> {
+        self.get(key).copied()
+    }
+    fn delete(&mut self, key: &K) -> Option<usize> {
+        self.remove(key)
+    }
+}
+
+fn round<K: Clone, M: Map<K>>(mut map: M, hits: &[K], misses: &[K]) -> usize {
+    for (i, key) in hits.iter().enumerate() {
+        map.put(key.clone(), i);
+    }
+    let mut found = 0;
+    for key in hits {
+        found += map.lookup(key).is_some() as usize;
+    }
+    for key in misses {
+        found += map.lookup(key).is_some() as usize;
+    }
+    // 删一半再插回去：线性探测会留下墓碑
+    for key in hits.iter().step_by(2) {
+        map.delete(key);
+    }
+    for (i, key) in hits.iter().enumerate().step_by(2) {
+        map.put(key.clone(), i);
+    }
+    found
+}
+
+fn measure<K: Clone, M: Map<K>>(name: &str, hits: &[K], misses: &[K], make: impl Fn() -> M) {
+    assert_eq!(round(make(), hits, misses), hits.len());
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(round(make(), hits, misses));
+    }
+    println!("  {name:<28} {:>10.2?}/round", start.elapsed() / ROUNDS);
+}
+
+fn open<K: Hash + Eq, S: BuildHasher>(hasher: S, probe: Probe) -> OpenMap<K, usize, S> {
+    OpenMap::with_hasher(hasher).probing(probe)
+}
+
+fn compare<K: Hash + Eq + Clone>(title: &str, hits: &[K], misses: &[K]) {
+    println!("{title}: {} keys, {ROUNDS} rounds", hits.len());
+    measure("std HashMap (SipHash)", hits, misses, HashMap::new);
+    measure("std HashMap (Fx)", hits, misses, || {
+        HashMap::with_hasher(FxBuildHasher::default())
+    });
+    for probe in [Probe::RobinHood, Probe::Linear] {
+        let label = |hasher: &str| format!("{probe:?} ({hasher})");
+        measure(&label("SipHash"), hits, misses, || {
+            open(SipBuildHasher::new(), probe)
+        });
+        measure(&label("Fx"), hits, misses, || {
+            open(FxBuildHasher::default(), probe)
+        });
+        measure(&label("FNV"), hits, misses, || {
+            open(FnvBuildHasher::default(), probe)
+        });
+    }
+
+    // 同样装满时两种策略的探测长度
+    for probe in [Probe::RobinHood, Probe::Linear] {
+        let mut map = open(FxBuildHasher::default(), probe);
+        map.extend(hits.iter().cloned().zip(0..));
+        println!(
+            "  {probe:?}: load {:.2}, average probe {:.2}, max probe {}",
+            map.len() as f64 / map.capacity() as f64,
+            map.average_probe_len(),
+            map.max_probe_len()
+        );
+    }
+}
+
+fn main() {
+    let strings =
+        |prefix: &str| -> Vec<String> { (0..KEYS).map(|i| format!("{prefix}{i}")).collect() };
+    compare("string keys", &strings("user"), &strings("nobody"));
+
+    let accounts = |prefix: &str| -> Vec<Account> {
+        (0..KEYS)
+            .map(|i| Account {
+                username: format!("{prefix}.everyman{i}"),
+                password: format!("password{}", i * 7919 % 1000),
+            })
+            .collect()
+    };
+    compare("Account keys", &accounts("j"), &accounts("x"));
+}
@@ -0,0 +1,106 @@
+// SmallVec 和 Vec 的分配次数对比（collection-vector.md）：
+// - 大量短向量：长度不超过 N 时 SmallVec 一次都不分配
+// - 一个长向量一直 push：扩容倍数越小，realloc 越多，但最后空着的容量越少
+//
+// realloc 在 CountingAlloc 里算一次释放加一次分配。
+//
+// cargo bench -p x_library --bench small_vec
+
+use x_library::arena::CountingAlloc;
+use x_library::smallvec::{Growth, SmallVec};
+
+mod common;
+
+use common::Measure;
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc::system();
+
+const SHORT_VECS: u64 = 10_000;
+const LONG_LEN: u64 = 100_000;
+const ROUNDS: u32 = 50;
+static BENCH: Measure = Measure::new(&GLOBAL, ROUNDS);
+
+// 两种向量共同的操作
+trait Push: Default {
+    fn put(&mut self, value: u64);
+    fn total(&self) -> u64;
+    fn cap(&self) -> usize;
+}
+
+impl Push for Vec<u64> {
+    fn put(&mut self, value: u64) {
+        self.push(value);
+    }
+    fn total(&self) -> u64 {
+        self.iter().sum()
+    }
+    fn cap(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<const N: usize> Push for SmallVec<u64, N> {
+    fn put(&mut self, value: u64) {
+        self.push(value);
+    }
+    fn total(&self) -> u64 {
+        self.iter().sum()
+    }
+    fn cap(&self) -> usize {
+        self.capacity()
+    }
+}
+
+// 长度 0..=7 的短向量，每个建完就丢掉
+fn short<V: Push>(make: impl Fn() -> V) -> u64 {
+    let mut total = 0;
+    for i in 0..SHORT_VECS {
+        let mut vec = make();
+        for j in 0..i % 8 {
+            vec.put(j);
+        }
+        total += vec.total();
+    }
+    total
+}
+
+fn long<V: Push>(make: impl Fn() -> V) -> (u64, usize) {
+    let mut vec = make();
+    for i in 0..LONG_LEN {
+        vec.put(i);
+    }
+    (vec.total(), vec.cap())
+}
+
+fn main() {
+    println!("{SHORT_VECS} short vectors (len 0..=7), {ROUNDS} rounds");
+    let expected = BENCH.run("Vec", || short(Vec::new));
+    assert_eq!(
+        BENCH.run("Vec::with_capacity(8)", || short(|| Vec::with_capacity(8))),
+        expected
+    );
+    assert_eq!(
+        BENCH.run("SmallVec<_, 4>", || short(SmallVec::<u64, 4>::new)),
+        expected
+    );
+    assert_eq!(
+        BENCH.run("SmallVec<_, 8>", || short(SmallVec::<u64, 8>::new)),
+        expected
+    );
+
+    println!("one vector, {LONG_LEN} pushes, {ROUNDS} rounds");
+    let (expected, cap) = BENCH.run("Vec", || long(Vec::new));
+    println!("    final capacity {cap}");
+    for (label, growth) in [
+        ("SmallVec x2", Growth::DOUBLE),
+        ("SmallVec x1.5", Growth::ONE_AND_HALF),
+        ("SmallVec x4", Growth::new(4, 1)),
+    ] {
+        let (total, cap) = BENCH.run(label, || {
+            long(|| SmallVec::<u64, 8>::new().growth_factor(growth))
+        });
+        assert_eq!(total, expected);
+        println!("    final capacity {cap}");
+    }
+}
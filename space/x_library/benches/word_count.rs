@@ -0,0 +1,123 @@
+// 多线程统计仓库里所有 .md 文件的词频，比较三种做法：
+// - 所有线程共用一个 Mutex<HashMap>
+// - 分片的 CounterMap，每个词直接 increment
+// - 每个线程先在自己的 HashMap 里计数，最后 merge 进 CounterMap
+//
+// cargo bench -p x_library --bench word_count
+
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use x_library::counter::CounterMap;
+
+const ROUNDS: u32 = 20;
+// 文本不多，复制几份让每轮有足够的工作量
+const COPIES: usize = 8;
+
+fn load_markdown() -> Vec<String> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let mut texts = Vec::new();
+    for entry in std::fs::read_dir(root).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|e| e == "md") {
+            texts.push(std::fs::read_to_string(path).unwrap());
+        }
+    }
+    texts
+}
+
+// 尽量均分给 threads 个线程；输入为空时块大小是 0，chunks(0) 会 panic，所以至少取 1
+fn split<'a>(lines: &'a [&'a str], threads: usize) -> std::slice::Chunks<'a, &'a str> {
+    lines.chunks(lines.len().div_ceil(threads).max(1))
+}
+
+fn mutex_map(lines: &[&str], threads: usize) -> usize {
+    let counts = Mutex::new(HashMap::<&str, u64>::new());
+    thread::scope(|s| {
+        for part in split(lines, threads) {
+            let counts = &counts;
+            s.spawn(move || {
+                for word in part.iter().flat_map(|l| l.split_whitespace()) {
+                    *counts.lock().unwrap().entry(word).or_insert(0) += 1;
+                }
+            });
+        }
+    });
+    counts.into_inner().unwrap().len()
+}
+
+fn sharded(lines: &[&str], threads: usize) -> usize {
+    let counts = CounterMap::<String>::new();
+    thread::scope(|s| {
+        for part in split(lines, threads) {
+            let counts = &counts;
+            s.spawn(move || {
+                for word in part.iter().flat_map(|l| l.split_whitespace()) {
+                    counts.increment(word);
+                }
+            });
+        }
+    });
+    counts.len()
+}
+
+fn local_then_merge(lines: &[&str], threads: usize) -> usize {
+    let counts = CounterMap::<String>::new();
+    thread::scope(|s| {
+        for part in split(lines, threads) {
+            let counts = &counts;
+            s.spawn(move || {
+                let mut local = HashMap::<String, u64>::new();
+                for word in part.iter().flat_map(|l| l.split_whitespace()) {
+                    match local.get_mut(word) {
+                        Some(n) => *n += 1,
+                        None => {
+                            local.insert(word.to_string(), 1);
+                        }
+                    }
+                }
+                counts.merge(local);
+            });
+        }
+    });
+    counts.len()
+}
+
+fn measure(name: &str, expected: usize, mut round: impl FnMut() -> usize) {
+    // 预热一轮，顺便确认几种做法统计出的词数一致
+    assert_eq!(round(), expected);
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(round());
+    }
+    println!("{name:<20} {:>10.2?}/round", start.elapsed() / ROUNDS);
+}
+
+fn main() {
+    let texts = load_markdown();
+    let lines: Vec<&str> = texts
+        .iter()
+        .flat_map(|t| t.lines())
+        .cycle()
+        .take(texts.iter().map(|t| t.lines().count()).sum::<usize>() * COPIES)
+        .collect();
+    let words: usize = lines.iter().map(|l| l.split_whitespace().count()).sum();
+    let threads = thread::available_parallelism()
+        .map_or(4, |n| n.get())
+        .max(2);
+    let distinct = mutex_map(&lines, 1);
+    println!(
+        "{} .md files, {words} words ({distinct} distinct), {threads} threads, {ROUNDS} rounds",
+        texts.len()
+    );
+
+    measure("Mutex<HashMap>", distinct, || mutex_map(&lines, threads));
+    measure("sharded CounterMap", distinct, || sharded(&lines, threads));
+    measure("local + merge", distinct, || {
+        local_then_merge(&lines, threads)
+    });
+}
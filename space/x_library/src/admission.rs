@@ -0,0 +1,716 @@
+// 连接准入控制：给 tokio / async-std 的 TCP 服务器加上限流和配额
+//
+// 两个 echo 服务器对每个连接都直接 spawn，没有任何限制。这里把限制拆成三层：
+// - 每个对端 IP 一个令牌桶（token bucket），限制建连速率，允许一定的突发
+// - 全局信号量，限制同时在线的连接数，permit 随连接任务结束而 drop；满员时先拒绝，不消耗令牌
+// - 每个连接的空闲超时，读或写任一方向卡住超过一定时间就断开，两个方向分别计时
+// 被拒绝的连接会先收到一行 `ERR ...\n` 再被关闭。
+//
+// 判定逻辑和运行时无关；`serve_tokio` / `serve_async_std` 只是两个薄适配层。
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+// 可注入的时钟，测试里用 ManualClock 手动拨动时间
+pub trait Clock: Send + Sync {
+    // 从某个固定起点开始经过的时间
+    fn now(&self) -> Duration;
+}
+
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_sec: f64,
+    tokens: f64,
+    last: Duration,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, refill_per_sec: f64, now: Duration) -> Self {
+        TokenBucket {
+            capacity: capacity as f64,
+            refill_per_sec,
+            tokens: capacity as f64,
+            last: now,
+        }
+    }
+
+    fn refill(&mut self, now: Duration) {
+        let elapsed = now.saturating_sub(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last = now;
+    }
+
+    pub fn try_take(&mut self, now: Duration) -> bool {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    // 桶已经装满，说明这个 IP 很久没来了，可以回收
+    fn is_full(&mut self, now: Duration) -> bool {
+        self.refill(now);
+        self.tokens >= self.capacity
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AdmissionConfig {
+    // 每个 IP 允许的突发连接数
+    pub burst: u32,
+    // 每个 IP 每秒补充的令牌数
+    pub refill_per_sec: f64,
+    pub max_connections: usize,
+    pub idle_timeout: Duration,
+}
+
+impl Default for AdmissionConfig {
+    fn default() -> Self {
+        AdmissionConfig {
+            burst: 10,
+            refill_per_sec: 5.0,
+            max_connections: 256,
+            idle_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    RateLimited,
+    TooManyConnections,
+}
+
+impl Rejection {
+    // 拒绝时写给客户端的那一行
+    pub fn line(self) -> &'static [u8] {
+        match self {
+            Rejection::RateLimited => b"ERR rate limited\n",
+            Rejection::TooManyConnections => b"ERR too many connections\n",
+        }
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rejection::RateLimited => write!(f, "rate limited"),
+            Rejection::TooManyConnections => write!(f, "too many connections"),
+        }
+    }
+}
+
+impl std::error::Error for Rejection {}
+
+// 连接存活期间一直持有；drop 时把名额还给信号量
+#[derive(Debug)]
+pub struct ConnectionPermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+// 桶的数量超过这个值时顺手清理一次已经装满的桶
+const PRUNE_THRESHOLD: usize = 1024;
+
+pub struct AdmissionControl {
+    config: AdmissionConfig,
+    clock: Arc<dyn Clock>,
+    buckets: Mutex<HashMap<IpAddr, TokenBucket>>,
+    // tokio::sync::Semaphore 不依赖 tokio 运行时，async-std 下也能用
+    connections: Arc<Semaphore>,
+}
+
+impl AdmissionControl {
+    pub fn new(config: AdmissionConfig) -> Self {
+        Self::with_clock(config, Arc::new(SystemClock::default()))
+    }
+
+    pub fn with_clock(config: AdmissionConfig, clock: Arc<dyn Clock>) -> Self {
+        AdmissionControl {
+            config,
+            clock,
+            buckets: Mutex::new(HashMap::new()),
+            connections: Arc::new(Semaphore::new(config.max_connections)),
+        }
+    }
+
+    pub fn config(&self) -> &AdmissionConfig {
+        &self.config
+    }
+
+    pub fn active_connections(&self) -> usize {
+        self.config.max_connections - self.connections.available_permits()
+    }
+
+    pub fn admit(&self, peer: IpAddr) -> Result<ConnectionPermit, Rejection> {
+        // 先看连接数：因为满员被拒的连接不应该再扣掉这个 IP 的令牌
+        let permit = Arc::clone(&self.connections)
+            .try_acquire_owned()
+            .map_err(|_| Rejection::TooManyConnections)?;
+        let now = self.clock.now();
+        {
+            let mut buckets = self.buckets.lock().unwrap();
+            if buckets.len() > PRUNE_THRESHOLD {
+                buckets.retain(|_, bucket| !bucket.is_full(now));
+            }
+            let bucket = buckets.entry(peer).or_insert_with(|| {
+                TokenBucket::new(self.config.burst, self.config.refill_per_sec, now)
+            });
+            if !bucket.try_take(now) {
+                return Err(Rejection::RateLimited);
+            }
+        }
+        Ok(ConnectionPermit { _permit: permit })
+    }
+}
+
+type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+// 空闲超时包装：读或写在 timeout 内没有任何进展就返回 TimedOut。
+// 读和写各有一个计时器，一个方向上的进展不会让另一个方向的等待续命。
+// 计时器由 `sleeper` 创建，所以同一个包装既能跑在 tokio 下也能跑在 async-std 下
+pub struct IdleTimeout<S> {
+    inner: S,
+    timeout: Duration,
+    sleeper: fn(Duration) -> Sleep,
+    read_timer: Option<Sleep>,
+    write_timer: Option<Sleep>,
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Read,
+    Write,
+}
+
+impl<S> IdleTimeout<S> {
+    pub fn new(inner: S, timeout: Duration, sleeper: fn(Duration) -> Sleep) -> Self {
+        IdleTimeout {
+            inner,
+            timeout,
+            sleeper,
+            read_timer: None,
+            write_timer: None,
+        }
+    }
+
+    pub fn tokio(inner: S, timeout: Duration) -> Self {
+        Self::new(inner, timeout, |d| Box::pin(tokio::time::sleep(d)))
+    }
+
+    pub fn async_std(inner: S, timeout: Duration) -> Self {
+        Self::new(inner, timeout, |d| Box::pin(async_std::task::sleep(d)))
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    // 内部 IO 的 poll 结果经过这里：有进展就重置计时器，挂起时检查是否已超时
+    fn track<T>(
+        &mut self,
+        direction: Direction,
+        cx: &mut Context<'_>,
+        poll: Poll<io::Result<T>>,
+    ) -> Poll<io::Result<T>> {
+        let (timeout, sleeper) = (self.timeout, self.sleeper);
+        let slot = match direction {
+            Direction::Read => &mut self.read_timer,
+            Direction::Write => &mut self.write_timer,
+        };
+        match poll {
+            Poll::Ready(result) => {
+                *slot = None;
+                Poll::Ready(result)
+            }
+            Poll::Pending => {
+                let timer = slot.get_or_insert_with(|| sleeper(timeout));
+                match timer.as_mut().poll(cx) {
+                    Poll::Ready(()) => {
+                        *slot = None;
+                        Poll::Ready(Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "connection idle timeout",
+                        )))
+                    }
+                    Poll::Pending => Poll::Pending,
+                }
+            }
+        }
+    }
+}
+
+impl<S: tokio::io::AsyncRead + Unpin> tokio::io::AsyncRead for IdleTimeout<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        self.track(Direction::Read, cx, poll)
+    }
+}
+
+impl<S: tokio::io::AsyncWrite + Unpin> tokio::io::AsyncWrite for IdleTimeout<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        self.track(Direction::Write, cx, poll)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let poll = Pin::new(&mut self.inner).poll_flush(cx);
+        self.track(Direction::Write, cx, poll)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+impl<S: futures::io::AsyncRead + Unpin> futures::io::AsyncRead for IdleTimeout<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        self.track(Direction::Read, cx, poll)
+    }
+}
+
+impl<S: futures::io::AsyncWrite + Unpin> futures::io::AsyncWrite for IdleTimeout<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        self.track(Direction::Write, cx, poll)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let poll = Pin::new(&mut self.inner).poll_flush(cx);
+        self.track(Direction::Write, cx, poll)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+// 准入之后的单个连接：持有 permit 直到 handler 结束
+pub async fn handle_tokio<S, H, Fut>(
+    mut stream: S,
+    peer: SocketAddr,
+    control: &AdmissionControl,
+    handler: H,
+) -> io::Result<()>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    H: FnOnce(IdleTimeout<S>, SocketAddr) -> Fut,
+    Fut: Future<Output = io::Result<()>>,
+{
+    use tokio::io::AsyncWriteExt;
+
+    match control.admit(peer.ip()) {
+        Ok(permit) => {
+            let stream = IdleTimeout::tokio(stream, control.config().idle_timeout);
+            let result = handler(stream, peer).await;
+            drop(permit);
+            result
+        }
+        Err(rejection) => {
+            stream.write_all(rejection.line()).await?;
+            stream.shutdown().await
+        }
+    }
+}
+
+pub async fn serve_tokio<H, Fut>(
+    listener: tokio::net::TcpListener,
+    control: Arc<AdmissionControl>,
+    handler: H,
+) -> io::Result<()>
+where
+    H: Fn(IdleTimeout<tokio::net::TcpStream>, SocketAddr) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = io::Result<()>> + Send,
+{
+    loop {
+        let (socket, peer) = listener.accept().await?;
+        let control = Arc::clone(&control);
+        let handler = handler.clone();
+        tokio::spawn(async move {
+            let _ = handle_tokio(socket, peer, &control, handler).await;
+        });
+    }
+}
+
+pub async fn handle_async_std<S, H, Fut>(
+    mut stream: S,
+    peer: SocketAddr,
+    control: &AdmissionControl,
+    handler: H,
+) -> io::Result<()>
+where
+    S: futures::io::AsyncRead + futures::io::AsyncWrite + Unpin,
+    H: FnOnce(IdleTimeout<S>, SocketAddr) -> Fut,
+    Fut: Future<Output = io::Result<()>>,
+{
+    use futures::io::AsyncWriteExt;
+
+    match control.admit(peer.ip()) {
+        Ok(permit) => {
+            let stream = IdleTimeout::async_std(stream, control.config().idle_timeout);
+            let result = handler(stream, peer).await;
+            drop(permit);
+            result
+        }
+        Err(rejection) => {
+            stream.write_all(rejection.line()).await?;
+            stream.close().await
+        }
+    }
+}
+
+pub async fn serve_async_std<H, Fut>(
+    listener: async_std::net::TcpListener,
+    control: Arc<AdmissionControl>,
+    handler: H,
+) -> io::Result<()>
+where
+    H: Fn(IdleTimeout<async_std::net::TcpStream>, SocketAddr) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = io::Result<()>> + Send,
+{
+    loop {
+        let (socket, peer) = listener.accept().await?;
+        let control = Arc::clone(&control);
+        let handler = handler.clone();
+        async_std::task::spawn(async move {
+            let _ = handle_async_std(socket, peer, &control, handler).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const A: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    const B: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+    fn control(config: AdmissionConfig) -> (AdmissionControl, ManualClock) {
+        let clock = ManualClock::default();
+        (
+            AdmissionControl::with_clock(config, Arc::new(clock.clone())),
+            clock,
+        )
+    }
+
+    #[test]
+    fn token_bucket_per_ip() {
+        let (control, clock) = control(AdmissionConfig {
+            burst: 3,
+            refill_per_sec: 2.0,
+            ..AdmissionConfig::default()
+        });
+
+        let mut held = Vec::new();
+        for _ in 0..3 {
+            held.push(control.admit(A).unwrap());
+        }
+        assert_eq!(control.admit(A).unwrap_err(), Rejection::RateLimited);
+        // 另一个 IP 有自己的桶
+        held.push(control.admit(B).unwrap());
+
+        clock.advance(Duration::from_millis(499));
+        assert_eq!(control.admit(A).unwrap_err(), Rejection::RateLimited);
+        clock.advance(Duration::from_millis(1));
+        held.push(control.admit(A).unwrap());
+        assert_eq!(control.admit(A).unwrap_err(), Rejection::RateLimited);
+
+        // 长时间不来，桶最多攒满 burst 个
+        clock.advance(Duration::from_secs(60));
+        for _ in 0..3 {
+            held.push(control.admit(A).unwrap());
+        }
+        assert_eq!(control.admit(A).unwrap_err(), Rejection::RateLimited);
+    }
+
+    #[test]
+    fn global_connection_quota() {
+        let (control, _) = control(AdmissionConfig {
+            max_connections: 2,
+            ..AdmissionConfig::default()
+        });
+        let first = control.admit(A).unwrap();
+        let _second = control.admit(B).unwrap();
+        assert_eq!(control.active_connections(), 2);
+        assert_eq!(control.admit(A).unwrap_err(), Rejection::TooManyConnections);
+
+        drop(first);
+        assert_eq!(control.active_connections(), 1);
+        control.admit(A).unwrap();
+    }
+
+    #[test]
+    fn full_server_does_not_spend_tokens() {
+        let (control, _) = control(AdmissionConfig {
+            max_connections: 1,
+            burst: 2,
+            refill_per_sec: 0.0,
+            ..AdmissionConfig::default()
+        });
+        let first = control.admit(A).unwrap();
+        for _ in 0..5 {
+            assert_eq!(control.admit(A).unwrap_err(), Rejection::TooManyConnections);
+        }
+        drop(first);
+        // 桶里还剩一个令牌
+        let _second = control.admit(A).unwrap();
+    }
+
+    #[test]
+    fn idle_buckets_are_pruned() {
+        let (control, clock) = control(AdmissionConfig::default());
+        for i in 0..=PRUNE_THRESHOLD as u32 {
+            drop(control.admit(IpAddr::V4(Ipv4Addr::from(i))).unwrap());
+        }
+        clock.advance(Duration::from_secs(60));
+        drop(control.admit(A).unwrap());
+        assert_eq!(control.buckets.lock().unwrap().len(), 1);
+    }
+
+    async fn echo<S>(mut stream: IdleTimeout<S>, _: SocketAddr) -> io::Result<()>
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        let mut buf = [0; 1024];
+        loop {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                return Ok(());
+            }
+            stream.write_all(&buf[..n]).await?;
+        }
+    }
+
+    fn peer(ip: IpAddr) -> SocketAddr {
+        SocketAddr::new(ip, 40000)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_timeout_resets_on_activity() {
+        let (control, _) = control(AdmissionConfig {
+            idle_timeout: Duration::from_secs(10),
+            ..AdmissionConfig::default()
+        });
+        let (mut client, server) = tokio::io::duplex(64);
+        let start = tokio::time::Instant::now();
+        let client_side = async move {
+            for _ in 0..3 {
+                tokio::time::sleep(Duration::from_secs(9)).await;
+                client.write_all(b"hi").await.unwrap();
+                let mut buf = [0; 2];
+                client.read_exact(&mut buf).await.unwrap();
+            }
+            client
+        };
+        let (result, _client) =
+            tokio::join!(handle_tokio(server, peer(A), &control, echo), client_side);
+
+        // 三次间隔 9 秒的交互都没有超时，最后一次之后空闲 10 秒才断开
+        let err = result.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(start.elapsed(), Duration::from_secs(37));
+        assert_eq!(control.active_connections(), 0);
+    }
+
+    // 一直在写不会让卡住的读免于超时
+    #[tokio::test(start_paused = true)]
+    async fn read_and_write_time_out_separately() {
+        let (_client, server) = tokio::io::duplex(64);
+        let stream = IdleTimeout::tokio(server, Duration::from_secs(10));
+        let (mut reader, mut writer) = tokio::io::split(stream);
+        let start = tokio::time::Instant::now();
+        let reading = async {
+            let mut buf = [0; 1];
+            let result = reader.read(&mut buf).await;
+            (result, start.elapsed())
+        };
+        let writing = async {
+            for _ in 0..5 {
+                tokio::time::sleep(Duration::from_secs(4)).await;
+                writer.write_all(b"x").await.unwrap();
+            }
+        };
+        let ((result, elapsed), ()) = tokio::time::timeout(Duration::from_secs(60), async {
+            tokio::join!(reading, writing)
+        })
+        .await
+        .unwrap();
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::TimedOut);
+        assert_eq!(elapsed, Duration::from_secs(10));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rejected_connection_gets_error_line() {
+        let (control, _) = control(AdmissionConfig {
+            burst: 1,
+            refill_per_sec: 0.0,
+            ..AdmissionConfig::default()
+        });
+        control.admit(A).unwrap();
+
+        let (mut client, server) = tokio::io::duplex(64);
+        handle_tokio(server, peer(A), &control, echo).await.unwrap();
+        let mut reply = String::new();
+        client.read_to_string(&mut reply).await.unwrap();
+        assert_eq!(reply, "ERR rate limited\n");
+    }
+
+    #[tokio::test]
+    async fn tokio_listener_enforces_quota() {
+        let control = Arc::new(AdmissionControl::new(AdmissionConfig {
+            max_connections: 1,
+            ..AdmissionConfig::default()
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_tokio(listener, Arc::clone(&control), echo));
+
+        let mut first = tokio::net::TcpStream::connect(addr).await.unwrap();
+        first.write_all(b"ping").await.unwrap();
+        let mut buf = [0; 4];
+        first.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+
+        let mut second = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let mut reply = String::new();
+        second.read_to_string(&mut reply).await.unwrap();
+        assert_eq!(reply, "ERR too many connections\n");
+
+        // 第一个连接关闭后名额被释放
+        drop(first);
+        while control.active_connections() > 0 {
+            tokio::task::yield_now().await;
+        }
+        let mut third = tokio::net::TcpStream::connect(addr).await.unwrap();
+        third.write_all(b"pong").await.unwrap();
+        third.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"pong");
+    }
+
+    async fn echo_async_std(
+        mut stream: IdleTimeout<async_std::net::TcpStream>,
+        _: SocketAddr,
+    ) -> io::Result<()> {
+        use futures::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut buf = [0; 1024];
+        loop {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                return Ok(());
+            }
+            stream.write_all(&buf[..n]).await?;
+        }
+    }
+
+    #[test]
+    fn async_std_listener_rate_limit_and_idle_timeout() {
+        use futures::io::{AsyncReadExt, AsyncWriteExt};
+
+        async_std::task::block_on(async {
+            let control = Arc::new(AdmissionControl::new(AdmissionConfig {
+                burst: 1,
+                refill_per_sec: 0.0,
+                idle_timeout: Duration::from_millis(100),
+                ..AdmissionConfig::default()
+            }));
+            let listener = async_std::net::TcpListener::bind("127.0.0.1:0")
+                .await
+                .unwrap();
+            let addr = listener.local_addr().unwrap();
+            async_std::task::spawn(serve_async_std(
+                listener,
+                Arc::clone(&control),
+                echo_async_std,
+            ));
+
+            let mut first = async_std::net::TcpStream::connect(addr).await.unwrap();
+            first.write_all(b"ping").await.unwrap();
+            let mut buf = [0; 4];
+            first.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"ping");
+
+            let mut second = async_std::net::TcpStream::connect(addr).await.unwrap();
+            let mut reply = String::new();
+            second.read_to_string(&mut reply).await.unwrap();
+            assert_eq!(reply, "ERR rate limited\n");
+
+            // 什么都不发，空闲超时后服务器主动断开
+            let n = first.read(&mut buf).await.unwrap_or(0);
+            assert_eq!(n, 0);
+            while control.active_connections() > 0 {
+                async_std::task::yield_now().await;
+            }
+        });
+    }
+}
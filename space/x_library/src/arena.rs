@@ -0,0 +1,499 @@
+// 类型化的 bump/arena 分配器，配合 memory_allocation_and_deallocation.md 和 stack_and_heap.md
+//
+// 全局分配器每个 Box::new 都要单独 malloc / free 一次。arena 一次向全局分配器要一大块（chunk），
+// 之后每次分配只是把"指针"往后挪一段（bump），所有对象在 reset 或 arena 被 drop 时一次性释放。
+// `alloc` 返回的 `&mut T` 生命周期绑在 `&self` 上，编译器保证对象不会比 arena 活得更久。
+//
+// 析构函数在 reset / drop 时才运行，那时 T 借用的数据可能早就释放了，编译器看不到类型擦除之后的 drop。
+// 所以 `alloc` 只接受 `T: 'static`；借用别的数据（包括 arena 里其他对象）的类型用 `alloc_borrowed`，
+// 它的析构函数永远不会运行，相当于泄漏，对没有 Drop 的类型（比如链表节点）没有影响。
+//
+// 另外提供一个实现了 GlobalAlloc 的 CountingAlloc，包装 System 统计分配次数和字节数，
+// 可以作为 `#[global_allocator]` 来对比 Box 与 arena 的分配次数。
+
+use std::alloc::{self, GlobalAlloc, Layout, System};
+use std::cell::{Cell, RefCell};
+use std::mem;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const INITIAL_CHUNK: usize = 4 * 1024;
+// chunk 的对齐：足够覆盖常见类型，更严格的对齐在 bump 时再补齐
+const CHUNK_ALIGN: usize = 16;
+
+struct Chunk {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl Chunk {
+    fn new(size: usize) -> Chunk {
+        let layout = Layout::from_size_align(size, CHUNK_ALIGN).expect("chunk too large");
+        // SAFETY: size 总是大于 0
+        let ptr = unsafe { alloc::alloc(layout) };
+        let ptr = NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        Chunk { ptr, layout }
+    }
+
+    fn start(&self) -> usize {
+        self.ptr.as_ptr() as usize
+    }
+
+    fn end(&self) -> usize {
+        self.start() + self.layout.size()
+    }
+}
+
+impl Drop for Chunk {
+    fn drop(&mut self) {
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) };
+    }
+}
+
+// 需要 drop 的对象：地址 + 类型擦除后的 drop 函数
+struct DropEntry {
+    ptr: *mut u8,
+    drop_fn: unsafe fn(*mut u8),
+}
+
+unsafe fn drop_in_place_erased<T>(ptr: *mut u8) {
+    ptr::drop_in_place(ptr as *mut T);
+}
+
+pub struct Arena {
+    chunks: RefCell<Vec<Chunk>>,
+    // 当前 chunk 中下一个空闲字节和 chunk 末尾的地址
+    cursor: Cell<usize>,
+    end: Cell<usize>,
+    drops: RefCell<Vec<DropEntry>>,
+    allocated: Cell<usize>,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Arena {
+    pub fn new() -> Self {
+        Arena {
+            chunks: RefCell::new(Vec::new()),
+            cursor: Cell::new(0),
+            end: Cell::new(0),
+            drops: RefCell::new(Vec::new()),
+            allocated: Cell::new(0),
+        }
+    }
+
+    pub fn with_capacity(bytes: usize) -> Self {
+        let arena = Self::new();
+        if bytes > 0 {
+            arena.push_chunk(bytes);
+        }
+        arena
+    }
+
+    fn push_chunk(&self, size: usize) {
+        let chunk = Chunk::new(size);
+        self.cursor.set(chunk.start());
+        self.end.set(chunk.end());
+        self.chunks.borrow_mut().push(chunk);
+    }
+
+    // 在当前 chunk 里划出 layout 大小的一段；放不下就换一个更大的新 chunk
+    fn bump(&self, layout: Layout) -> NonNull<u8> {
+        if let Some(ptr) = self.try_bump(layout) {
+            return ptr;
+        }
+        // 每次翻倍，保证单次分配一定放得下
+        let last = self
+            .chunks
+            .borrow()
+            .last()
+            .map_or(INITIAL_CHUNK / 2, |c| c.layout.size());
+        let size = (last * 2).max(layout.size() + layout.align());
+        self.push_chunk(size);
+        self.try_bump(layout)
+            .expect("fresh chunk must fit the allocation")
+    }
+
+    fn try_bump(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let start = self.cursor.get().checked_add(layout.align() - 1)? & !(layout.align() - 1);
+        let new_cursor = start.checked_add(layout.size())?;
+        if self.end.get() == 0 || new_cursor > self.end.get() {
+            return None;
+        }
+        self.cursor.set(new_cursor);
+        self.allocated.set(self.allocated.get() + layout.size());
+        // 零大小类型时 start 也是一个对齐的非空地址
+        NonNull::new(start as *mut u8)
+    }
+
+    /// 放进 arena，reset 或 drop 时运行析构函数。
+    ///
+    /// 借用了别的数据的类型不能用这个方法，否则析构函数可能读到已经释放的数据：
+    ///
+    /// ```compile_fail
+    /// use x_library::arena::Arena;
+    ///
+    /// struct Printer<'a>(&'a String);
+    ///
+    /// impl Drop for Printer<'_> {
+    ///     fn drop(&mut self) {
+    ///         println!("{}", self.0);
+    ///     }
+    /// }
+    ///
+    /// let arena = Arena::new();
+    /// let s = String::from("freed");
+    /// arena.alloc(Printer(&s));
+    /// drop(s);
+    /// drop(arena);
+    /// ```
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T: 'static>(&self, value: T) -> &mut T {
+        let ptr = self.write(value);
+        if mem::needs_drop::<T>() {
+            self.drops.borrow_mut().push(DropEntry {
+                ptr: ptr.as_ptr() as *mut u8,
+                drop_fn: drop_in_place_erased::<T>,
+            });
+        }
+        // SAFETY: 这段内存只属于这次分配，直到 arena 被 reset 或 drop，
+        // 而 reset 需要 &mut self，此时不可能还有借出去的引用
+        unsafe { &mut *ptr.as_ptr() }
+    }
+
+    // 可以借用任何活得比返回的引用更久的数据，但析构函数永远不会运行
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_borrowed<T>(&self, value: T) -> &mut T {
+        let ptr = self.write(value);
+        // SAFETY: 同 alloc；不登记 drop，arena 释放时只回收内存
+        unsafe { &mut *ptr.as_ptr() }
+    }
+
+    fn write<T>(&self, value: T) -> NonNull<T> {
+        let ptr = self.bump(Layout::new::<T>()).cast::<T>();
+        // SAFETY: bump 返回的内存对齐、大小都符合 T，且还没被使用
+        unsafe { ptr.as_ptr().write(value) };
+        ptr
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy<T: Copy>(&self, src: &[T]) -> &mut [T] {
+        let layout = Layout::array::<T>(src.len()).expect("slice too large");
+        let ptr = self.bump(layout).cast::<T>();
+        unsafe {
+            ptr::copy_nonoverlapping(src.as_ptr(), ptr.as_ptr(), src.len());
+            std::slice::from_raw_parts_mut(ptr.as_ptr(), src.len())
+        }
+    }
+
+    pub fn alloc_str(&self, s: &str) -> &str {
+        let bytes = self.alloc_slice_copy(s.as_bytes());
+        // SAFETY: 从合法的 &str 原样复制过来
+        unsafe { std::str::from_utf8_unchecked(bytes) }
+    }
+
+    // 通过 alloc 分配出去、尚未释放的字节数（不含对齐填充）
+    pub fn allocated_bytes(&self) -> usize {
+        self.allocated.get()
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.borrow().len()
+    }
+
+    // 按分配的逆序 drop 所有对象（后分配的可能引用先分配的）
+    fn run_drops(&mut self) {
+        let drops = mem::take(self.drops.get_mut());
+        for entry in drops.into_iter().rev() {
+            unsafe { (entry.drop_fn)(entry.ptr) };
+        }
+    }
+
+    // 批量释放所有对象，只保留最大的那个 chunk 以便复用
+    pub fn reset(&mut self) {
+        self.run_drops();
+        let chunks = self.chunks.get_mut();
+        if let Some(largest) = chunks.pop() {
+            chunks.clear();
+            self.cursor.set(largest.start());
+            self.end.set(largest.end());
+            chunks.push(largest);
+        }
+        self.allocated.set(0);
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        self.run_drops();
+    }
+}
+
+// 包装另一个 GlobalAlloc，统计分配次数、释放次数、当前和峰值字节数
+pub struct CountingAlloc<A = System> {
+    inner: A,
+    allocations: AtomicUsize,
+    deallocations: AtomicUsize,
+    live_bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AllocStats {
+    pub allocations: usize,
+    pub deallocations: usize,
+    pub live_bytes: usize,
+    pub peak_bytes: usize,
+}
+
+impl AllocStats {
+    // 两次快照之间发生的分配（峰值取后一次的）
+    pub fn since(self, earlier: AllocStats) -> AllocStats {
+        AllocStats {
+            allocations: self.allocations - earlier.allocations,
+            deallocations: self.deallocations - earlier.deallocations,
+            live_bytes: self.live_bytes.saturating_sub(earlier.live_bytes),
+            peak_bytes: self.peak_bytes,
+        }
+    }
+}
+
+impl CountingAlloc<System> {
+    pub const fn system() -> Self {
+        Self::new(System)
+    }
+}
+
+impl<A> CountingAlloc<A> {
+    pub const fn new(inner: A) -> Self {
+        CountingAlloc {
+            inner,
+            allocations: AtomicUsize::new(0),
+            deallocations: AtomicUsize::new(0),
+            live_bytes: AtomicUsize::new(0),
+            peak_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn stats(&self) -> AllocStats {
+        AllocStats {
+            allocations: self.allocations.load(Ordering::Relaxed),
+            deallocations: self.deallocations.load(Ordering::Relaxed),
+            live_bytes: self.live_bytes.load(Ordering::Relaxed),
+            peak_bytes: self.peak_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record_alloc(&self, size: usize) {
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        let live = self.live_bytes.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak_bytes.fetch_max(live, Ordering::Relaxed);
+    }
+
+    fn record_dealloc(&self, size: usize) {
+        self.deallocations.fetch_add(1, Ordering::Relaxed);
+        self.live_bytes.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAlloc<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            self.record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        self.record_dealloc(layout.size());
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    // realloc 算作一次释放加一次分配
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = self.inner.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            self.record_dealloc(layout.size());
+            self.record_alloc(new_size);
+        }
+        new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    // enum-linked-list.rs 的 List，把 Box<List> 换成 arena 里的引用
+    enum List<'a> {
+        Cons(u32, &'a List<'a>),
+        Nil,
+    }
+
+    impl<'a> List<'a> {
+        fn prepend(&'a self, arena: &'a Arena, elem: u32) -> &'a List<'a> {
+            arena.alloc_borrowed(List::Cons(elem, self))
+        }
+
+        fn len(&self) -> u32 {
+            match *self {
+                List::Cons(_, tail) => 1 + tail.len(),
+                List::Nil => 0,
+            }
+        }
+
+        fn stringify(&self) -> String {
+            match *self {
+                List::Cons(head, tail) => format!("{}, {}", head, tail.stringify()),
+                List::Nil => "Nil".to_string(),
+            }
+        }
+    }
+
+    #[test]
+    fn linked_list_in_arena() {
+        let arena = Arena::new();
+        let mut list: &List = arena.alloc_borrowed(List::Nil);
+        list = list.prepend(&arena, 1);
+        list = list.prepend(&arena, 2);
+        list = list.prepend(&arena, 3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.stringify(), "3, 2, 1, Nil");
+        assert_eq!(arena.chunk_count(), 1);
+    }
+
+    #[test]
+    fn respects_alignment() {
+        let arena = Arena::new();
+        arena.alloc(1u8);
+        let x: &mut u64 = arena.alloc(7);
+        assert_eq!(x as *mut u64 as usize % mem::align_of::<u64>(), 0);
+
+        #[repr(align(64))]
+        struct CacheLine(#[allow(dead_code)] u8);
+        arena.alloc(1u8);
+        let line = arena.alloc(CacheLine(0));
+        assert_eq!(line as *mut CacheLine as usize % 64, 0);
+    }
+
+    #[test]
+    fn grows_by_adding_chunks() {
+        let arena = Arena::new();
+        let refs: Vec<&mut u64> = (0..10_000).map(|i| arena.alloc(i)).collect();
+        assert!(arena.chunk_count() > 1);
+        // 旧 chunk 不会被搬动，先前拿到的引用仍然有效
+        assert!(refs.iter().enumerate().all(|(i, r)| **r == i as u64));
+        assert_eq!(arena.allocated_bytes(), 10_000 * 8);
+
+        // 比当前 chunk 还大的分配
+        let big = arena.alloc([0u8; 64 * 1024]);
+        assert_eq!(big.len(), 64 * 1024);
+    }
+
+    #[test]
+    fn slices_and_strings() {
+        let arena = Arena::new();
+        let s = arena.alloc_str("hello arena");
+        let v = arena.alloc_slice_copy(&[1, 2, 3]);
+        v[0] = 10;
+        assert_eq!(s, "hello arena");
+        assert_eq!(v, [10, 2, 3]);
+        assert!(arena.alloc_slice_copy::<u32>(&[]).is_empty());
+    }
+
+    struct Logged(u32, Rc<RefCell<Vec<u32>>>);
+
+    impl Drop for Logged {
+        fn drop(&mut self) {
+            self.1.borrow_mut().push(self.0);
+        }
+    }
+
+    #[test]
+    fn reset_drops_in_reverse_and_reuses_memory() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut arena = Arena::new();
+        for i in 0..3 {
+            arena.alloc(Logged(i, log.clone()));
+        }
+        arena.alloc(5u32); // 不需要 drop 的类型不登记
+        (0..2000).for_each(|i| {
+            arena.alloc(i as u64);
+        });
+        let chunks_before = arena.chunk_count();
+        assert!(chunks_before > 1);
+
+        arena.reset();
+        assert_eq!(*log.borrow(), [2, 1, 0]);
+        assert_eq!(arena.chunk_count(), 1);
+        assert_eq!(arena.allocated_bytes(), 0);
+
+        arena.alloc(Logged(9, log.clone()));
+        drop(arena);
+        assert_eq!(*log.borrow(), [2, 1, 0, 9]);
+    }
+
+    // 借用外部数据的对象不跑析构函数：arena 比 s 活得久也不会读到释放掉的内存
+    #[test]
+    fn borrowed_values_are_never_dropped() {
+        struct Borrowing<'a>(&'a str, Rc<RefCell<Vec<u32>>>);
+
+        impl Drop for Borrowing<'_> {
+            fn drop(&mut self) {
+                self.1.borrow_mut().push(self.0.len() as u32);
+            }
+        }
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let arena = Arena::new();
+        let s = String::from("short-lived");
+        let value = arena.alloc_borrowed(Borrowing(&s, log.clone()));
+        assert_eq!(value.0, "short-lived");
+        drop(s);
+        drop(arena);
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn zero_sized_types() {
+        let arena = Arena::new();
+        let a: *mut () = arena.alloc(());
+        assert_eq!(a as usize % mem::align_of::<()>(), 0);
+        arena.alloc(());
+        assert_eq!(arena.allocated_bytes(), 0);
+    }
+
+    #[test]
+    fn counting_alloc_counts() {
+        let counter = CountingAlloc::system();
+        let layout = Layout::from_size_align(32, 8).unwrap();
+        unsafe {
+            let p = counter.alloc(layout);
+            let q = counter.alloc_zeroed(layout);
+            assert_eq!(counter.stats().live_bytes, 64);
+            let p = counter.realloc(p, layout, 128);
+            assert_eq!(counter.stats().live_bytes, 160);
+            counter.dealloc(p, Layout::from_size_align(128, 8).unwrap());
+            counter.dealloc(q, layout);
+        }
+        let stats = counter.stats();
+        assert_eq!(stats.allocations, 3);
+        assert_eq!(stats.deallocations, 3);
+        assert_eq!(stats.live_bytes, 0);
+        assert_eq!(stats.peak_bytes, 160);
+    }
+}
@@ -0,0 +1,607 @@
+// 位图实现的整数集合，对应 hashset.rs 里 HashSet<i32> 的集合运算
+//
+// 元素是较小的非负整数，第 i 个元素对应第 i / 64 个 u64 里的第 i % 64 位：
+// - 并、交、差、对称差都是逐个 u64 做 `|`、`&`、`& !`、`^`，一次处理 64 个元素
+// - 迭代按位从低到高，天然有序（HashSet 的输出是乱序的）
+// - `rank(x)` 数比 x 小的元素，整字用 count_ones
+//
+// 内存和最大元素成正比，不适合存很大或很稀疏的值。
+// 不变式：`words` 末尾没有全 0 的字，所以派生的 PartialEq / Hash 只看内容。
+//
+// `to_rle` / `from_rle` 把集合写成游程编码：连续的 1 只记起点和长度，适合成段出现的数据。
+
+use std::fmt;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Bound};
+use std::ops::{RangeBounds, Sub, SubAssign};
+
+const BITS: usize = u64::BITS as usize;
+
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        BitSet { words: Vec::new() }
+    }
+
+    // 预留能放下 0..bits 的空间
+    pub fn with_capacity(bits: usize) -> Self {
+        BitSet {
+            words: Vec::with_capacity(bits.div_ceil(BITS)),
+        }
+    }
+
+    fn trim(&mut self) {
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
+
+    pub fn insert(&mut self, value: usize) -> bool {
+        let (word, bit) = (value / BITS, 1 << (value % BITS));
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        let fresh = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        fresh
+    }
+
+    pub fn remove(&mut self, value: usize) -> bool {
+        let (word, bit) = (value / BITS, 1 << (value % BITS));
+        match self.words.get_mut(word) {
+            Some(w) if *w & bit != 0 => {
+                *w &= !bit;
+                self.trim();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.words
+            .get(value / BITS)
+            .is_some_and(|w| w & (1 << (value % BITS)) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.words.clear();
+    }
+
+    pub fn first(&self) -> Option<usize> {
+        self.iter().next()
+    }
+
+    pub fn last(&self) -> Option<usize> {
+        let top = self.words.last()?;
+        Some((self.words.len() - 1) * BITS + (BITS - 1 - top.leading_zeros() as usize))
+    }
+
+    // 比 value 小的元素个数，value 不必在集合里
+    pub fn rank(&self, value: usize) -> usize {
+        let (word, bit) = (value / BITS, value % BITS);
+        let full: usize = self.words[..word.min(self.words.len())]
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum();
+        let partial = self
+            .words
+            .get(word)
+            .map_or(0, |w| (w & ((1 << bit) - 1)).count_ones() as usize);
+        full + partial
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        self.range(..)
+    }
+
+    pub fn range<R: RangeBounds<usize>>(&self, range: R) -> Iter<'_> {
+        let start = match range.start_bound() {
+            Bound::Included(&s) => s,
+            Bound::Excluded(&s) => s.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&e) => e.saturating_add(1),
+            Bound::Excluded(&e) => e,
+            Bound::Unbounded => usize::MAX,
+        };
+        let word = start / BITS;
+        // 第一个字里把 start 之前的位清掉
+        let current = self
+            .words
+            .get(word)
+            .map_or(0, |w| w & (!0 << (start % BITS)));
+        Iter {
+            words: &self.words,
+            word,
+            current,
+            end,
+        }
+    }
+
+    pub fn union_with(&mut self, other: &BitSet) {
+        if self.words.len() < other.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a |= b;
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        self.words.truncate(other.words.len());
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= b;
+        }
+        self.trim();
+    }
+
+    pub fn difference_with(&mut self, other: &BitSet) {
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= !b;
+        }
+        self.trim();
+    }
+
+    pub fn symmetric_difference_with(&mut self, other: &BitSet) {
+        if self.words.len() < other.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a ^= b;
+        }
+        self.trim();
+    }
+
+    pub fn is_subset(&self, other: &BitSet) -> bool {
+        self.words.len() <= other.words.len()
+            && self
+                .words
+                .iter()
+                .zip(&other.words)
+                .all(|(a, b)| a & !b == 0)
+    }
+
+    pub fn is_superset(&self, other: &BitSet) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &BitSet) -> bool {
+        self.words.iter().zip(&other.words).all(|(a, b)| a & b == 0)
+    }
+
+    // 连续元素组成的区间 [start, end)，按顺序
+    pub fn runs(&self) -> Runs<'_> {
+        Runs { set: self, next: 0 }
+    }
+
+    // 格式：LEB128 变长整数序列，每个游程写两个数：
+    // 距上一个游程结尾的间隔（第一个游程是起点本身）和游程长度
+    pub fn to_rle(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut prev_end = 0;
+        for (start, end) in self.runs() {
+            write_varint(&mut out, start - prev_end);
+            write_varint(&mut out, end - start);
+            prev_end = end;
+        }
+        out
+    }
+
+    // max_bits 限制解码出的最大元素，防止几个字节的输入要求分配巨大的位图
+    pub fn from_rle(bytes: &[u8], max_bits: usize) -> Result<BitSet, RleError> {
+        let mut set = BitSet::new();
+        let mut pos = 0;
+        let mut prev_end = 0usize;
+        while pos < bytes.len() {
+            let gap = read_varint(bytes, &mut pos)?;
+            let len = read_varint(bytes, &mut pos)?;
+            if len == 0 {
+                return Err(RleError::EmptyRun);
+            }
+            // 除了第一个游程，间隔为 0 说明两个游程本该合并，编码不规范
+            if gap == 0 && prev_end != 0 {
+                return Err(RleError::AdjacentRuns);
+            }
+            let start = prev_end.checked_add(gap).ok_or(RleError::TooLarge)?;
+            let end = start.checked_add(len).ok_or(RleError::TooLarge)?;
+            if end > max_bits {
+                return Err(RleError::TooLarge);
+            }
+            set.insert_range(start, end);
+            prev_end = end;
+        }
+        Ok(set)
+    }
+
+    // 整字地填 [start, end)
+    fn insert_range(&mut self, start: usize, end: usize) {
+        if start >= end {
+            return;
+        }
+        let last = (end - 1) / BITS;
+        if last >= self.words.len() {
+            self.words.resize(last + 1, 0);
+        }
+        for word in start / BITS..=last {
+            let lo = if word == start / BITS {
+                start % BITS
+            } else {
+                0
+            };
+            let hi = if word == last {
+                (end - 1) % BITS
+            } else {
+                BITS - 1
+            };
+            self.words[word] |= (!0 >> (BITS - 1 - hi)) & (!0 << lo);
+        }
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut n: usize) {
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<usize, RleError> {
+    let mut n = 0usize;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos).ok_or(RleError::Truncated)?;
+        *pos += 1;
+        if shift >= usize::BITS || (byte & 0x7f) as usize > usize::MAX >> shift {
+            return Err(RleError::TooLarge);
+        }
+        n |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(n);
+        }
+        shift += 7;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RleError {
+    // 变长整数读到一半输入就结束了
+    Truncated,
+    EmptyRun,
+    AdjacentRuns,
+    // 数值溢出或者超过了 max_bits
+    TooLarge,
+}
+
+impl fmt::Display for RleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RleError::Truncated => write!(f, "input ends in the middle of a number"),
+            RleError::EmptyRun => write!(f, "run of length zero"),
+            RleError::AdjacentRuns => write!(f, "adjacent runs should have been merged"),
+            RleError::TooLarge => write!(f, "value exceeds the allowed range"),
+        }
+    }
+}
+
+impl std::error::Error for RleError {}
+
+impl fmt::Debug for BitSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+pub struct Iter<'a> {
+    words: &'a [u64],
+    word: usize,
+    // 当前字里还没输出的位
+    current: u64,
+    end: usize,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.current == 0 {
+            self.word += 1;
+            self.current = *self.words.get(self.word)?;
+        }
+        let value = self.word * BITS + self.current.trailing_zeros() as usize;
+        if value >= self.end {
+            self.words = &[];
+            self.current = 0;
+            return None;
+        }
+        // 清掉最低的 1
+        self.current &= self.current - 1;
+        Some(value)
+    }
+}
+
+pub struct Runs<'a> {
+    set: &'a BitSet,
+    next: usize,
+}
+
+impl Iterator for Runs<'_> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        let words = &self.set.words;
+        // 找下一个 1
+        let mut word = self.next / BITS;
+        let mut bits = words.get(word)? & (!0 << (self.next % BITS));
+        while bits == 0 {
+            word += 1;
+            bits = *words.get(word)?;
+        }
+        let start = word * BITS + bits.trailing_zeros() as usize;
+        // 再找它之后的第一个 0
+        let mut zeros = !words[word] & (!0 << (start % BITS));
+        while zeros == 0 {
+            word += 1;
+            zeros = !words.get(word).copied().unwrap_or(0);
+        }
+        let end = word * BITS + zeros.trailing_zeros() as usize;
+        self.next = end;
+        Some((start, end))
+    }
+}
+
+impl<'a> IntoIterator for &'a BitSet {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl Extend<usize> for BitSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a> Extend<&'a usize> for BitSet {
+    fn extend<I: IntoIterator<Item = &'a usize>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = BitSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+pub struct IntoIter {
+    words: std::vec::IntoIter<u64>,
+    // 当前字的下标和还没输出的位
+    word: usize,
+    current: u64,
+}
+
+impl Iterator for IntoIter {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.current == 0 {
+            self.current = self.words.next()?;
+            self.word += 1;
+        }
+        let value = (self.word - 1) * BITS + self.current.trailing_zeros() as usize;
+        self.current &= self.current - 1;
+        Some(value)
+    }
+}
+
+impl IntoIterator for BitSet {
+    type Item = usize;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter {
+            words: self.words.into_iter(),
+            word: 0,
+            current: 0,
+        }
+    }
+}
+
+impl<const N: usize> From<[usize; N]> for BitSet {
+    fn from(values: [usize; N]) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+// 和 HashSet / BTreeSet 一样，&a | &b 之类的运算返回新集合
+macro_rules! set_op {
+    ($trait:ident, $method:ident, $assign:ident, $assign_method:ident, $with:ident) => {
+        impl $assign<&BitSet> for BitSet {
+            fn $assign_method(&mut self, rhs: &BitSet) {
+                self.$with(rhs);
+            }
+        }
+
+        impl $trait<&BitSet> for &BitSet {
+            type Output = BitSet;
+
+            fn $method(self, rhs: &BitSet) -> BitSet {
+                let mut out = self.clone();
+                out.$with(rhs);
+                out
+            }
+        }
+    };
+}
+
+set_op!(BitOr, bitor, BitOrAssign, bitor_assign, union_with);
+set_op!(BitAnd, bitand, BitAndAssign, bitand_assign, intersect_with);
+set_op!(Sub, sub, SubAssign, sub_assign, difference_with);
+set_op!(
+    BitXor,
+    bitxor,
+    BitXorAssign,
+    bitxor_assign,
+    symmetric_difference_with
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::lcg;
+    use std::collections::{BTreeSet, HashSet};
+
+    fn random_set(seed: &mut u64, n: usize, max: usize) -> Vec<usize> {
+        (0..n).map(|_| lcg(seed) as usize % max).collect()
+    }
+
+    #[test]
+    fn hashset_example() {
+        // hashset.rs 的同一组操作
+        let mut a = BitSet::from([1, 2, 3]);
+        let mut b = BitSet::from([2, 3, 4]);
+        let mut ha: HashSet<usize> = [1, 2, 3].into_iter().collect();
+        let mut hb: HashSet<usize> = [2, 3, 4].into_iter().collect();
+
+        assert_eq!(a.insert(4), ha.insert(4));
+        assert!(a.contains(4));
+        assert_eq!(b.insert(4), hb.insert(4));
+        b.insert(5);
+        hb.insert(5);
+
+        let as_hash = |s: &BitSet| s.iter().collect::<HashSet<_>>();
+        assert_eq!(as_hash(&(&a | &b)), &ha | &hb);
+        assert_eq!(as_hash(&(&a - &b)), &ha - &hb);
+        assert_eq!(as_hash(&(&a & &b)), &ha & &hb);
+        assert_eq!(as_hash(&(&a ^ &b)), &ha ^ &hb);
+        // 输出有序
+        assert_eq!((&a | &b).iter().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+        assert_eq!(format!("{:?}", &a ^ &b), "{1, 5}");
+    }
+
+    #[test]
+    fn btreeset_example() {
+        // BTreeSet.rs 的同一组操作
+        let mut set = BitSet::new();
+        let mut std = BTreeSet::new();
+        for x in [3, 1, 4, 1, 2] {
+            assert_eq!(set.insert(x), std.insert(x));
+        }
+        assert_eq!(format!("{:?}", set), format!("{:?}", std));
+        assert_eq!(set.contains(3), std.contains(&3));
+        assert_eq!(set.remove(2), std.remove(&2));
+        assert_eq!(set.remove(2), std.remove(&2));
+        assert!(set.iter().eq(std.iter().copied()));
+        assert!(set.range(1..=3).eq(std.range(1..=3).copied()));
+        assert_eq!(set.len(), std.len());
+        assert_eq!(set.first(), std.first().copied());
+        assert_eq!(set.last(), std.last().copied());
+    }
+
+    #[test]
+    fn random_algebra_matches_std_sets() {
+        let mut seed = 3;
+        for round in 0..50 {
+            // 大小不同的集合，覆盖长短不一的 words
+            let max = 10 + round * 20;
+            let xs = random_set(&mut seed, round * 3, max);
+            let ys = random_set(&mut seed, round * 2, max / 2 + 1);
+            let (a, b): (BitSet, BitSet) =
+                (xs.iter().copied().collect(), ys.iter().copied().collect());
+            let (sa, sb): (BTreeSet<_>, BTreeSet<_>) =
+                (xs.into_iter().collect(), ys.into_iter().collect());
+            let (ha, hb): (HashSet<_>, HashSet<_>) =
+                (sa.iter().copied().collect(), sb.iter().copied().collect());
+
+            assert!((&a | &b).iter().eq(sa.union(&sb).copied()));
+            assert!((&a & &b).iter().eq(sa.intersection(&sb).copied()));
+            assert!((&a - &b).iter().eq(sa.difference(&sb).copied()));
+            assert!((&a ^ &b).iter().eq(sa.symmetric_difference(&sb).copied()));
+            assert_eq!((&a ^ &b).iter().collect::<HashSet<_>>(), &ha ^ &hb);
+            assert_eq!(a.is_subset(&b), sa.is_subset(&sb));
+            assert_eq!(a.is_disjoint(&b), sa.is_disjoint(&sb));
+            assert!((&a & &b).is_subset(&a));
+            assert_eq!(a.len(), sa.len());
+
+            for probe in (0..max + 70).step_by(7) {
+                assert_eq!(a.rank(probe), sa.range(..probe).count());
+                assert!(a
+                    .range(probe..probe + 40)
+                    .eq(sa.range(probe..probe + 40).copied()));
+            }
+        }
+    }
+
+    #[test]
+    fn equality_ignores_trailing_words() {
+        let mut a = BitSet::from([1, 500]);
+        a.remove(500);
+        assert_eq!(a, BitSet::from([1]));
+        let mut b = BitSet::from([1, 2, 300]);
+        b -= &BitSet::from([300]);
+        b &= &BitSet::from([1, 2, 3]);
+        assert_eq!(b, BitSet::from([1, 2]));
+        assert_eq!(b.last(), Some(2));
+    }
+
+    #[test]
+    fn rle_round_trip() {
+        let mut set = BitSet::new();
+        set.insert_range(0, 3);
+        set.insert_range(60, 200);
+        set.insert_range(1000, 1001);
+        assert_eq!(
+            set.runs().collect::<Vec<_>>(),
+            [(0, 3), (60, 200), (1000, 1001)]
+        );
+
+        let bytes = set.to_rle();
+        // 144 个元素编码成 8 个字节，其中 140 和 800 各占两个字节
+        assert_eq!(bytes, [0, 3, 57, 140, 1, 160, 6, 1]);
+        assert_eq!(BitSet::from_rle(&bytes, 2000), Ok(set));
+
+        let mut seed = 11;
+        for _ in 0..20 {
+            let set: BitSet = random_set(&mut seed, 300, 1000).into_iter().collect();
+            assert_eq!(BitSet::from_rle(&set.to_rle(), 1000).unwrap(), set);
+        }
+        assert_eq!(BitSet::from_rle(&[], 0), Ok(BitSet::new()));
+    }
+
+    #[test]
+    fn rle_rejects_bad_input() {
+        assert_eq!(BitSet::from_rle(&[0x80], 100), Err(RleError::Truncated));
+        assert_eq!(BitSet::from_rle(&[5], 100), Err(RleError::Truncated));
+        assert_eq!(BitSet::from_rle(&[5, 0], 100), Err(RleError::EmptyRun));
+        assert_eq!(
+            BitSet::from_rle(&[0, 2, 0, 2], 100),
+            Err(RleError::AdjacentRuns)
+        );
+        assert_eq!(BitSet::from_rle(&[90, 20], 100), Err(RleError::TooLarge));
+        assert_eq!(
+            BitSet::from_rle(&[0xff; 11], usize::MAX),
+            Err(RleError::TooLarge)
+        );
+    }
+}
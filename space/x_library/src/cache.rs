@@ -0,0 +1,791 @@
+// 几种有界缓存：把 HashMap.md 的哈希表和"顺序"结合起来，决定满了以后淘汰谁
+//
+// - `LruCache`：淘汰最久没被访问的。HashMap 存 key -> 节点下标，节点放在 Vec 里，
+//   用下标串成双向链表（侵入式：prev/next 就在节点里），访问时把节点挪到表头，get / insert 都是 O(1)
+// - `LfuCache`：淘汰访问次数最少的，次数相同时淘汰最久没访问的；顺序用 BTreeMap<(次数, 时间戳), K> 维护，O(log n)
+// - `TtlCache`：每个条目有过期时间，时钟可以注入（admission.rs 的 Clock），测试里用 ManualClock 拨时间
+//
+// 三种缓存都记录命中、未命中、淘汰（TTL 另外记过期）次数。`peek` 只看不算访问，也不计入统计。
+
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::admission::{Clock, SystemClock};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    // 因为容量满了被挤掉的
+    pub evictions: u64,
+    // 因为过期被删掉的，只有 TtlCache 会用到
+    pub expirations: u64,
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+const NIL: usize = usize::MAX;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    prev: usize,
+    next: usize,
+}
+
+pub struct LruCache<K, V> {
+    map: HashMap<K, usize>,
+    // remove 留下的空位是 None，下标记在 free 里复用
+    nodes: Vec<Option<Node<K, V>>>,
+    free: Vec<usize>,
+    // head 是最近访问的，tail 是下一个被淘汰的
+    head: usize,
+    tail: usize,
+    capacity: usize,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "cache capacity must be positive");
+        LruCache {
+            map: HashMap::with_capacity(capacity),
+            nodes: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            capacity,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn node(&self, index: usize) -> &Node<K, V> {
+        self.nodes[index].as_ref().expect("linked node is live")
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut Node<K, V> {
+        self.nodes[index].as_mut().expect("linked node is live")
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let (prev, next) = {
+            let node = self.node(index);
+            (node.prev, node.next)
+        };
+        match prev {
+            NIL => self.head = next,
+            p => self.node_mut(p).next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            n => self.node_mut(n).prev = prev,
+        }
+    }
+
+    fn push_front(&mut self, index: usize) {
+        let head = self.head;
+        {
+            let node = self.node_mut(index);
+            node.prev = NIL;
+            node.next = head;
+        }
+        match head {
+            NIL => self.tail = index,
+            h => self.node_mut(h).prev = index,
+        }
+        self.head = index;
+    }
+
+    fn touch(&mut self, index: usize) {
+        if self.head != index {
+            self.unlink(index);
+            self.push_front(index);
+        }
+    }
+
+    // 命中时把条目标记为最近使用
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(|v| &*v)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some(&index) = self.map.get(key) else {
+            self.stats.misses += 1;
+            return None;
+        };
+        self.stats.hits += 1;
+        self.touch(index);
+        Some(&mut self.node_mut(index).value)
+    }
+
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|&i| &self.node(i).value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    // key 已存在时替换并返回旧值；满了就淘汰表尾，淘汰次数记在 stats 里
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&index) = self.map.get(&key) {
+            self.touch(index);
+            return Some(std::mem::replace(&mut self.node_mut(index).value, value));
+        }
+        if self.map.len() == self.capacity {
+            self.evict();
+        }
+        let node = Node {
+            key: key.clone(),
+            value,
+            prev: NIL,
+            next: NIL,
+        };
+        let index = match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = Some(node);
+                i
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+        self.push_front(index);
+        self.map.insert(key, index);
+        None
+    }
+
+    fn take(&mut self, index: usize) -> (K, V) {
+        self.unlink(index);
+        let node = self.nodes[index].take().expect("linked node is live");
+        self.free.push(index);
+        self.map.remove(&node.key);
+        (node.key, node.value)
+    }
+
+    fn evict(&mut self) {
+        if self.tail != NIL {
+            self.take(self.tail);
+            self.stats.evictions += 1;
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = *self.map.get(key)?;
+        Some(self.take(index).1)
+    }
+
+    // 下一个会被淘汰的条目
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        (self.tail != NIL).then(|| {
+            let node = self.node(self.tail);
+            (&node.key, &node.value)
+        })
+    }
+
+    // 从最近使用到最久未用
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            nodes: &self.nodes,
+            cursor: self.head,
+            remaining: self.len(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.nodes.clear();
+        self.free.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    nodes: &'a [Option<Node<K, V>>],
+    cursor: usize,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.nodes.get(self.cursor)?.as_ref()?;
+        self.cursor = node.next;
+        self.remaining -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<'a, K: Hash + Eq + Clone, V> IntoIterator for &'a LruCache<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+// 缓存没有 FromIterator：容量必须由调用方给出。extend 就是逐个 insert，超出容量照常淘汰
+impl<K: Hash + Eq + Clone, V> Extend<(K, V)> for LruCache<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|(k, v)| {
+            self.insert(k, v);
+        });
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Extend<(K, V)> for LfuCache<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|(k, v)| {
+            self.insert(k, v);
+        });
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Extend<(K, V)> for TtlCache<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|(k, v)| {
+            self.insert(k, v);
+        });
+    }
+}
+
+struct LfuEntry<V> {
+    value: V,
+    count: u64,
+    last_used: u64,
+}
+
+pub struct LfuCache<K, V> {
+    entries: HashMap<K, LfuEntry<V>>,
+    // (访问次数, 最近一次访问的时间戳) -> key，第一个就是要淘汰的
+    order: BTreeMap<(u64, u64), K>,
+    // 逻辑时间戳，每次访问加一
+    tick: u64,
+    capacity: usize,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LfuCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "cache capacity must be positive");
+        LfuCache {
+            entries: HashMap::with_capacity(capacity),
+            order: BTreeMap::new(),
+            tick: 0,
+            capacity,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    // 访问次数，不存在时为 0
+    pub fn frequency<Q>(&self, key: &Q) -> u64
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.get(key).map_or(0, |e| e.count)
+    }
+
+    // 记一次访问：从 order 里按旧位置取出 key，换到新位置
+    fn bump(&mut self, entry_count: u64, entry_last: u64) -> (u64, u64) {
+        self.tick += 1;
+        let key = self
+            .order
+            .remove(&(entry_count, entry_last))
+            .expect("entry is ordered");
+        let position = (entry_count + 1, self.tick);
+        self.order.insert(position, key);
+        position
+    }
+
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some(entry) = self.entries.get(key) else {
+            self.stats.misses += 1;
+            return None;
+        };
+        self.stats.hits += 1;
+        let (count, last_used) = self.bump(entry.count, entry.last_used);
+        let entry = self.entries.get_mut(key).expect("entry exists");
+        entry.count = count;
+        entry.last_used = last_used;
+        Some(&entry.value)
+    }
+
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.get(key).map(|e| &e.value)
+    }
+
+    // 替换已有的值也算一次访问；新 key 从 1 次开始
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(entry) = self.entries.get(&key) {
+            let (count, last_used) = self.bump(entry.count, entry.last_used);
+            let entry = self.entries.get_mut(&key).expect("entry exists");
+            entry.count = count;
+            entry.last_used = last_used;
+            return Some(std::mem::replace(&mut entry.value, value));
+        }
+        if self.entries.len() == self.capacity {
+            if let Some((_, victim)) = self.order.pop_first() {
+                self.entries.remove(&victim);
+                self.stats.evictions += 1;
+            }
+        }
+        self.tick += 1;
+        self.order.insert((1, self.tick), key.clone());
+        self.entries.insert(
+            key,
+            LfuEntry {
+                value,
+                count: 1,
+                last_used: self.tick,
+            },
+        );
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = self.entries.remove(key)?;
+        self.order.remove(&(entry.count, entry.last_used));
+        Some(entry.value)
+    }
+}
+
+struct TtlEntry<V> {
+    value: V,
+    expires_at: Duration,
+    // 区分同一时刻过期的多个条目
+    seq: u64,
+}
+
+pub struct TtlCache<K, V> {
+    entries: HashMap<K, TtlEntry<V>>,
+    // (过期时间, 序号) -> key，按过期先后排列
+    expiry: BTreeMap<(Duration, u64), K>,
+    seq: u64,
+    ttl: Duration,
+    capacity: usize,
+    clock: Arc<dyn Clock>,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> TtlCache<K, V> {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self::with_clock(capacity, ttl, Arc::new(SystemClock::default()))
+    }
+
+    pub fn with_clock(capacity: usize, ttl: Duration, clock: Arc<dyn Clock>) -> Self {
+        assert!(capacity > 0, "cache capacity must be positive");
+        TtlCache {
+            entries: HashMap::with_capacity(capacity),
+            expiry: BTreeMap::new(),
+            seq: 0,
+            ttl,
+            capacity,
+            clock,
+            stats: CacheStats::default(),
+        }
+    }
+
+    // 包括已经过期但还没被清理的条目
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn take<Q>(&mut self, key: &Q) -> Option<TtlEntry<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = self.entries.remove(key)?;
+        self.expiry.remove(&(entry.expires_at, entry.seq));
+        Some(entry)
+    }
+
+    // 过期的条目在被访问到时才删除，算一次未命中
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let now = self.clock.now();
+        match self.entries.get(key) {
+            Some(entry) if entry.expires_at > now => {
+                self.stats.hits += 1;
+                self.entries.get(key).map(|e| &e.value)
+            }
+            Some(_) => {
+                self.take(key);
+                self.stats.expirations += 1;
+                self.stats.misses += 1;
+                None
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    // 剩余的存活时间，已过期或不存在时为 None
+    pub fn time_to_live<Q>(&self, key: &Q) -> Option<Duration>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = self.entries.get(key)?;
+        entry
+            .expires_at
+            .checked_sub(self.clock.now())
+            .filter(|d| !d.is_zero())
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_with_ttl(key, value, self.ttl)
+    }
+
+    // 重新插入会刷新过期时间；只返回还没过期的旧值，替换掉的过期条目计入 expirations。
+    // ttl 大到过期时间溢出（比如 Duration::MAX）时算永不过期
+    pub fn insert_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        let now = self.clock.now();
+        let old = match self.take(&key) {
+            Some(entry) if entry.expires_at > now => Some(entry.value),
+            Some(_) => {
+                self.stats.expirations += 1;
+                None
+            }
+            None => None,
+        };
+        if old.is_none() && self.entries.len() == self.capacity {
+            // 先清过期的，还不够就淘汰最快要过期的
+            if self.purge_expired() == 0 {
+                if let Some((_, victim)) = self.expiry.pop_first() {
+                    self.entries.remove(&victim);
+                    self.stats.evictions += 1;
+                }
+            }
+        }
+        self.seq += 1;
+        let expires_at = now.checked_add(ttl).unwrap_or(Duration::MAX);
+        self.expiry.insert((expires_at, self.seq), key.clone());
+        self.entries.insert(
+            key,
+            TtlEntry {
+                value,
+                expires_at,
+                seq: self.seq,
+            },
+        );
+        old
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.take(key).map(|e| e.value)
+    }
+
+    // 删掉所有已过期的条目，返回删了几个
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.clock.now();
+        let mut purged = 0;
+        while let Some(entry) = self.expiry.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            let key = entry.remove();
+            self.entries.remove(&key);
+            purged += 1;
+        }
+        self.stats.expirations += purged as u64;
+        purged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admission::ManualClock;
+    use crate::test_util::lcg;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn lru_evicts_least_recently_used() {
+        let mut cache = LruCache::new(3);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        cache.insert("c", 3);
+        // 访问 a 之后，最久没用的是 b
+        assert_eq!(cache.get("a"), Some(&1));
+        assert_eq!(cache.peek_lru(), Some((&"b", &2)));
+        cache.insert("d", 4);
+        assert!(!cache.contains_key("b"));
+        assert_eq!(
+            cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+            ["d", "a", "c"]
+        );
+
+        // peek 不改变顺序，也不计入统计
+        assert_eq!(cache.peek("c"), Some(&3));
+        cache.insert("e", 5);
+        assert!(!cache.contains_key("c"));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                evictions: 2,
+                expirations: 0
+            }
+        );
+        assert_eq!(cache.stats().hit_rate(), 0.5);
+    }
+
+    #[test]
+    fn lru_replace_and_remove_reuse_slots() {
+        let mut cache: LruCache<String, u32> = LruCache::new(2);
+        assert_eq!(cache.insert("x".into(), 1), None);
+        assert_eq!(cache.insert("x".into(), 2), Some(1));
+        *cache.get_mut("x").unwrap() += 10;
+        assert_eq!(cache.remove("x"), Some(12));
+        assert!(cache.is_empty());
+        cache.insert("y".into(), 3);
+        cache.insert("z".into(), 4);
+        // remove 空出的节点被复用，没有多分配
+        assert_eq!(cache.nodes.len(), 2);
+        assert_eq!(cache.len(), 2);
+        cache.clear();
+        assert_eq!(cache.iter().count(), 0);
+    }
+
+    #[test]
+    fn lru_matches_naive_model() {
+        // 用 VecDeque 按最近使用排列，线性查找，作为对照
+        let mut cache = LruCache::new(8);
+        let mut model: VecDeque<(u64, u64)> = VecDeque::new();
+        let mut seed = 9u64;
+        for step in 0..5000u64 {
+            let key = lcg(&mut seed) % 16;
+            let position = model.iter().position(|(k, _)| *k == key);
+            if step % 3 == 0 {
+                let hit = position.map(|i| model.remove(i).unwrap().1);
+                assert_eq!(cache.get(&key).copied(), hit);
+                if let Some(v) = hit {
+                    model.push_front((key, v));
+                }
+            } else {
+                let old = position.map(|i| model.remove(i).unwrap().1);
+                if old.is_none() && model.len() == 8 {
+                    model.pop_back();
+                }
+                model.push_front((key, step));
+                assert_eq!(cache.insert(key, step), old);
+            }
+            assert!(cache
+                .iter()
+                .map(|(k, v)| (*k, *v))
+                .eq(model.iter().copied()));
+        }
+    }
+
+    #[test]
+    fn lfu_evicts_least_frequently_used() {
+        let mut cache = LfuCache::new(3);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        cache.insert("c", 3);
+        cache.get("a");
+        cache.get("a");
+        cache.get("c");
+        // b 只被用过一次
+        cache.insert("d", 4);
+        assert_eq!(cache.peek("b"), None);
+        assert_eq!(cache.frequency("a"), 3);
+        // d 和 c 比，d 次数更少
+        cache.insert("e", 5);
+        assert_eq!(cache.peek("d"), None);
+        assert_eq!(cache.peek("c"), Some(&3));
+
+        // e 再访问一次后和 c 都是 2 次，次数相同时淘汰更早访问的 c
+        cache.get("e");
+        cache.insert("f", 6);
+        assert_eq!(cache.peek("c"), None);
+        assert_eq!(cache.remove("e"), Some(5));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.stats().evictions, 3);
+        assert_eq!(cache.stats().hits, 4);
+    }
+
+    fn ttl_cache(capacity: usize, ttl: u64) -> (TtlCache<&'static str, u32>, ManualClock) {
+        let clock = ManualClock::default();
+        let cache =
+            TtlCache::with_clock(capacity, Duration::from_secs(ttl), Arc::new(clock.clone()));
+        (cache, clock)
+    }
+
+    #[test]
+    fn ttl_entries_expire() {
+        let (mut cache, clock) = ttl_cache(10, 10);
+        cache.insert("session", 1);
+        cache.insert_with_ttl("token", 2, Duration::from_secs(3));
+
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(cache.get("token"), Some(&2));
+        assert_eq!(cache.time_to_live("token"), Some(Duration::from_secs(1)));
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(cache.get("token"), None);
+        assert_eq!(cache.time_to_live("token"), None);
+        assert_eq!(cache.get("session"), Some(&1));
+
+        // 重新插入刷新过期时间
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(cache.insert("session", 3), Some(1));
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(cache.get("session"), Some(&3));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(cache.get("session"), None);
+
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 3,
+                misses: 2,
+                evictions: 0,
+                expirations: 2
+            }
+        );
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn ttl_purge_and_capacity() {
+        let (mut cache, clock) = ttl_cache(3, 10);
+        cache.insert_with_ttl("a", 1, Duration::from_secs(1));
+        cache.insert_with_ttl("b", 2, Duration::from_secs(5));
+        cache.insert_with_ttl("c", 3, Duration::from_secs(2));
+
+        // 满了但没有过期的：淘汰最快过期的 a
+        cache.insert("d", 4);
+        assert_eq!(cache.stats().evictions, 1);
+        assert_eq!(cache.len(), 3);
+        assert!(cache.time_to_live("a").is_none());
+
+        // c 过期了，插入时先把它清掉，不算淘汰
+        clock.advance(Duration::from_secs(2));
+        cache.insert("e", 5);
+        assert_eq!(cache.stats().evictions, 1);
+        assert_eq!(cache.stats().expirations, 1);
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(cache.purge_expired(), 3);
+        assert!(cache.is_empty());
+        // 过期了的旧值不会被当成替换结果返回，但算一次过期
+        cache.insert_with_ttl("x", 1, Duration::ZERO);
+        assert_eq!(cache.insert("x", 2), None);
+        assert_eq!(cache.stats().expirations, 5);
+    }
+
+    #[test]
+    fn huge_ttl_never_expires() {
+        let (mut cache, clock) = ttl_cache(3, 10);
+        clock.advance(Duration::from_secs(5));
+        cache.insert_with_ttl("forever", 1, Duration::MAX);
+        clock.advance(Duration::from_secs(u32::MAX as u64));
+        assert_eq!(cache.get("forever"), Some(&1));
+        assert!(cache.time_to_live("forever").is_some());
+        assert_eq!(cache.purge_expired(), 0);
+    }
+}
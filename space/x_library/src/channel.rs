@@ -0,0 +1,744 @@
+// 多生产者、单消费者的通道，和 std::sync::mpsc 用法一致，只用 Mutex + Condvar 实现
+//
+// - `unbounded()`：发送永不阻塞
+// - `bounded(n)`：队列满了发送方等待
+// - `rendezvous()`（即 `bounded(0)`）：发送方一直等到接收方把值取走
+//
+// 错误类型直接用 std::sync::mpsc 里的，方便和 std 的通道互换、对照测试。
+// 所有 Sender 都 drop 后，接收方先取完剩下的值再收到 Disconnected；接收方 drop 后，send 把值原样退回。
+//
+// `select!` 同时等多个接收端：每个通道登记等待者的信号，有新值或断开时通知，等待者醒来后再逐个 try_recv。
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::sync::mpsc::{RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+struct State<T> {
+    queue: VecDeque<T>,
+    // None 表示无界；Some(0) 是 rendezvous
+    cap: Option<usize>,
+    senders: usize,
+    receiver_alive: bool,
+    // rendezvous 用：已经入队 / 已经被取走的消息序号
+    sent: u64,
+    received: u64,
+    // 阻塞在 recv 里的接收方，加上登记在这个通道上的 select!
+    waiting_receivers: usize,
+    selectors: Vec<Arc<Signal>>,
+}
+
+struct Chan<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    // 队列有空位、rendezvous 的值被取走、接收方 drop 都会通知这里
+    not_full: Condvar,
+}
+
+impl<T> Chan<T> {
+    fn new(cap: Option<usize>) -> Arc<Self> {
+        Arc::new(Chan {
+            state: Mutex::new(State {
+                queue: VecDeque::new(),
+                cap,
+                senders: 1,
+                receiver_alive: true,
+                sent: 0,
+                received: 0,
+                waiting_receivers: 0,
+                selectors: Vec::new(),
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        // 用户代码不会在持锁时运行，锁中毒只可能来自内部 bug
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn notify_receiver(&self, state: &State<T>) {
+        self.not_empty.notify_one();
+        for signal in &state.selectors {
+            signal.notify();
+        }
+    }
+}
+
+impl<T> State<T> {
+    // rendezvous 也占一个槽：值放进去，发送方再等它被取走
+    fn has_room(&self) -> bool {
+        match self.cap {
+            None => true,
+            Some(cap) => self.queue.len() < cap.max(1),
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        !self.queue.is_empty() || self.senders == 0
+    }
+}
+
+pub struct Sender<T> {
+    chan: Arc<Chan<T>>,
+}
+
+// 和 std 一样可以 Send 但不是 Sync：同一时刻只有一个线程在收，select! 看到就绪后 try_recv 一定拿得到
+pub struct Receiver<T> {
+    chan: Arc<Chan<T>>,
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
+    pair(Chan::new(None))
+}
+
+pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    pair(Chan::new(Some(cap)))
+}
+
+pub fn rendezvous<T>() -> (Sender<T>, Receiver<T>) {
+    bounded(0)
+}
+
+fn pair<T>(chan: Arc<Chan<T>>) -> (Sender<T>, Receiver<T>) {
+    let receiver = Receiver {
+        chan: chan.clone(),
+        _not_sync: PhantomData,
+    };
+    (Sender { chan }, receiver)
+}
+
+impl<T> Sender<T> {
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let chan = &self.chan;
+        let mut state = chan.lock();
+        while state.receiver_alive && !state.has_room() {
+            state = chan.not_full.wait(state).unwrap();
+        }
+        if !state.receiver_alive {
+            return Err(SendError(value));
+        }
+        state.queue.push_back(value);
+        state.sent += 1;
+        let ticket = state.sent;
+        chan.notify_receiver(&state);
+
+        if state.cap == Some(0) {
+            // 等接收方取走；接收方先 drop 的话值还在队列里，拿回来退给调用方
+            while state.received < ticket {
+                if !state.receiver_alive {
+                    let value = state.queue.pop_back().unwrap();
+                    return Err(SendError(value));
+                }
+                state = chan.not_full.wait(state).unwrap();
+            }
+        }
+        Ok(())
+    }
+
+    // rendezvous 只有在接收方正在等待（recv 或 select!）时才能成功。
+    // select! 最后选了别的分支的话，这个值留在通道里，交给下一次 recv
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        let chan = &self.chan;
+        let mut state = chan.lock();
+        if !state.receiver_alive {
+            return Err(TrySendError::Disconnected(value));
+        }
+        let room = match state.cap {
+            Some(0) => state.waiting_receivers > state.queue.len(),
+            _ => state.has_room(),
+        };
+        if !room {
+            return Err(TrySendError::Full(value));
+        }
+        state.queue.push_back(value);
+        state.sent += 1;
+        chan.notify_receiver(&state);
+        Ok(())
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.chan.lock().senders += 1;
+        Sender {
+            chan: self.chan.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.chan.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            self.chan.not_empty.notify_all();
+            for signal in &state.selectors {
+                signal.notify();
+            }
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    fn take(&self, state: &mut State<T>) -> Option<T> {
+        let value = state.queue.pop_front()?;
+        state.received += 1;
+        self.chan.not_full.notify_all();
+        Some(value)
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.chan.lock();
+        match self.take(&mut state) {
+            Some(value) => Ok(value),
+            None if state.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let mut state = self.chan.lock();
+        state.waiting_receivers += 1;
+        let result = loop {
+            if let Some(value) = self.take(&mut state) {
+                break Ok(value);
+            }
+            if state.senders == 0 {
+                break Err(RecvError);
+            }
+            state = self.chan.not_empty.wait(state).unwrap();
+        };
+        state.waiting_receivers -= 1;
+        result
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.chan.lock();
+        state.waiting_receivers += 1;
+        let result = loop {
+            if let Some(value) = self.take(&mut state) {
+                break Ok(value);
+            }
+            if state.senders == 0 {
+                break Err(RecvTimeoutError::Disconnected);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                break Err(RecvTimeoutError::Timeout);
+            }
+            state = self
+                .chan
+                .not_empty
+                .wait_timeout(state, deadline - now)
+                .unwrap()
+                .0;
+        };
+        state.waiting_receivers -= 1;
+        result
+    }
+
+    // 阻塞迭代，所有发送方 drop 且取完后结束
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        std::iter::from_fn(|| self.recv().ok())
+    }
+
+    // 只取当前已经到达的值
+    pub fn try_iter(&self) -> impl Iterator<Item = T> + '_ {
+        std::iter::from_fn(|| self.try_recv().ok())
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.chan.lock();
+        state.receiver_alive = false;
+        // 队列里剩下的值没人要了，现在就释放（放到锁外面 drop）；rendezvous 等待中的那个值留给发送方取回
+        let leftover = match state.cap {
+            Some(0) => VecDeque::new(),
+            _ => std::mem::take(&mut state.queue),
+        };
+        self.chan.not_full.notify_all();
+        drop(state);
+        drop(leftover);
+    }
+}
+
+impl<T> IntoIterator for Receiver<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { rx: self }
+    }
+}
+
+pub struct IntoIter<T> {
+    rx: Receiver<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
+
+#[doc(hidden)]
+#[derive(Default)]
+pub struct Signal {
+    fired: Mutex<bool>,
+    cond: Condvar,
+}
+
+impl Signal {
+    fn notify(&self) {
+        *self.fired.lock().unwrap() = true;
+        self.cond.notify_one();
+    }
+}
+
+// select! 需要的最小接口，和元素类型无关
+#[doc(hidden)]
+pub trait Selectable {
+    fn is_ready(&self) -> bool;
+    fn register(&self, signal: &Arc<Signal>);
+    fn unregister(&self, signal: &Arc<Signal>);
+}
+
+impl<T> Selectable for Receiver<T> {
+    fn is_ready(&self) -> bool {
+        self.chan.lock().is_ready()
+    }
+
+    // 登记着的 select! 也算在等待，rendezvous 的 try_send 才能交给它
+    fn register(&self, signal: &Arc<Signal>) {
+        let mut state = self.chan.lock();
+        state.selectors.push(signal.clone());
+        state.waiting_receivers += 1;
+    }
+
+    fn unregister(&self, signal: &Arc<Signal>) {
+        let mut state = self.chan.lock();
+        state.selectors.retain(|s| !Arc::ptr_eq(s, signal));
+        state.waiting_receivers -= 1;
+    }
+}
+
+// 返回第一个就绪（有值或已断开）的接收端下标，超时返回 None
+#[doc(hidden)]
+pub fn select_ready(receivers: &[&dyn Selectable], timeout: Option<Duration>) -> Option<usize> {
+    let deadline = timeout.map(|t| Instant::now() + t);
+    let signal = Arc::new(Signal::default());
+    let ready = |signal: &Arc<Signal>| {
+        *signal.fired.lock().unwrap() = false;
+        receivers.iter().position(|rx| rx.is_ready())
+    };
+    if let Some(i) = ready(&signal) {
+        return Some(i);
+    }
+    receivers.iter().for_each(|rx| rx.register(&signal));
+    let result = loop {
+        // 登记之后再检查一次，避免检查和登记之间到达的消息被漏掉
+        if let Some(i) = ready(&signal) {
+            break Some(i);
+        }
+        let mut fired = signal.fired.lock().unwrap();
+        while !*fired {
+            match deadline {
+                None => fired = signal.cond.wait(fired).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break;
+                    }
+                    fired = signal.cond.wait_timeout(fired, deadline - now).unwrap().0;
+                }
+            }
+        }
+        if !*fired {
+            drop(fired);
+            break ready(&signal);
+        }
+    };
+    receivers.iter().for_each(|rx| rx.unregister(&signal));
+    result
+}
+
+// select! {
+//     recv(rx1) -> msg => ...,
+//     recv(rx2) -> msg => ...,
+//     default(Duration::from_millis(10)) => ...,   // 可选，超时分支
+// }
+//
+// msg 的类型是 Result<T, RecvError>，断开的接收端也算就绪，分支里拿到 Err(RecvError)。
+// 没有 default 分支时一直等到某个接收端就绪。
+#[macro_export]
+macro_rules! select {
+    ($(recv($rx:expr) -> $msg:pat => $body:expr),+ $(,)?) => {
+        $crate::select!(@bind [] $(($rx, $msg, $body))+ @run None, ::std::unreachable!())
+    };
+    ($(recv($rx:expr) -> $msg:pat => $body:expr,)+ default($timeout:expr) => $default:expr $(,)?) => {
+        $crate::select!(@bind [] $(($rx, $msg, $body))+ @run Some($timeout), $default)
+    };
+    // 每个接收端表达式只求值一次，绑定到一个局部变量上。每层递归引入的 `rx` 卫生上互不相同
+    (@bind [$($bound:tt)*] ($rx:expr, $msg:pat, $body:expr) $($rest:tt)*) => {{
+        let rx = &$rx;
+        $crate::select!(@bind [$($bound)* (rx, $msg, $body)] $($rest)*)
+    }};
+    (@bind [$(($rx:ident, $msg:pat, $body:expr))+] @run $timeout:expr, $default:expr) => {
+        match $crate::channel::select_ready(
+            &[$($rx as &dyn $crate::channel::Selectable),+],
+            $timeout,
+        ) {
+            Some(index) => 'select: {
+                let mut index: usize = index;
+                $(
+                    if index == 0 {
+                        // Receiver 不是 Sync，就绪之后值不会被别的线程拿走
+                        let $msg = $rx.try_recv().map_err(|_| ::std::sync::mpsc::RecvError);
+                        break 'select $body;
+                    }
+                    index -= 1;
+                )+
+                let _ = index;
+                unreachable!()
+            }
+            None => $default,
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+
+    // 对 std::sync::mpsc 和我们的通道跑同一组场景，两边的行为必须一致
+    macro_rules! conformance {
+        ($name:ident, $make:expr) => {
+            mod $name {
+                use super::*;
+
+                #[test]
+                fn fifo_single_producer() {
+                    let (tx, rx) = $make;
+                    let h = thread::spawn(move || {
+                        for i in 0..100 {
+                            tx.send(i).unwrap();
+                        }
+                    });
+                    assert_eq!(
+                        rx.iter().collect::<Vec<i32>>(),
+                        (0..100).collect::<Vec<_>>()
+                    );
+                    h.join().unwrap();
+                }
+
+                #[test]
+                fn drains_before_disconnect() {
+                    let (tx, rx) = $make;
+                    let h = thread::spawn(move || {
+                        tx.send(1).unwrap();
+                        tx.send(2).unwrap();
+                    });
+                    assert_eq!(rx.recv(), Ok(1));
+                    assert_eq!(rx.recv(), Ok(2));
+                    h.join().unwrap();
+                    assert_eq!(rx.recv(), Err(RecvError));
+                    assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+                }
+
+                #[test]
+                fn disconnect_only_after_last_sender() {
+                    let (tx, rx) = $make;
+                    let tx2 = tx.clone();
+                    drop(tx);
+                    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+                    drop(tx2);
+                    assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+                    assert_eq!(
+                        rx.recv_timeout(Duration::from_millis(10)),
+                        Err(RecvTimeoutError::Disconnected)
+                    );
+                }
+
+                #[test]
+                fn send_after_receiver_dropped() {
+                    let (tx, rx) = $make;
+                    drop(rx);
+                    assert_eq!(tx.send(5), Err(SendError(5)));
+                }
+
+                #[test]
+                fn recv_timeout() {
+                    let (tx, rx) = $make;
+                    assert_eq!(
+                        rx.recv_timeout(Duration::from_millis(20)),
+                        Err(RecvTimeoutError::Timeout)
+                    );
+                    let h = thread::spawn(move || {
+                        thread::sleep(Duration::from_millis(20));
+                        tx.send(9).unwrap();
+                    });
+                    assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(9));
+                    h.join().unwrap();
+                }
+
+                // 普通的压力测试：靠 yield_now 打乱线程交错，并不像 loom 那样穷举所有交错
+                #[test]
+                fn stress_many_producers() {
+                    // 多生产者、多轮、随机让出 CPU 打乱交错顺序；每个生产者自己的消息必须保持先后顺序
+                    const PRODUCERS: usize = 4;
+                    const PER_PRODUCER: usize = 500;
+                    for round in 0..10 {
+                        let (tx, rx) = $make;
+                        let handles: Vec<_> = (0..PRODUCERS)
+                            .map(|p| {
+                                let tx = tx.clone();
+                                thread::spawn(move || {
+                                    for i in 0..PER_PRODUCER {
+                                        if (i * 7 + p + round) % 13 == 0 {
+                                            thread::yield_now();
+                                        }
+                                        tx.send((p * PER_PRODUCER + i) as i32).unwrap();
+                                    }
+                                })
+                            })
+                            .collect();
+                        drop(tx);
+                        let mut last = [-1i32; PRODUCERS];
+                        let mut count = 0;
+                        for v in rx.iter() {
+                            let p = v as usize / PER_PRODUCER;
+                            assert!(v > last[p], "out of order from producer {p}");
+                            last[p] = v;
+                            count += 1;
+                        }
+                        assert_eq!(count, PRODUCERS * PER_PRODUCER);
+                        handles.into_iter().for_each(|h| h.join().unwrap());
+                    }
+                }
+            }
+        };
+    }
+
+    // 有界通道额外的场景
+    macro_rules! conformance_bounded {
+        ($name:ident, $make:expr) => {
+            mod $name {
+                use super::*;
+
+                #[test]
+                fn try_send_full_then_room() {
+                    let (tx, rx) = $make;
+                    assert_eq!(tx.try_send(1), Ok(()));
+                    assert_eq!(tx.try_send(2), Err(TrySendError::Full(2)));
+                    assert_eq!(rx.recv(), Ok(1));
+                    assert_eq!(tx.try_send(3), Ok(()));
+                    drop(rx);
+                    assert_eq!(tx.try_send(4), Err(TrySendError::Disconnected(4)));
+                }
+
+                #[test]
+                fn send_blocks_until_space() {
+                    let (tx, rx) = $make;
+                    tx.send(1).unwrap();
+                    let h = thread::spawn(move || {
+                        tx.send(2).unwrap();
+                        Instant::now()
+                    });
+                    thread::sleep(Duration::from_millis(30));
+                    let taken = Instant::now();
+                    assert_eq!(rx.recv(), Ok(1));
+                    assert!(h.join().unwrap() >= taken);
+                    assert_eq!(rx.recv(), Ok(2));
+                }
+            }
+        };
+    }
+
+    macro_rules! conformance_rendezvous {
+        ($name:ident, $make:expr) => {
+            mod $name {
+                use super::*;
+
+                #[test]
+                fn send_waits_for_receiver() {
+                    let (tx, rx) = $make;
+                    assert_eq!(tx.try_send(1), Err(TrySendError::Full(1)));
+                    let h = thread::spawn(move || {
+                        tx.send(2).unwrap();
+                        Instant::now()
+                    });
+                    thread::sleep(Duration::from_millis(30));
+                    let before = Instant::now();
+                    assert_eq!(rx.recv(), Ok(2));
+                    assert!(h.join().unwrap() >= before);
+                }
+
+                #[test]
+                fn send_fails_when_receiver_leaves() {
+                    let (tx, rx) = $make;
+                    let h = thread::spawn(move || tx.send(3));
+                    thread::sleep(Duration::from_millis(30));
+                    drop(rx);
+                    assert_eq!(h.join().unwrap(), Err(SendError(3)));
+                }
+            }
+        };
+    }
+
+    conformance!(std_unbounded, mpsc::channel::<i32>());
+    conformance!(std_bounded, mpsc::sync_channel::<i32>(4));
+    conformance!(std_rendezvous, mpsc::sync_channel::<i32>(0));
+    conformance!(our_unbounded, unbounded::<i32>());
+    conformance!(our_bounded, bounded::<i32>(4));
+    conformance!(our_rendezvous, rendezvous::<i32>());
+    conformance_bounded!(std_bounded_extra, mpsc::sync_channel::<i32>(1));
+    conformance_bounded!(our_bounded_extra, bounded::<i32>(1));
+    conformance_rendezvous!(std_rendezvous_extra, mpsc::sync_channel::<i32>(0));
+    conformance_rendezvous!(our_rendezvous_extra, rendezvous::<i32>());
+
+    #[test]
+    fn receiver_drop_frees_queued_values() {
+        let marker = Arc::new(());
+        let (tx, rx) = unbounded();
+        tx.send(marker.clone()).unwrap();
+        assert_eq!(Arc::strong_count(&marker), 2);
+        drop(rx);
+        assert_eq!(Arc::strong_count(&marker), 1);
+    }
+
+    #[test]
+    fn select_picks_ready_receiver() {
+        let (tx_num, rx_num) = unbounded::<i32>();
+        let (tx_text, rx_text) = bounded::<&str>(1);
+
+        tx_text.send("hello").unwrap();
+        let got = select! {
+            recv(rx_num) -> msg => format!("num {msg:?}"),
+            recv(rx_text) -> msg => format!("text {msg:?}"),
+        };
+        assert_eq!(got, "text Ok(\"hello\")");
+
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            tx_num.send(42).unwrap();
+        });
+        let got = select! {
+            recv(rx_num) -> msg => msg.unwrap(),
+            recv(rx_text) -> _msg => -1,
+        };
+        assert_eq!(got, 42);
+        h.join().unwrap();
+
+        // tx_num 已经 drop，断开也算就绪
+        let got = select! {
+            recv(rx_num) -> msg => msg,
+            recv(rx_text) -> _msg => Ok(-1),
+        };
+        assert_eq!(got, Err(RecvError));
+        drop(tx_text);
+    }
+
+    #[test]
+    fn select_evaluates_each_receiver_once() {
+        let (tx1, rx1) = unbounded::<i32>();
+        let (_tx2, rx2) = unbounded::<i32>();
+        tx1.send(5).unwrap();
+        let mut evaluated = Vec::new();
+        let got = select! {
+            recv({ evaluated.push(1); rx1 }) -> msg => msg.unwrap(),
+            recv({ evaluated.push(2); rx2 }) -> _msg => -1,
+            default(Duration::from_millis(10)) => -2,
+        };
+        assert_eq!(got, 5);
+        assert_eq!(evaluated, [1, 2]);
+    }
+
+    #[test]
+    fn rendezvous_try_send_reaches_blocked_select() {
+        let (tx, rx) = rendezvous::<i32>();
+        let (_tx_other, rx_other) = unbounded::<i32>();
+        let h = thread::spawn(move || {
+            crate::select! {
+                recv(rx) -> msg => msg,
+                recv(rx_other) -> _msg => Ok(-1),
+            }
+        });
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut value = 7;
+        loop {
+            match tx.try_send(value) {
+                Ok(()) => break,
+                Err(TrySendError::Full(v)) => value = v,
+                Err(e) => panic!("{e:?}"),
+            }
+            assert!(
+                Instant::now() < deadline,
+                "select! never counted as waiting"
+            );
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(h.join().unwrap(), Ok(7));
+    }
+
+    #[test]
+    fn select_default_on_timeout() {
+        let (_tx1, rx1) = unbounded::<i32>();
+        let (_tx2, rx2) = rendezvous::<i32>();
+        let start = Instant::now();
+        let got = select! {
+            recv(rx1) -> _msg => "one",
+            recv(rx2) -> _msg => "two",
+            default(Duration::from_millis(30)) => "timeout",
+        };
+        assert_eq!(got, "timeout");
+        assert!(start.elapsed() >= Duration::from_millis(30));
+        // 超时后登记的信号都撤掉了
+        assert!(rx1.chan.lock().selectors.is_empty());
+    }
+
+    #[test]
+    fn select_stress_two_producers() {
+        let (tx_a, rx_a) = bounded::<u32>(2);
+        let (tx_b, rx_b) = unbounded::<u32>();
+        let pa = thread::spawn(move || (0..1000).for_each(|i| tx_a.send(i).unwrap()));
+        let pb = thread::spawn(move || (0..1000).for_each(|i| tx_b.send(i).unwrap()));
+
+        let (mut a, mut b) = (0, 0);
+        let (mut a_open, mut b_open) = (true, true);
+        while a_open || b_open {
+            select! {
+                recv(rx_a) -> msg => match msg {
+                    Ok(v) => { assert_eq!(v, a); a += 1; }
+                    Err(_) => a_open = false,
+                },
+                recv(rx_b) -> msg => match msg {
+                    Ok(v) => { assert_eq!(v, b); b += 1; }
+                    Err(_) => b_open = false,
+                },
+            }
+            // 一个通道断开后就一直就绪，select 会偏向它；断开的接收端换成阻塞 recv 的另一个
+            if !a_open && b_open {
+                b += rx_b.iter().count() as u32;
+                b_open = false;
+            }
+            if !b_open && a_open {
+                a += rx_a.iter().count() as u32;
+                a_open = false;
+            }
+        }
+        assert_eq!((a, b), (1000, 1000));
+        pa.join().unwrap();
+        pb.join().unwrap();
+    }
+}
@@ -0,0 +1,226 @@
+// collect.md 的 collect / extend / into_iter 在本库各个集合上的统一检查
+//
+// 约定：
+// - 能从元素直接建出来的集合实现 FromIterator，建出来之后还能接着 Extend，按值和按引用都能 IntoIterator
+// - Copy 元素的集合额外接受引用（Extend<&T>），和 std 一样；FromIterator 只收元素本身，不然 collect 推断不出类型
+// - 实现了 FromIterator 的集合自动支持 std 的 `Result<C, E>` / `Option<C>` 收集：遇到第一个错误就停止
+// - 例外：缓存的容量必须由调用方给出，只有 Extend；CounterMap 借用迭代要持有分片锁，只能按值迭代；
+//   Text 和 String 一样不是元素容器，没有 IntoIterator；通道的 Receiver 只有 IntoIterator；
+//   图只从 (from, to, edge) 收集，遍历用 Digraph 的方法
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::time::Duration;
+
+use crate::admission::ManualClock;
+use crate::bitset::BitSet;
+use crate::cache::{LfuCache, LruCache, TtlCache};
+use crate::channel;
+use crate::counter::CounterMap;
+use crate::graph::{AdjList, Digraph};
+use crate::openmap::{FxBuildHasher, OpenMap};
+use crate::ordmap::OrdMap;
+use crate::smallvec::SmallVec;
+use crate::text::Text;
+
+// 前一半 collect，后一半 extend，再按值迭代出来，和输入（不重复）作为集合比较
+fn round_trip<C, T>(items: &[T]) -> C
+where
+    C: FromIterator<T> + Extend<T> + IntoIterator<Item = T> + Clone,
+    T: Clone + Ord + Debug,
+{
+    let (head, tail) = items.split_at(items.len() / 2);
+    let mut collection: C = head.iter().cloned().collect();
+    collection.extend(tail.iter().cloned());
+
+    let mut out: Vec<T> = collection.clone().into_iter().collect();
+    let mut expected = items.to_vec();
+    out.sort();
+    expected.sort();
+    assert_eq!(out, expected);
+    collection
+}
+
+// 全是 Ok 时收集成功；第三个元素出错时立刻停止，后面的不再被消费
+fn fallible<C, T>(items: &[T])
+where
+    C: FromIterator<T>,
+    T: Clone,
+{
+    assert!(items.len() > 3);
+    let ok: Result<C, String> = items.iter().cloned().map(Ok).collect();
+    assert!(ok.is_ok());
+
+    let mut consumed = 0;
+    let err: Result<C, String> = items
+        .iter()
+        .cloned()
+        .enumerate()
+        .map(|(i, item)| {
+            consumed += 1;
+            if i == 2 {
+                Err(format!("bad item {i}"))
+            } else {
+                Ok(item)
+            }
+        })
+        .collect();
+    assert_eq!(err.err().as_deref(), Some("bad item 2"));
+    assert_eq!(consumed, 3);
+
+    let none: Option<C> = items
+        .iter()
+        .cloned()
+        .enumerate()
+        .map(|(i, item)| (i != 1).then_some(item))
+        .collect();
+    assert!(none.is_none());
+}
+
+#[test]
+fn bitset() {
+    let items = [5, 1, 64, 300, 2, 127];
+    let set: BitSet = round_trip(&items);
+    // 按值迭代也是有序的
+    assert!(set.clone().into_iter().eq([1, 2, 5, 64, 127, 300]));
+    assert!((&set).into_iter().eq(set.clone()));
+    let mut from_refs = BitSet::new();
+    from_refs.extend(&items);
+    assert_eq!(from_refs, set);
+    fallible::<BitSet, _>(&items);
+
+    // 解析失败时返回第一个错误
+    let parsed: Result<BitSet, _> = "3 1 x 4".split(' ').map(str::parse::<usize>).collect();
+    assert!(parsed.is_err());
+    let parsed: Result<BitSet, std::num::ParseIntError> =
+        "3 1 4".split(' ').map(str::parse).collect();
+    assert_eq!(parsed, Ok(BitSet::from([1, 3, 4])));
+}
+
+#[test]
+fn smallvec() {
+    let items = [3, 1, 4, 1, 5, 9, 2, 6];
+    // 前一半 collect 时还是内联的，extend 之后搬到堆上
+    let vec: SmallVec<i32, 4> = round_trip(&items);
+    assert!(!vec.is_inline());
+    assert_eq!(vec, items);
+    assert!((&vec).into_iter().eq(&items));
+    let mut from_refs: SmallVec<i32, 4> = SmallVec::new();
+    from_refs.extend(&items);
+    assert_eq!(from_refs, vec);
+    fallible::<SmallVec<i32, 4>, _>(&items);
+    fallible::<SmallVec<String, 0>, _>(&items.map(|n| n.to_string()));
+}
+
+#[test]
+fn ordmap() {
+    let items = [(3, "three"), (1, "one"), (4, "four"), (2, "two")];
+    let map: OrdMap<i32, &str> = round_trip(&items);
+    assert!(map.clone().into_iter().map(|(k, _)| k).eq(1..=4));
+    let mut copy = OrdMap::new();
+    copy.extend(&map);
+    assert_eq!(copy, map);
+    fallible::<OrdMap<i32, &str>, _>(&items);
+}
+
+#[test]
+fn openmap() {
+    let items = [("苹果", 3), ("香蕉", 2), ("橙子", 5), ("葡萄", 7)];
+    let map: OpenMap<&str, i32, FxBuildHasher> = round_trip(&items);
+    assert_eq!((&map).into_iter().len(), 4);
+    let mut copy = OpenMap::with_hasher(FxBuildHasher::default());
+    copy.extend(&map);
+    assert_eq!(copy, map);
+    fallible::<OpenMap<&str, i32>, _>(&items);
+}
+
+#[test]
+fn adj_list() {
+    let edges = [("a", "b", 1), ("b", "c", 2), ("a", "c", 5), ("c", "d", 1)];
+    let (head, tail) = edges.split_at(2);
+    let mut graph: AdjList<&str, i32> = head.iter().copied().collect();
+    graph.extend(tail.iter().copied());
+    assert_eq!(graph.edge_count(), 4);
+    assert!(graph.nodes().eq(["a", "b", "c", "d"]));
+    fallible::<AdjList<&str, i32>, _>(&edges);
+}
+
+// CounterMap 没有 Clone，单独写
+#[test]
+fn counter_map() {
+    let words = "the quick brown fox jumps over the lazy dog the end";
+    let counts: CounterMap<&str> = words.split(' ').collect();
+    assert_eq!(counts.get("the"), 3);
+
+    // 按值迭代得到 (key, 次数)，再收集回去结果不变
+    let pairs: HashMap<&str, u64> = counts.into_iter().collect();
+    let again: CounterMap<&str> = pairs.clone().into_iter().collect();
+    assert_eq!(again.snapshot(), pairs);
+
+    // 两种 extend：逐个计数，和按次数累加
+    let mut counts = again;
+    counts.extend(["fox", "fox"]);
+    counts.extend([("dog", 10)]);
+    assert_eq!(counts.get("fox"), 3);
+    assert_eq!(counts.get("dog"), 11);
+
+    fallible::<CounterMap<&str>, _>(&["a", "b", "a", "c"]);
+}
+
+#[test]
+fn text() {
+    let text: Text = "hello".chars().collect();
+    assert!(text.is_inline());
+    let mut text: Text = ["hello", ", ", "world"].into_iter().collect();
+    text.extend(['!'; 3]);
+    text.extend(vec![String::from(" bye")]);
+    assert_eq!(text, "hello, world!!! bye");
+
+    let long: Text = std::iter::repeat_n("abc", 20).collect();
+    assert!(long.is_heap());
+    assert_eq!(long.len(), 60);
+
+    fallible::<Text, _>(&['a', 'b', 'c', 'd']);
+    fallible::<Text, _>(&["ab", "cd", "ef", "gh"]);
+}
+
+#[test]
+fn caches_extend_with_eviction() {
+    let mut lru = LruCache::new(2);
+    lru.extend([("a", 1), ("b", 2), ("c", 3)]);
+    assert_eq!(lru.len(), 2);
+    assert_eq!(lru.stats().evictions, 1);
+    // 借用迭代从最近使用的开始
+    assert_eq!(
+        (&lru).into_iter().collect::<Vec<_>>(),
+        [(&"c", &3), (&"b", &2)]
+    );
+
+    let mut lfu = LfuCache::new(2);
+    lfu.extend([("a", 1), ("a", 2), ("b", 3), ("c", 4)]);
+    assert_eq!(lfu.peek("a"), Some(&2));
+    assert_eq!(lfu.peek("b"), None);
+
+    let clock = ManualClock::default();
+    let mut ttl = TtlCache::with_clock(
+        4,
+        Duration::from_secs(1),
+        std::sync::Arc::new(clock.clone()),
+    );
+    ttl.extend([("a", 1), ("b", 2)]);
+    clock.advance(Duration::from_secs(1));
+    ttl.extend([("c", 3)]);
+    assert_eq!(ttl.purge_expired(), 2);
+    assert_eq!(ttl.len(), 1);
+}
+
+#[test]
+fn receiver_into_iter_collects() {
+    let (tx, rx) = channel::unbounded();
+    for x in [3, 1, 2] {
+        tx.send(x).unwrap();
+    }
+    drop(tx);
+    let set: BitSet = rx.into_iter().collect();
+    assert_eq!(set, BitSet::from([1, 2, 3]));
+}
@@ -0,0 +1,324 @@
+// 多线程共享的计数表（HashMap.md 里 entry().or_insert 计数的并发版本）
+//
+// 按 key 的哈希分成若干个分片，每片一个 `RwLock<HashMap<K, AtomicU64>>`：
+// - 已经存在的 key 只拿读锁，计数用 fetch_add，不同线程给同一片里的不同 key（甚至同一个 key）计数互不阻塞
+// - 第一次出现的 key 才拿写锁插入
+// - `snapshot` 逐片拿写锁复制，每一片内部是某个时刻的一致状态，片与片之间不保证
+//
+// 另一种常见做法是每个线程先在自己的 HashMap 里计数，最后 `merge` 进来，每片只拿一次写锁。
+//
+// 计数溢出时按 u64 回绕（和 AtomicU64::fetch_add 一致），debug 和 release 下行为相同，不会 panic。
+
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{PoisonError, RwLock};
+
+pub struct CounterMap<K, S = RandomState> {
+    shards: Box<[RwLock<HashMap<K, AtomicU64>>]>,
+    hasher: S,
+}
+
+// 默认分片数：每个核 4 片，减少不同线程撞到同一片的机会
+fn default_shards() -> usize {
+    std::thread::available_parallelism().map_or(4, |n| n.get()) * 4
+}
+
+impl<K: Hash + Eq> CounterMap<K> {
+    pub fn new() -> Self {
+        CounterMap::with_shards(default_shards())
+    }
+
+    pub fn with_shards(shards: usize) -> Self {
+        CounterMap::with_shards_and_hasher(shards, RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq> Default for CounterMap<K> {
+    fn default() -> Self {
+        CounterMap::new()
+    }
+}
+
+impl<K: Hash + Eq, S: BuildHasher> CounterMap<K, S> {
+    pub fn with_shards_and_hasher(shards: usize, hasher: S) -> Self {
+        assert!(shards > 0, "need at least one shard");
+        CounterMap {
+            shards: (0..shards).map(|_| RwLock::default()).collect(),
+            hasher,
+        }
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    fn shard<Q: Hash + ?Sized>(&self, key: &Q) -> &RwLock<HashMap<K, AtomicU64>> {
+        let index = self.hasher.hash_one(key) as usize % self.shards.len();
+        &self.shards[index]
+    }
+
+    pub fn increment<Q>(&self, key: &Q) -> u64
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        self.add(key, 1)
+    }
+
+    // 返回加完之后的值；只有 key 第一次出现时才会 to_owned
+    pub fn add<Q>(&self, key: &Q, n: u64) -> u64
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        let shard = self.shard(key);
+        {
+            // 计数都是原子的，持锁的线程 panic 也不会留下半截状态
+            let map = shard.read().unwrap_or_else(PoisonError::into_inner);
+            if let Some(count) = map.get(key) {
+                return count.fetch_add(n, Ordering::Relaxed).wrapping_add(n);
+            }
+        }
+        let mut map = shard.write().unwrap_or_else(PoisonError::into_inner);
+        // 拿写锁之前可能已经有别的线程插进来了，entry 会处理这种情况
+        let count = map
+            .entry(key.to_owned())
+            .or_insert_with(|| AtomicU64::new(0));
+        count.fetch_add(n, Ordering::Relaxed).wrapping_add(n)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> u64
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let map = self
+            .shard(key)
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        map.get(key).map_or(0, |c| c.load(Ordering::Relaxed))
+    }
+
+    // 不同 key 的个数
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|s| s.read().unwrap_or_else(PoisonError::into_inner).len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn total(&self) -> u64 {
+        self.shards
+            .iter()
+            .map(|s| {
+                let map = s.read().unwrap_or_else(PoisonError::into_inner);
+                map.values()
+                    .fold(0u64, |sum, c| sum.wrapping_add(c.load(Ordering::Relaxed)))
+            })
+            .fold(0, u64::wrapping_add)
+    }
+
+    // 把线程本地的计数表合并进来：先按分片分组，每片只拿一次写锁
+    pub fn merge<I: IntoIterator<Item = (K, u64)>>(&self, local: I) {
+        let mut groups: Vec<Vec<(K, u64)>> = (0..self.shards.len()).map(|_| Vec::new()).collect();
+        for (key, n) in local {
+            let index = self.hasher.hash_one(&key) as usize % self.shards.len();
+            groups[index].push((key, n));
+        }
+        for (shard, group) in self.shards.iter().zip(groups) {
+            if group.is_empty() {
+                continue;
+            }
+            let mut map = shard.write().unwrap_or_else(PoisonError::into_inner);
+            for (key, n) in group {
+                let count = map.entry(key).or_default().get_mut();
+                *count = count.wrapping_add(n);
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, S: BuildHasher> CounterMap<K, S> {
+    // 写锁挡住了新 key 的插入，也挡住了持读锁的 fetch_add，所以每一片是一致的
+    pub fn snapshot(&self) -> HashMap<K, u64> {
+        let mut out = HashMap::new();
+        for shard in self.shards.iter() {
+            let map = shard.write().unwrap_or_else(PoisonError::into_inner);
+            out.extend(
+                map.iter()
+                    .map(|(k, c)| (k.clone(), c.load(Ordering::Relaxed))),
+            );
+        }
+        out
+    }
+
+    // 计数最多的 n 个，计数相同时顺序不定
+    pub fn most_common(&self, n: usize) -> Vec<(K, u64)> {
+        let mut all: Vec<_> = self.snapshot().into_iter().collect();
+        all.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+        all.truncate(n);
+        all
+    }
+}
+
+impl<K: Hash + Eq, S: BuildHasher> CounterMap<K, S> {
+    pub fn into_map(self) -> HashMap<K, u64> {
+        self.shards
+            .into_vec()
+            .into_iter()
+            .flat_map(|s| s.into_inner().unwrap_or_else(PoisonError::into_inner))
+            .map(|(k, c)| (k, c.into_inner()))
+            .collect()
+    }
+}
+
+// 逐个 key 计数：words.collect::<CounterMap<_>>() 就是词频表
+impl<K: Hash + Eq, S: BuildHasher> Extend<K> for CounterMap<K, S> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        self.merge(iter.into_iter().map(|k| (k, 1)));
+    }
+}
+
+// (key, 次数) 对按次数累加，和 into_iter 的输出对应
+impl<K: Hash + Eq, S: BuildHasher> Extend<(K, u64)> for CounterMap<K, S> {
+    fn extend<I: IntoIterator<Item = (K, u64)>>(&mut self, iter: I) {
+        self.merge(iter);
+    }
+}
+
+impl<K: Hash + Eq, S: BuildHasher + Default> FromIterator<K> for CounterMap<K, S> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut counts = CounterMap::with_shards_and_hasher(default_shards(), S::default());
+        counts.extend(iter);
+        counts
+    }
+}
+
+impl<K: Hash + Eq, S: BuildHasher + Default> FromIterator<(K, u64)> for CounterMap<K, S> {
+    fn from_iter<I: IntoIterator<Item = (K, u64)>>(iter: I) -> Self {
+        let mut counts = CounterMap::with_shards_and_hasher(default_shards(), S::default());
+        counts.extend(iter);
+        counts
+    }
+}
+
+// 只有按值的迭代：借用迭代要一直拿着各分片的锁，用 snapshot 代替
+impl<K: Hash + Eq, S: BuildHasher> IntoIterator for CounterMap<K, S> {
+    type Item = (K, u64);
+    type IntoIter = std::collections::hash_map::IntoIter<K, u64>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_map().into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    const TEXT: &str = "the quick brown fox jumps over the lazy dog the end";
+
+    fn sequential(text: &str) -> HashMap<String, u64> {
+        let mut counts = HashMap::new();
+        for word in text.split_whitespace() {
+            *counts.entry(word.to_string()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn increment_and_get() {
+        let counts: CounterMap<String> = CounterMap::with_shards(4);
+        assert_eq!(counts.increment("a"), 1);
+        assert_eq!(counts.increment("a"), 2);
+        assert_eq!(counts.add("b", 5), 5);
+        assert_eq!(counts.get("a"), 2);
+        assert_eq!(counts.get("missing"), 0);
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts.total(), 7);
+        assert_eq!(counts.most_common(1), [("b".to_string(), 5)]);
+    }
+
+    #[test]
+    fn overflow_wraps() {
+        let counts: CounterMap<String> = CounterMap::with_shards(2);
+        counts.add("a", u64::MAX);
+        assert_eq!(counts.add("a", 2), 1);
+        counts.merge([("a".to_string(), u64::MAX)]);
+        assert_eq!(counts.get("a"), 0);
+        counts.add("b", u64::MAX);
+        counts.add("c", 3);
+        assert_eq!(counts.total(), 2);
+    }
+
+    #[test]
+    fn concurrent_increments_match_sequential() {
+        let counts = Arc::new(CounterMap::<String>::with_shards(8));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let counts = counts.clone();
+                thread::spawn(move || {
+                    for _ in 0..200 {
+                        TEXT.split_whitespace().for_each(|w| {
+                            counts.increment(w);
+                        });
+                    }
+                })
+            })
+            .collect();
+        handles.into_iter().for_each(|h| h.join().unwrap());
+
+        let expected: HashMap<String, u64> = sequential(TEXT)
+            .into_iter()
+            .map(|(k, v)| (k, v * 1600))
+            .collect();
+        assert_eq!(counts.snapshot(), expected);
+        assert_eq!(Arc::try_unwrap(counts).ok().unwrap().into_map(), expected);
+    }
+
+    #[test]
+    fn merge_thread_local_maps() {
+        let counts = CounterMap::<String>::with_shards(3);
+        thread::scope(|s| {
+            for line in [TEXT, "the fox", "dog dog"] {
+                let counts = &counts;
+                s.spawn(move || counts.merge(sequential(line)));
+            }
+        });
+        let mut expected = sequential(TEXT);
+        for w in ["the", "fox", "dog", "dog"] {
+            *expected.get_mut(w).unwrap() += 1;
+        }
+        assert_eq!(counts.snapshot(), expected);
+    }
+
+    #[test]
+    fn snapshot_during_updates_is_consistent_per_shard() {
+        // 只有一片：每次 add 让两个 key 一起变，快照里两个值必须相等
+        let counts = Arc::new(CounterMap::<&'static str>::with_shards(1));
+        counts.merge([("x", 0), ("y", 0)]);
+        let writer = {
+            let counts = counts.clone();
+            thread::spawn(move || {
+                for _ in 0..2000 {
+                    counts.merge([("x", 1), ("y", 1)]);
+                }
+            })
+        };
+        for _ in 0..200 {
+            let snap = counts.snapshot();
+            assert_eq!(snap["x"], snap["y"]);
+        }
+        writer.join().unwrap();
+        assert_eq!(counts.get("x"), 2000);
+    }
+}
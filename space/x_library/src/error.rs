@@ -0,0 +1,274 @@
+// 替代 `Result<f64, String>` 的错误类型
+//
+// - `Error`：手写的错误枚举（不依赖 derive 宏），通过 `source()` 串起下层错误
+// - `Context`：给 `Result` / `Option` 加上 `.context("…")`，一层层包上说明
+// - io / 解析错误都有 `From`，可以直接用 `?`（errorable_operator.md）
+// - `Report`：把整条错误链打印出来
+//
+// 约定：Display 只写本层的信息，下层错误一律通过 source() 暴露，避免同一句话在链上重复出现。
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+use std::num::{ParseFloatError, ParseIntError};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+type BoxError = Box<dyn StdError + Send + Sync + 'static>;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    ParseInt(ParseIntError),
+    ParseFloat(ParseFloatError),
+    DivisionByZero,
+    Message(String),
+    Context { message: String, source: BoxError },
+}
+
+impl Error {
+    pub fn msg(message: impl Into<String>) -> Self {
+        Error::Message(message.into())
+    }
+
+    // 最底层的那个错误，方便按根因分支处理
+    pub fn root_cause(&self) -> &(dyn StdError + 'static) {
+        chain(self).last().unwrap()
+    }
+
+    pub fn report(&self) -> Report<'_> {
+        Report(self)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(_) => f.write_str("I/O error"),
+            Error::ParseInt(_) => f.write_str("invalid integer"),
+            Error::ParseFloat(_) => f.write_str("invalid float"),
+            Error::DivisionByZero => f.write_str("division by zero"),
+            Error::Message(message) => f.write_str(message),
+            Error::Context { message, .. } => f.write_str(message),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::ParseInt(e) => Some(e),
+            Error::ParseFloat(e) => Some(e),
+            Error::DivisionByZero | Error::Message(_) => None,
+            Error::Context { source, .. } => Some(&**source),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<ParseIntError> for Error {
+    fn from(e: ParseIntError) -> Self {
+        Error::ParseInt(e)
+    }
+}
+
+impl From<ParseFloatError> for Error {
+    fn from(e: ParseFloatError) -> Self {
+        Error::ParseFloat(e)
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Message(message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Error::Message(message.to_string())
+    }
+}
+
+pub trait Context<T> {
+    fn context(self, message: impl Into<String>) -> Result<T>;
+
+    // 说明文字需要 format! 时用这个，只在出错时才拼字符串
+    fn with_context<S: Into<String>>(self, f: impl FnOnce() -> S) -> Result<T>;
+}
+
+impl<T, E> Context<T> for std::result::Result<T, E>
+where
+    E: StdError + Send + Sync + 'static,
+{
+    fn context(self, message: impl Into<String>) -> Result<T> {
+        self.map_err(|e| Error::Context {
+            message: message.into(),
+            source: Box::new(e),
+        })
+    }
+
+    fn with_context<S: Into<String>>(self, f: impl FnOnce() -> S) -> Result<T> {
+        self.map_err(|e| Error::Context {
+            message: f().into(),
+            source: Box::new(e),
+        })
+    }
+}
+
+// None 没有下层错误，说明文字就是链的终点
+impl<T> Context<T> for Option<T> {
+    fn context(self, message: impl Into<String>) -> Result<T> {
+        self.ok_or_else(|| Error::Message(message.into()))
+    }
+
+    fn with_context<S: Into<String>>(self, f: impl FnOnce() -> S) -> Result<T> {
+        self.ok_or_else(|| Error::Message(f().into()))
+    }
+}
+
+// 从 err 自己开始，沿着 source() 一直走到底
+pub fn chain<'a>(
+    err: &'a (dyn StdError + 'static),
+) -> impl Iterator<Item = &'a (dyn StdError + 'static)> {
+    std::iter::successors(Some(err), |&e| e.source())
+}
+
+// error: 最外层
+// caused by:
+//    0: 下一层
+//    1: 再下一层
+pub struct Report<'a>(pub &'a (dyn StdError + 'static));
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error: {}", self.0)?;
+        let mut causes = chain(self.0).skip(1).peekable();
+        if causes.peek().is_some() {
+            write!(f, "\ncaused by:")?;
+            for (i, cause) in causes.enumerate() {
+                write!(f, "\n  {i:>2}: {cause}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Result-Ok(T)-Err(E).rs 里的 divide，错误从 String 换成 Error
+    fn divide(dividend: f64, divisor: f64) -> Result<f64> {
+        if divisor == 0.0 {
+            Err(Error::DivisionByZero)
+        } else {
+            Ok(dividend / divisor)
+        }
+    }
+
+    // option.rs 里的 checked_division 保持返回 Option，调用方按需加上下文
+    fn checked_division(dividend: i32, divisor: i32) -> Option<i32> {
+        if divisor == 0 {
+            None
+        } else {
+            Some(dividend / divisor)
+        }
+    }
+
+    fn divide_text(dividend: &str, divisor: &str) -> Result<f64> {
+        let a: f64 = dividend
+            .trim()
+            .parse()
+            .with_context(|| format!("parsing dividend {dividend:?}"))?;
+        let b: f64 = divisor.parse()?;
+        divide(a, b).with_context(|| format!("dividing {a} by {b}"))
+    }
+
+    #[test]
+    fn divide_errors() {
+        assert_eq!(divide(10.0, 2.0).unwrap(), 5.0);
+        let err = divide(10.0, 0.0).unwrap_err();
+        assert!(matches!(err, Error::DivisionByZero));
+        assert_eq!(err.to_string(), "division by zero");
+        assert_eq!(err.report().to_string(), "error: division by zero");
+    }
+
+    #[test]
+    fn context_chain_output() {
+        let err = divide_text("10", "0").unwrap_err();
+        assert_eq!(
+            err.report().to_string(),
+            "error: dividing 10 by 0\ncaused by:\n   0: division by zero"
+        );
+
+        let err = divide_text("ten", "2")
+            .context("computing ratio")
+            .unwrap_err();
+        assert_eq!(
+            err.report().to_string(),
+            "error: computing ratio\n\
+             caused by:\n   \
+             0: parsing dividend \"ten\"\n   \
+             1: invalid float literal"
+        );
+        assert_eq!(err.root_cause().to_string(), "invalid float literal");
+        assert_eq!(chain(&err).count(), 3);
+    }
+
+    #[test]
+    fn from_conversions_with_question_mark() {
+        let err = divide_text("1", "x").unwrap_err();
+        assert!(matches!(err, Error::ParseFloat(_)));
+        assert_eq!(
+            err.report().to_string(),
+            "error: invalid float\ncaused by:\n   0: invalid float literal"
+        );
+
+        fn parse_int(s: &str) -> Result<i32> {
+            Ok(s.parse::<i32>()?)
+        }
+        assert!(matches!(parse_int("q"), Err(Error::ParseInt(_))));
+
+        fn read(path: &str) -> Result<String> {
+            Ok(std::fs::read_to_string(path)?)
+        }
+        let err = read("/definitely/not/here").unwrap_err();
+        let io = err.source().unwrap().downcast_ref::<io::Error>().unwrap();
+        assert_eq!(io.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn option_context() {
+        assert_eq!(checked_division(4, 2).context("4 / 2").unwrap(), 2);
+        let err = checked_division(1, 0)
+            .with_context(|| format!("{} / {} failed!", 1, 0))
+            .unwrap_err();
+        assert_eq!(err.report().to_string(), "error: 1 / 0 failed!");
+        assert!(err.source().is_none());
+    }
+
+    #[test]
+    fn context_on_foreign_errors_keeps_them_downcastable() {
+        let err = "x"
+            .parse::<u8>()
+            .context("reading port")
+            .context("loading config")
+            .unwrap_err();
+        let inner = chain(&err).nth(2).unwrap();
+        assert!(inner.downcast_ref::<ParseIntError>().is_some());
+        assert_eq!(
+            Report(&err).to_string(),
+            "error: loading config\n\
+             caused by:\n   \
+             0: reading port\n   \
+             1: invalid digit found in string"
+        );
+    }
+}
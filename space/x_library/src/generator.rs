@@ -0,0 +1,216 @@
+// 在 stable Rust 上用 async/await 模拟生成器（coroutine.rs 需要 nightly 的 feature）
+//
+// 思路：async 块本身就是编译器生成的状态机，`co.yield_(v).await` 第一次被 poll 时
+// 把 v 放进共享的"气闸"(airlock) 里并返回 Pending，状态机就停在这个 await 点上；
+// 外部的 `resume` 用一个什么都不做的 waker 去 poll，拿到 Pending 后从气闸里取出 v。
+// 下一次 `resume(arg)` 先把 arg 放进气闸，再 poll，await 点就拿着 arg 继续往下执行。
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+// 与 std::ops::CoroutineState 对应
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneratorState<Y, C> {
+    Yielded(Y),
+    Complete(C),
+}
+
+struct Airlock<Y, R> {
+    yielded: Option<Y>,
+    resume_arg: Option<R>,
+}
+
+// 生成器体内用来 yield 的句柄，`R` 是 resume 参数的类型
+pub struct Co<Y, R = ()> {
+    airlock: Rc<RefCell<Airlock<Y, R>>>,
+}
+
+impl<Y, R> Co<Y, R> {
+    // 产出一个值并挂起，`.await` 的结果是下一次 `resume` 传进来的参数
+    pub fn yield_(&self, value: Y) -> YieldFuture<'_, Y, R> {
+        YieldFuture {
+            co: self,
+            value: Some(value),
+        }
+    }
+}
+
+pub struct YieldFuture<'a, Y, R> {
+    co: &'a Co<Y, R>,
+    value: Option<Y>,
+}
+
+// 只持有 Option<Y> 和引用，不存在自引用，可以安全地 Unpin
+impl<Y, R> Unpin for YieldFuture<'_, Y, R> {}
+
+impl<Y, R> Future for YieldFuture<'_, Y, R> {
+    type Output = R;
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<R> {
+        match self.value.take() {
+            // 第一次 poll：交出值，挂起
+            Some(value) => {
+                self.co.airlock.borrow_mut().yielded = Some(value);
+                Poll::Pending
+            }
+            // 第二次 poll：说明外部已经 resume 了，取出 resume 参数
+            None => match self.co.airlock.borrow_mut().resume_arg.take() {
+                Some(arg) => Poll::Ready(arg),
+                None => panic!("generator resumed without a resume argument"),
+            },
+        }
+    }
+}
+
+// 生成器本体：Y 是 yield 出的类型，R 是 resume 参数类型，C 是完成时的返回值类型
+pub struct Gen<Y, R, C> {
+    airlock: Rc<RefCell<Airlock<Y, R>>>,
+    future: Option<Pin<Box<dyn Future<Output = C>>>>,
+}
+
+// 对应 nightly 的 `#[coroutine] || { yield 1; ... }`
+pub fn gen<Y, R, C, F, Fut>(producer: F) -> Gen<Y, R, C>
+where
+    F: FnOnce(Co<Y, R>) -> Fut,
+    Fut: Future<Output = C> + 'static,
+{
+    let airlock = Rc::new(RefCell::new(Airlock {
+        yielded: None,
+        resume_arg: None,
+    }));
+    let co = Co {
+        airlock: Rc::clone(&airlock),
+    };
+    Gen {
+        airlock,
+        future: Some(Box::pin(producer(co))),
+    }
+}
+
+impl<Y, R, C> Gen<Y, R, C> {
+    // 与 Coroutine::resume 对应。第一次 resume 时生成器还没有停在任何 yield 上，
+    // 所以第一次传入的参数不会被读到
+    pub fn resume(&mut self, arg: R) -> GeneratorState<Y, C> {
+        let future = self
+            .future
+            .as_mut()
+            .expect("generator resumed after completion");
+
+        self.airlock.borrow_mut().resume_arg = Some(arg);
+        // 生成器由 resume 同步驱动，不需要被唤醒，用空 waker 就行
+        let mut cx = Context::from_waker(Waker::noop());
+        let poll = future.as_mut().poll(&mut cx);
+
+        let mut airlock = self.airlock.borrow_mut();
+        airlock.resume_arg = None;
+        match poll {
+            Poll::Pending => match airlock.yielded.take() {
+                Some(value) => GeneratorState::Yielded(value),
+                None => panic!("generator awaited a future other than `Co::yield_`"),
+            },
+            Poll::Ready(value) => {
+                drop(airlock);
+                self.future = None;
+                GeneratorState::Complete(value)
+            }
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.future.is_none()
+    }
+}
+
+// resume 参数为 () 时，生成器就是一个迭代器，完成值被丢弃
+impl<Y, C> Iterator for Gen<Y, (), C> {
+    type Item = Y;
+
+    fn next(&mut self) -> Option<Y> {
+        if self.is_complete() {
+            return None;
+        }
+        match self.resume(()) {
+            GeneratorState::Yielded(value) => Some(value),
+            GeneratorState::Complete(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coroutine_rs_assertions_on_stable() {
+        let mut coroutine = gen(|co| async move {
+            co.yield_(1).await;
+            co.yield_(2).await;
+            co.yield_(3).await;
+        });
+
+        assert_eq!(coroutine.resume(()), GeneratorState::Yielded(1));
+        assert_eq!(coroutine.resume(()), GeneratorState::Yielded(2));
+        assert_eq!(coroutine.resume(()), GeneratorState::Yielded(3));
+        assert_eq!(coroutine.resume(()), GeneratorState::Complete(()));
+        assert!(coroutine.is_complete());
+    }
+
+    #[test]
+    fn generator_as_iterator() {
+        let fib = gen(|co| async move {
+            let (mut a, mut b) = (0u64, 1u64);
+            loop {
+                co.yield_(a).await;
+                (a, b) = (b, a + b);
+            }
+        });
+
+        let v: Vec<u64> = fib.take(8).collect();
+        assert_eq!(v, [0, 1, 1, 2, 3, 5, 8, 13]);
+    }
+
+    #[test]
+    fn resume_arguments_and_completion_value() {
+        // 累加器：每次 yield 出当前总和，resume 传进来下一个加数
+        let mut acc = gen(|co| async move {
+            let mut total = 0;
+            loop {
+                let n: i32 = co.yield_(total).await;
+                if n < 0 {
+                    return format!("total = {total}");
+                }
+                total += n;
+            }
+        });
+
+        assert_eq!(acc.resume(100), GeneratorState::Yielded(0)); // 第一次的参数被忽略
+        assert_eq!(acc.resume(1), GeneratorState::Yielded(1));
+        assert_eq!(acc.resume(2), GeneratorState::Yielded(3));
+        assert_eq!(
+            acc.resume(-1),
+            GeneratorState::Complete("total = 3".to_string())
+        );
+    }
+
+    #[test]
+    fn iterator_is_fused_after_completion() {
+        let mut g = gen(|co| async move {
+            co.yield_('a').await;
+        });
+        assert_eq!(g.next(), Some('a'));
+        assert_eq!(g.next(), None);
+        assert_eq!(g.next(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "other than `Co::yield_`")]
+    fn foreign_await_panics() {
+        let mut g = gen(|_co: Co<i32>| async move {
+            std::future::pending::<()>().await;
+        });
+        g.resume(());
+    }
+}
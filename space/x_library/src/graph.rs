@@ -0,0 +1,1064 @@
+// 有向图：enum-linked-list.rs 是链表，BTreeMap.rs 是映射，这里补上图
+//
+// 两种表示：
+// - `AdjList<N, E>`：邻接表，节点就是值本身（`BTreeMap<N, Vec<(N, E)>>`），可以增删节点
+// - `Graph<N, E>`：arena 表示，节点和边都放在 Vec 里，用 `NodeId` / `EdgeId` 下标互相引用。
+//   smart_pointers.md 里那种 `Rc<RefCell<Node>>` 连起来的图，一成环引用计数就归不了零，
+//   访问邻居还要处处 borrow；下标没有所有权，不会泄漏，也没有运行时借用检查。代价是不能删节点
+//
+// 算法只依赖 `Digraph` trait：bfs / dfs / dijkstra / shortest_path / toposort / scc，
+// `to_dot` 输出 Graphviz 的 DOT 文本。遍历顺序由 successors 的顺序决定，结果都是确定的。
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, VecDeque};
+use std::fmt::{self, Write};
+use std::ops::{Index, IndexMut};
+
+pub trait Digraph {
+    type Node: Clone + Ord;
+    type Edge;
+
+    fn nodes(&self) -> impl Iterator<Item = Self::Node> + '_;
+
+    // node 的出边，按加入的顺序
+    fn successors(&self, node: Self::Node) -> impl Iterator<Item = (Self::Node, &Self::Edge)> + '_;
+
+    fn node_count(&self) -> usize {
+        self.nodes().count()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct AdjList<N, E = ()> {
+    adj: BTreeMap<N, Vec<(N, E)>>,
+}
+
+impl<N: Ord + Clone, E> AdjList<N, E> {
+    pub fn new() -> Self {
+        AdjList {
+            adj: BTreeMap::new(),
+        }
+    }
+
+    // 已经存在时返回 false
+    pub fn add_node(&mut self, node: N) -> bool {
+        if self.adj.contains_key(&node) {
+            return false;
+        }
+        self.adj.insert(node, Vec::new());
+        true
+    }
+
+    // 两端的节点不存在时自动加上；允许重边和自环
+    pub fn add_edge(&mut self, from: N, to: N, edge: E) {
+        self.add_node(to.clone());
+        self.adj.entry(from).or_default().push((to, edge));
+    }
+
+    pub fn contains_node(&self, node: &N) -> bool {
+        self.adj.contains_key(node)
+    }
+
+    // from -> to 的第一条边
+    pub fn edge(&self, from: &N, to: &N) -> Option<&E> {
+        self.adj
+            .get(from)?
+            .iter()
+            .find(|(n, _)| n == to)
+            .map(|(_, e)| e)
+    }
+
+    pub fn remove_edge(&mut self, from: &N, to: &N) -> Option<E> {
+        let out = self.adj.get_mut(from)?;
+        let i = out.iter().position(|(n, _)| n == to)?;
+        Some(out.remove(i).1)
+    }
+
+    // 连同指向它的边一起删掉
+    pub fn remove_node(&mut self, node: &N) -> bool {
+        if self.adj.remove(node).is_none() {
+            return false;
+        }
+        for out in self.adj.values_mut() {
+            out.retain(|(n, _)| n != node);
+        }
+        true
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.adj.values().map(Vec::len).sum()
+    }
+
+    pub fn out_degree(&self, node: &N) -> usize {
+        self.adj.get(node).map_or(0, Vec::len)
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (&N, &N, &E)> + '_ {
+        self.adj
+            .iter()
+            .flat_map(|(from, out)| out.iter().map(move |(to, e)| (from, to, e)))
+    }
+
+    // 所有边反向
+    pub fn reversed(&self) -> Self
+    where
+        E: Clone,
+    {
+        let mut rev = AdjList::new();
+        for node in self.adj.keys() {
+            rev.add_node(node.clone());
+        }
+        for (from, to, e) in self.edges() {
+            rev.add_edge(to.clone(), from.clone(), e.clone());
+        }
+        rev
+    }
+
+    // 转成 arena 表示，顺便返回每个节点对应的下标
+    pub fn to_graph(&self) -> (Graph<N, E>, BTreeMap<N, NodeId>)
+    where
+        E: Clone,
+    {
+        let mut graph = Graph::with_capacity(self.adj.len(), self.edge_count());
+        let ids: BTreeMap<N, NodeId> = self
+            .adj
+            .keys()
+            .map(|n| (n.clone(), graph.add_node(n.clone())))
+            .collect();
+        for (from, to, e) in self.edges() {
+            graph.add_edge(ids[from], ids[to], e.clone());
+        }
+        (graph, ids)
+    }
+}
+
+impl<N: Ord + Clone, E> Default for AdjList<N, E> {
+    fn default() -> Self {
+        AdjList::new()
+    }
+}
+
+impl<N: Ord + Clone, E> Digraph for AdjList<N, E> {
+    type Node = N;
+    type Edge = E;
+
+    fn nodes(&self) -> impl Iterator<Item = N> + '_ {
+        self.adj.keys().cloned()
+    }
+
+    fn successors(&self, node: N) -> impl Iterator<Item = (N, &E)> + '_ {
+        self.adj
+            .get(&node)
+            .into_iter()
+            .flatten()
+            .map(|(n, e)| (n.clone(), e))
+    }
+
+    fn node_count(&self) -> usize {
+        self.adj.len()
+    }
+}
+
+impl<N: fmt::Debug, E: fmt::Debug> fmt::Debug for AdjList<N, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.adj.iter()).finish()
+    }
+}
+
+// 收集 (from, to, edge)
+impl<N: Ord + Clone, E> Extend<(N, N, E)> for AdjList<N, E> {
+    fn extend<I: IntoIterator<Item = (N, N, E)>>(&mut self, iter: I) {
+        for (from, to, edge) in iter {
+            self.add_edge(from, to, edge);
+        }
+    }
+}
+
+impl<N: Ord + Clone, E> FromIterator<(N, N, E)> for AdjList<N, E> {
+    fn from_iter<I: IntoIterator<Item = (N, N, E)>>(iter: I) -> Self {
+        let mut graph = AdjList::new();
+        graph.extend(iter);
+        graph
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EdgeId(usize);
+
+impl NodeId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl EdgeId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+struct EdgeData<E> {
+    from: NodeId,
+    to: NodeId,
+    weight: E,
+}
+
+#[derive(Debug, Clone)]
+pub struct Graph<N, E = ()> {
+    nodes: Vec<N>,
+    edges: Vec<EdgeData<E>>,
+    // 每个节点的出边，按加入的顺序
+    out: Vec<Vec<EdgeId>>,
+}
+
+impl<N, E> Graph<N, E> {
+    pub fn new() -> Self {
+        Graph::with_capacity(0, 0)
+    }
+
+    pub fn with_capacity(nodes: usize, edges: usize) -> Self {
+        Graph {
+            nodes: Vec::with_capacity(nodes),
+            edges: Vec::with_capacity(edges),
+            out: Vec::with_capacity(nodes),
+        }
+    }
+
+    pub fn add_node(&mut self, weight: N) -> NodeId {
+        self.nodes.push(weight);
+        self.out.push(Vec::new());
+        NodeId(self.nodes.len() - 1)
+    }
+
+    // 下标不属于这个图时 panic
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: E) -> EdgeId {
+        let count = self.nodes.len();
+        assert!(
+            from.0 < count && to.0 < count,
+            "edge {from:?} -> {to:?} out of bounds for {count} nodes"
+        );
+        let id = EdgeId(self.edges.len());
+        self.edges.push(EdgeData { from, to, weight });
+        self.out[from.0].push(id);
+        id
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.nodes.len()).map(NodeId)
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&N> {
+        self.nodes.get(id.0)
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut N> {
+        self.nodes.get_mut(id.0)
+    }
+
+    // 第一个满足条件的节点
+    pub fn find_node(&self, pred: impl FnMut(&N) -> bool) -> Option<NodeId> {
+        self.nodes.iter().position(pred).map(NodeId)
+    }
+
+    pub fn endpoints(&self, id: EdgeId) -> (NodeId, NodeId) {
+        let edge = &self.edges[id.0];
+        (edge.from, edge.to)
+    }
+
+    pub fn find_edge(&self, from: NodeId, to: NodeId) -> Option<EdgeId> {
+        self.out
+            .get(from.0)?
+            .iter()
+            .copied()
+            .find(|e| self.edges[e.0].to == to)
+    }
+
+    pub fn edges_from(&self, from: NodeId) -> impl Iterator<Item = (EdgeId, NodeId, &E)> + '_ {
+        self.out[from.0].iter().map(|&id| {
+            let edge = &self.edges[id.0];
+            (id, edge.to, &edge.weight)
+        })
+    }
+
+    // 节点和边的权重都换掉，结构不变
+    pub fn map<N2, E2>(
+        &self,
+        mut node: impl FnMut(NodeId, &N) -> N2,
+        mut edge: impl FnMut(EdgeId, &E) -> E2,
+    ) -> Graph<N2, E2> {
+        Graph {
+            nodes: self
+                .nodes
+                .iter()
+                .enumerate()
+                .map(|(i, n)| node(NodeId(i), n))
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .enumerate()
+                .map(|(i, e)| EdgeData {
+                    from: e.from,
+                    to: e.to,
+                    weight: edge(EdgeId(i), &e.weight),
+                })
+                .collect(),
+            out: self.out.clone(),
+        }
+    }
+}
+
+impl<N, E> Default for Graph<N, E> {
+    fn default() -> Self {
+        Graph::new()
+    }
+}
+
+impl<N, E> Index<NodeId> for Graph<N, E> {
+    type Output = N;
+
+    fn index(&self, id: NodeId) -> &N {
+        &self.nodes[id.0]
+    }
+}
+
+impl<N, E> IndexMut<NodeId> for Graph<N, E> {
+    fn index_mut(&mut self, id: NodeId) -> &mut N {
+        &mut self.nodes[id.0]
+    }
+}
+
+impl<N, E> Index<EdgeId> for Graph<N, E> {
+    type Output = E;
+
+    fn index(&self, id: EdgeId) -> &E {
+        &self.edges[id.0].weight
+    }
+}
+
+impl<N, E> IndexMut<EdgeId> for Graph<N, E> {
+    fn index_mut(&mut self, id: EdgeId) -> &mut E {
+        &mut self.edges[id.0].weight
+    }
+}
+
+impl<N, E> Digraph for Graph<N, E> {
+    type Node = NodeId;
+    type Edge = E;
+
+    fn nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.node_ids()
+    }
+
+    fn successors(&self, node: NodeId) -> impl Iterator<Item = (NodeId, &E)> + '_ {
+        self.edges_from(node).map(|(_, to, e)| (to, e))
+    }
+
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+// 广度优先，返回从 start 能到达的节点，按距离由近到远
+pub fn bfs<G: Digraph>(graph: &G, start: G::Node) -> Vec<G::Node> {
+    let mut seen = BTreeSet::from([start.clone()]);
+    let mut queue = VecDeque::from([start]);
+    let mut order = Vec::new();
+    while let Some(node) = queue.pop_front() {
+        for (next, _) in graph.successors(node.clone()) {
+            if seen.insert(next.clone()) {
+                queue.push_back(next);
+            }
+        }
+        order.push(node);
+    }
+    order
+}
+
+// 深度优先的先序，和递归写法的顺序相同，但用显式的栈，长链也不会爆栈
+pub fn dfs<G: Digraph>(graph: &G, start: G::Node) -> Vec<G::Node> {
+    let mut seen = BTreeSet::new();
+    let mut stack = vec![start];
+    let mut order = Vec::new();
+    while let Some(node) = stack.pop() {
+        if !seen.insert(node.clone()) {
+            continue;
+        }
+        // 倒着压栈，先弹出第一个邻居
+        let next: Vec<_> = graph.successors(node.clone()).map(|(n, _)| n).collect();
+        stack.extend(next.into_iter().rev().filter(|n| !seen.contains(n)));
+        order.push(node);
+    }
+    order
+}
+
+// 从 start 到每个可达节点的最短距离和路径上的前一个节点；cost 不能为负，所以用 u64
+pub fn dijkstra<G: Digraph>(
+    graph: &G,
+    start: G::Node,
+    cost: impl Fn(&G::Edge) -> u64,
+) -> BTreeMap<G::Node, (u64, Option<G::Node>)> {
+    let mut best = BTreeMap::from([(start.clone(), (0, None))]);
+    let mut done = BTreeSet::new();
+    let mut heap = BinaryHeap::from([Reverse((0u64, start))]);
+    while let Some(Reverse((dist, node))) = heap.pop() {
+        // 同一个节点可能在堆里有多份，只处理最先弹出（最短）的那份
+        if !done.insert(node.clone()) {
+            continue;
+        }
+        for (next, edge) in graph.successors(node.clone()) {
+            let candidate = dist.saturating_add(cost(edge));
+            let shorter = best.get(&next).is_none_or(|&(d, _)| candidate < d);
+            if shorter {
+                best.insert(next.clone(), (candidate, Some(node.clone())));
+                heap.push(Reverse((candidate, next)));
+            }
+        }
+    }
+    best
+}
+
+// 最短路径的总代价和经过的节点（包括两端）；到不了时返回 None
+pub fn shortest_path<G: Digraph>(
+    graph: &G,
+    from: G::Node,
+    to: G::Node,
+    cost: impl Fn(&G::Edge) -> u64,
+) -> Option<(u64, Vec<G::Node>)> {
+    let best = dijkstra(graph, from, cost);
+    let (total, _) = *best.get(&to)?;
+    let mut path = vec![to];
+    while let Some((_, Some(prev))) = best.get(path.last().unwrap()) {
+        path.push(prev.clone());
+    }
+    path.reverse();
+    Some((total, path))
+}
+
+// toposort 遇到环时返回环上的节点，按边的方向排列，首尾相接
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<N> {
+    pub nodes: Vec<N>,
+}
+
+impl<N: fmt::Debug> fmt::Display for Cycle<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("graph has a cycle: ")?;
+        for node in &self.nodes {
+            write!(f, "{node:?} -> ")?;
+        }
+        write!(f, "{:?}", self.nodes[0])
+    }
+}
+
+impl<N: fmt::Debug> std::error::Error for Cycle<N> {}
+
+// 拓扑排序（Kahn 算法）：每条边 a -> b 中 a 都排在 b 前面。
+// 入度同时为 0 的节点按 nodes() 的顺序输出，结果是确定的
+pub fn toposort<G: Digraph>(graph: &G) -> Result<Vec<G::Node>, Cycle<G::Node>> {
+    let mut in_degree: BTreeMap<G::Node, usize> = graph.nodes().map(|n| (n, 0)).collect();
+    for node in graph.nodes() {
+        for (next, _) in graph.successors(node) {
+            *in_degree.entry(next).or_default() += 1;
+        }
+    }
+
+    let mut ready: VecDeque<_> = graph.nodes().filter(|n| in_degree[n] == 0).collect();
+    let mut order = Vec::with_capacity(in_degree.len());
+    while let Some(node) = ready.pop_front() {
+        for (next, _) in graph.successors(node.clone()) {
+            let degree = in_degree.get_mut(&next).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                ready.push_back(next);
+            }
+        }
+        order.push(node);
+    }
+
+    if order.len() == in_degree.len() {
+        return Ok(order);
+    }
+    in_degree.retain(|_, d| *d > 0);
+    Err(find_cycle(graph, in_degree.into_keys().collect()))
+}
+
+// 剩下的节点每个都至少有一条入边来自剩下的节点，沿入边往回走一定会绕回来
+fn find_cycle<G: Digraph>(graph: &G, remaining: BTreeSet<G::Node>) -> Cycle<G::Node> {
+    let mut pred = BTreeMap::new();
+    for node in &remaining {
+        for (next, _) in graph.successors(node.clone()) {
+            if remaining.contains(&next) {
+                pred.entry(next).or_insert_with(|| node.clone());
+            }
+        }
+    }
+
+    let mut path = Vec::new();
+    let mut position = BTreeMap::new();
+    let mut node = remaining.into_iter().next().unwrap();
+    while !position.contains_key(&node) {
+        position.insert(node.clone(), path.len());
+        path.push(node.clone());
+        node = pred[&node].clone();
+    }
+    let mut nodes = path.split_off(position[&node]);
+    // 沿入边收集的，反过来才是边的方向；再转到从最小的节点开始，输出稳定
+    nodes.reverse();
+    let first = (0..nodes.len()).min_by_key(|&i| &nodes[i]).unwrap();
+    nodes.rotate_left(first);
+    Cycle { nodes }
+}
+
+// 强连通分量（Tarjan 算法，显式栈）。分量按逆拓扑序输出：
+// 一个分量能到达的其他分量都排在它前面；分量内部按发现的顺序
+pub fn scc<G: Digraph>(graph: &G) -> Vec<Vec<G::Node>> {
+    struct State<N> {
+        index: BTreeMap<N, usize>,
+        low: BTreeMap<N, usize>,
+        stack: Vec<N>,
+        on_stack: BTreeSet<N>,
+    }
+
+    impl<N: Clone + Ord> State<N> {
+        fn visit(&mut self, node: &N) {
+            let i = self.index.len();
+            self.index.insert(node.clone(), i);
+            self.low.insert(node.clone(), i);
+            self.stack.push(node.clone());
+            self.on_stack.insert(node.clone());
+        }
+
+        fn lower(&mut self, node: &N, to: usize) {
+            let low = self.low.get_mut(node).unwrap();
+            *low = (*low).min(to);
+        }
+    }
+
+    let mut state = State {
+        index: BTreeMap::new(),
+        low: BTreeMap::new(),
+        stack: Vec::new(),
+        on_stack: BTreeSet::new(),
+    };
+    let mut components = Vec::new();
+
+    for root in graph.nodes() {
+        if state.index.contains_key(&root) {
+            continue;
+        }
+        state.visit(&root);
+        let successors = |n: &G::Node| -> Vec<G::Node> {
+            graph.successors(n.clone()).map(|(next, _)| next).collect()
+        };
+        // 模拟递归：每一层记下节点和还没看过的邻居
+        let mut calls = vec![(root.clone(), successors(&root).into_iter())];
+        while let Some((node, pending)) = calls.last_mut() {
+            let node = node.clone();
+            if let Some(next) = pending.next() {
+                if !state.index.contains_key(&next) {
+                    state.visit(&next);
+                    calls.push((next.clone(), successors(&next).into_iter()));
+                } else if state.on_stack.contains(&next) {
+                    let to = state.index[&next];
+                    state.lower(&node, to);
+                }
+                continue;
+            }
+
+            calls.pop();
+            let low = state.low[&node];
+            if let Some((parent, _)) = calls.last() {
+                state.lower(parent, low);
+            }
+            if low == state.index[&node] {
+                let at = state.stack.iter().rposition(|n| *n == node).unwrap();
+                let component = state.stack.split_off(at);
+                for n in &component {
+                    state.on_stack.remove(n);
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+// Graphviz DOT 文本。节点按 nodes() 的顺序编号为 n0, n1, …；
+// edge_label 返回空字符串时这条边不加标签
+pub fn to_dot<G: Digraph>(
+    graph: &G,
+    name: &str,
+    node_label: impl Fn(&G::Node) -> String,
+    edge_label: impl Fn(&G::Edge) -> String,
+) -> String {
+    let ids: BTreeMap<G::Node, usize> = graph.nodes().zip(0..).collect();
+    let mut out = String::new();
+    // 写进 String 不会失败
+    writeln!(out, "digraph {} {{", quote(name)).unwrap();
+    for node in graph.nodes() {
+        let label = quote(&node_label(&node));
+        writeln!(out, "    n{} [label={label}];", ids[&node]).unwrap();
+    }
+    for node in graph.nodes() {
+        for (next, edge) in graph.successors(node.clone()) {
+            write!(out, "    n{} -> n{}", ids[&node], ids[&next]).unwrap();
+            let label = edge_label(edge);
+            if !label.is_empty() {
+                write!(out, " [label={}]", quote(&label)).unwrap();
+            }
+            out.push_str(";\n");
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::lcg;
+    use std::fs;
+    use std::path::Path;
+
+    // n 个节点的随机图，边权 1..=9
+    fn random_graph(seed: u64, n: usize, edges: usize) -> AdjList<usize, u64> {
+        let mut seed = seed;
+        let mut graph = AdjList::new();
+        for i in 0..n {
+            graph.add_node(i);
+        }
+        for _ in 0..edges {
+            let (a, b) = (lcg(&mut seed) as usize % n, lcg(&mut seed) as usize % n);
+            graph.add_edge(a, b, lcg(&mut seed) % 9 + 1);
+        }
+        graph
+    }
+
+    // 1 -> 2 -> 4, 1 -> 3 -> 4 -> 5, 6 孤立
+    fn diamond() -> AdjList<u32> {
+        let mut graph: AdjList<u32> = [(1, 2), (1, 3), (2, 4), (3, 4), (4, 5)]
+            .into_iter()
+            .map(|(a, b)| (a, b, ()))
+            .collect();
+        graph.add_node(6);
+        graph
+    }
+
+    fn reachable<G: Digraph>(graph: &G, from: G::Node) -> BTreeSet<G::Node> {
+        bfs(graph, from).into_iter().collect()
+    }
+
+    #[test]
+    fn adj_list_edits() {
+        let mut graph = diamond();
+        assert_eq!((graph.node_count(), graph.edge_count()), (6, 5));
+        assert!(!graph.add_node(1));
+        assert_eq!(graph.out_degree(&1), 2);
+        assert_eq!(graph.edge(&3, &4), Some(&()));
+        assert_eq!(graph.remove_edge(&3, &4), Some(()));
+        assert_eq!(graph.remove_edge(&3, &4), None);
+
+        assert!(graph.remove_node(&4));
+        assert!(!graph.contains_node(&4));
+        assert_eq!(graph.edge_count(), 2);
+        assert_eq!(
+            format!("{graph:?}"),
+            "{1: [(2, ()), (3, ())], 2: [], 3: [], 5: [], 6: []}"
+        );
+
+        let rev = diamond().reversed();
+        assert_eq!(rev.edge_count(), 5);
+        assert!(rev.edge(&5, &4).is_some());
+        assert_eq!(rev.reversed(), diamond());
+    }
+
+    #[test]
+    fn arena_ids_and_weights() {
+        let mut graph: Graph<&str, u32> = Graph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        let ab = graph.add_edge(a, b, 5);
+        graph.add_edge(a, c, 1);
+        graph.add_edge(c, b, 1);
+
+        assert_eq!(graph[b], "b");
+        assert_eq!(graph[ab], 5);
+        graph[ab] += 1;
+        assert_eq!(graph.endpoints(ab), (a, b));
+        assert_eq!(graph.find_edge(c, b).map(EdgeId::index), Some(2));
+        assert_eq!(graph.find_edge(b, c), None);
+        assert_eq!(graph.find_node(|n| *n == "c"), Some(c));
+        assert!(graph.successors(a).map(|(n, _)| n).eq([b, c]));
+
+        // 换权重，结构不变
+        let upper = graph.map(|_, n| n.to_uppercase(), |_, w| w * 10);
+        assert_eq!(upper[c], "C");
+        assert_eq!(
+            shortest_path(&upper, a, b, |w| *w as u64),
+            Some((20, vec![a, c, b]))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds for 1 nodes")]
+    fn arena_rejects_foreign_ids() {
+        let mut graph: Graph<(), ()> = Graph::new();
+        let a = graph.add_node(());
+        graph.add_edge(a, NodeId(3), ());
+    }
+
+    #[test]
+    fn bfs_and_dfs_orders() {
+        let graph = diamond();
+        assert_eq!(bfs(&graph, 1), [1, 2, 3, 4, 5]);
+        assert_eq!(dfs(&graph, 1), [1, 2, 4, 5, 3]);
+        assert_eq!(bfs(&graph, 6), [6]);
+        // 不存在的起点只返回它自己
+        assert_eq!(dfs(&graph, 9), [9]);
+
+        // arena 表示上遍历顺序一样
+        let (arena, ids) = graph.to_graph();
+        let names = |order: Vec<NodeId>| order.into_iter().map(|id| arena[id]).collect::<Vec<_>>();
+        assert_eq!(names(bfs(&arena, ids[&1])), [1, 2, 3, 4, 5]);
+        assert_eq!(names(dfs(&arena, ids[&1])), [1, 2, 4, 5, 3]);
+    }
+
+    #[test]
+    fn dfs_handles_long_chains() {
+        let mut graph = Graph::new();
+        let mut prev = graph.add_node(0);
+        for i in 1..100_000 {
+            let next = graph.add_node(i);
+            graph.add_edge(prev, next, ());
+            prev = next;
+        }
+        assert_eq!(dfs(&graph, NodeId(0)).len(), 100_000);
+        assert_eq!(scc(&graph).len(), 100_000);
+    }
+
+    // 和 Bellman-Ford 的结果对比
+    #[test]
+    fn dijkstra_matches_bellman_ford() {
+        for seed in 0..20 {
+            let graph = random_graph(seed, 12, 30);
+            let best = dijkstra(&graph, 0, |w| *w);
+
+            let mut dist: BTreeMap<usize, u64> = BTreeMap::from([(0, 0)]);
+            for _ in 0..graph.node_count() {
+                for (a, b, w) in graph.edges() {
+                    if let Some(&d) = dist.get(a) {
+                        let e = dist.entry(*b).or_insert(u64::MAX);
+                        *e = (*e).min(d + w);
+                    }
+                }
+            }
+            let ours: BTreeMap<usize, u64> = best.iter().map(|(n, (d, _))| (*n, *d)).collect();
+            assert_eq!(ours, dist, "seed {seed}");
+
+            // 沿着前驱走出来的路径，代价之和等于最短距离
+            for (&target, &(total, _)) in &best {
+                let (cost, path) = shortest_path(&graph, 0, target, |w| *w).unwrap();
+                assert_eq!(cost, total);
+                let sum: u64 = path
+                    .windows(2)
+                    .map(|w| {
+                        graph
+                            .successors(w[0])
+                            .filter(|(n, _)| *n == w[1])
+                            .map(|(_, c)| *c)
+                            .min()
+                            .unwrap()
+                    })
+                    .sum();
+                assert_eq!(sum, total);
+            }
+        }
+        assert_eq!(shortest_path(&diamond(), 5, 1, |_| 1), None);
+    }
+
+    #[test]
+    fn toposort_orders_every_edge() {
+        for seed in 0..20 {
+            // 只从小编号连向大编号，一定没有环
+            let mut graph = random_graph(seed, 15, 40);
+            let back: Vec<_> = graph
+                .edges()
+                .filter(|(a, b, _)| a >= b)
+                .map(|(a, b, _)| (*a, *b))
+                .collect();
+            for (a, b) in back {
+                while graph.remove_edge(&a, &b).is_some() {}
+            }
+            let order = toposort(&graph).unwrap();
+            let position: BTreeMap<_, _> = order.iter().zip(0..).collect();
+            assert_eq!(position.len(), graph.node_count());
+            for (a, b, _) in graph.edges() {
+                assert!(position[a] < position[b]);
+            }
+        }
+        assert_eq!(toposort(&diamond()), Ok(vec![1, 6, 2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn toposort_reports_a_cycle() {
+        let mut graph = diamond();
+        graph.add_edge(5, 3, ());
+        let cycle = toposort(&graph).unwrap_err();
+        assert_eq!(cycle.nodes, [3, 4, 5]);
+        assert_eq!(cycle.to_string(), "graph has a cycle: 3 -> 4 -> 5 -> 3");
+
+        graph.add_edge(6, 6, ());
+        assert_eq!(toposort(&graph).unwrap_err().nodes, [3, 4, 5]);
+        graph.remove_edge(&5, &3);
+        assert_eq!(toposort(&graph).unwrap_err().nodes, [6]);
+    }
+
+    #[test]
+    fn scc_groups_mutually_reachable_nodes() {
+        let graph: AdjList<char> = [
+            ('a', 'b'),
+            ('b', 'c'),
+            ('c', 'a'),
+            ('b', 'd'),
+            ('d', 'e'),
+            ('e', 'd'),
+            ('f', 'e'),
+        ]
+        .into_iter()
+        .map(|(a, b)| (a, b, ()))
+        .collect();
+        assert_eq!(
+            scc(&graph),
+            [vec!['d', 'e'], vec!['a', 'b', 'c'], vec!['f']]
+        );
+
+        for seed in 0..20 {
+            let graph = random_graph(seed, 10, 18);
+            let components = scc(&graph);
+            let mut which = BTreeMap::new();
+            for (i, component) in components.iter().enumerate() {
+                for n in component {
+                    assert!(which.insert(*n, i).is_none());
+                }
+            }
+            assert_eq!(which.len(), 10);
+
+            let reach: Vec<_> = (0..10).map(|n| reachable(&graph, n)).collect();
+            for a in 0..10 {
+                for b in 0..10 {
+                    let mutual = reach[a].contains(&b) && reach[b].contains(&a);
+                    assert_eq!(which[&a] == which[&b], mutual, "seed {seed}: {a} {b}");
+                }
+            }
+            // 逆拓扑序：边指向的分量排在前面
+            for (a, b, _) in graph.edges() {
+                assert!(which[b] <= which[a]);
+            }
+        }
+    }
+
+    #[test]
+    fn dot_output() {
+        let mut graph: AdjList<&str, &str> = AdjList::new();
+        graph.add_edge("main", "say \"hi\"", "calls");
+        graph.add_edge("main", "main", "");
+        let dot = to_dot(&graph, "demo", |n| n.to_string(), |e| e.to_string());
+        assert_eq!(
+            dot,
+            "digraph \"demo\" {\n    n0 [label=\"main\"];\n    n1 [label=\"say \\\"hi\\\"\"];\n    \
+             n0 -> n1 [label=\"calls\"];\n    n0 -> n0;\n}\n"
+        );
+    }
+
+    // space 工作区的依赖图：workspace 成员、crate 之间的 Cargo 依赖、mod 声明、模块之间的 crate:: 引用。
+    // 图从源码现读出来，加模块、改引用都不用改测试；测试只断言不太会变的几条边和「没有环」
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Dep {
+        Member,
+        Crate,
+        Mod,
+        Use,
+    }
+
+    // 源码里出现的 `crate::模块` 引用
+    fn crate_refs(source: &str, modules: &[String], this: &str) -> BTreeSet<String> {
+        source
+            .match_indices("crate::")
+            .map(|(i, m)| {
+                let rest = &source[i + m.len()..];
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                rest[..end].to_string()
+            })
+            .filter(|name| name != this && modules.contains(name))
+            .collect()
+    }
+
+    fn space() -> AdjList<String, Dep> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+        let read = |path: &str| fs::read_to_string(root.join(path)).unwrap();
+
+        // members = [ "x_library", # 注释 ... ]
+        let workspace = read("Cargo.toml");
+        let list = workspace.split("members = [").nth(1).unwrap();
+        let members: Vec<_> = list[..list.find(']').unwrap()]
+            .lines()
+            .filter_map(|line| line.split('"').nth(1))
+            .map(String::from)
+            .collect();
+
+        let mut graph = AdjList::new();
+        for member in &members {
+            graph.add_edge("space".into(), member.clone(), Dep::Member);
+            let manifest = read(&format!("{member}/Cargo.toml"));
+            for other in &members {
+                if manifest.contains(&format!("\n{other} = ")) {
+                    graph.add_edge(member.clone(), other.clone(), Dep::Crate);
+                }
+            }
+
+            // 二进制 crate 没有 lib.rs
+            let lib = fs::read_to_string(root.join(format!("{member}/src/lib.rs")));
+            let modules: Vec<_> = lib
+                .unwrap_or_default()
+                .lines()
+                .filter_map(|line| line.trim_start_matches("pub ").strip_prefix("mod "))
+                .filter_map(|rest| rest.strip_suffix(';'))
+                .map(String::from)
+                .collect();
+            for module in &modules {
+                let from = format!("{member}::{module}");
+                graph.add_edge(member.clone(), from.clone(), Dep::Mod);
+                let source = fs::read_to_string(root.join(format!("{member}/src/{module}.rs")))
+                    .unwrap_or_else(|_| read(&format!("{member}/src/{module}/mod.rs")));
+                for to in crate_refs(&source, &modules, module) {
+                    graph.add_edge(from.clone(), format!("{member}::{to}"), Dep::Use);
+                }
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn space_workspace_fixture() {
+        let graph = space();
+        let has = |from: &str, to: &str, dep| graph.edge(&from.into(), &to.into()) == Some(&dep);
+        assert!(has("space", "x_library", Dep::Member));
+        assert!(has("space", "x_pool", Dep::Member));
+        assert!(has("x_binary", "x_library", Dep::Crate));
+        assert!(has("x_library", "x_library::graph", Dep::Mod));
+        assert!(has("x_pool", "x_pool::scope", Dep::Mod));
+        assert!(has("x_library::cache", "x_library::admission", Dep::Use));
+        assert!(has("x_library::collect", "x_library::graph", Dep::Use));
+        assert!(has("x_pool::scope", "x_pool::pool", Dep::Use));
+
+        // crate_refs 把注释和字符串里的 `crate::` 也算上，代码库里有没有环说不准，
+        // 所以不断言没有环，只检查几个算法对这张图的结论互相一致
+        let components = scc(&graph);
+        let component: BTreeMap<_, _> = components
+            .iter()
+            .enumerate()
+            .flat_map(|(i, c)| c.iter().map(move |n| (n.clone(), i)))
+            .collect();
+        let reach: BTreeMap<_, _> = component
+            .keys()
+            .map(|n| (n, reachable(&graph, n.clone())))
+            .collect();
+        for (a, from_a) in &reach {
+            for (b, from_b) in &reach {
+                let mutual = from_a.contains(*b) && from_b.contains(*a);
+                assert_eq!(component[*a] == component[*b], mutual, "{a} / {b}");
+            }
+        }
+        // 能排出拓扑序当且仅当没有多节点的分量（没有自环），排出来的每条边都从前往后
+        match toposort(&graph) {
+            Ok(order) => {
+                assert!(components.iter().all(|c| c.len() == 1));
+                let position: BTreeMap<_, _> =
+                    order.iter().enumerate().map(|(i, n)| (n, i)).collect();
+                assert!(graph.edges().all(|(a, b, _)| position[a] < position[b]));
+            }
+            Err(_) => assert!(components.iter().any(|c| c.len() > 1)),
+        }
+
+        assert_eq!(bfs(&graph, "space".into()).len(), graph.node_count());
+        assert_eq!(
+            shortest_path(
+                &graph,
+                "x_binary".into(),
+                "x_library::admission".into(),
+                |_| 1
+            ),
+            Some((
+                2,
+                vec![
+                    "x_binary".into(),
+                    "x_library".into(),
+                    "x_library::admission".into()
+                ]
+            ))
+        );
+        // 只走 use 边时，admission 至少被 cache 引用
+        assert!(graph
+            .reversed()
+            .successors("x_library::admission".into())
+            .any(|(n, dep)| n == "x_library::cache" && *dep == Dep::Use));
+
+        // 同样的图放进 arena 表示，结果一致
+        let (arena, ids) = graph.to_graph();
+        assert_eq!(scc(&arena).len(), components.len());
+        assert_eq!(
+            reachable(&arena, ids["x_pool"]).len(),
+            reachable(&graph, "x_pool".into()).len()
+        );
+
+        let dot = to_dot(
+            &graph,
+            "space",
+            |n| n.clone(),
+            |dep| match dep {
+                Dep::Use => "use".into(),
+                Dep::Crate => "depends".into(),
+                _ => String::new(),
+            },
+        );
+        assert!(dot.starts_with("digraph \"space\" {\n"));
+        assert_eq!(dot.matches(" -> ").count(), graph.edge_count());
+        let uses = graph
+            .edges()
+            .filter(|(_, _, dep)| **dep == Dep::Use)
+            .count();
+        assert_eq!(dot.matches("[label=\"use\"]").count(), uses);
+        assert!(dot.contains("[label=\"x_pool::scope\"]"));
+    }
+}
@@ -0,0 +1,332 @@
+// RAII.md 的工具箱：把「离开作用域时做点什么」包装成守卫
+//
+// - `ScopeGuard` / `defer!`：离开作用域时执行闭包；`defer_on_unwind!` 只在 panic 展开时执行
+// - `TempDir`：临时目录，drop 时整个删掉
+// - `LockFile`：用 create_new 抢占的锁文件，drop 时删除
+// - `Transaction`：登记回滚动作，没有 `commit()` 就按相反顺序回滚
+//
+// 和 methods-static-and-instance.rs 里 `Pair::destroy(self)` 一样，`commit` / `keep` / `dismiss`
+// 都按值接收 self，调用之后守卫就没了，不会再走 drop 里的清理逻辑。
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum When {
+    Always,
+    // 只在 panic 展开时执行，正常离开作用域不执行
+    OnUnwind,
+    // 只在正常离开作用域时执行
+    OnSuccess,
+}
+
+pub struct ScopeGuard<F: FnOnce()> {
+    f: Option<F>,
+    when: When,
+}
+
+impl<F: FnOnce()> ScopeGuard<F> {
+    pub fn new(f: F) -> Self {
+        ScopeGuard::with(When::Always, f)
+    }
+
+    pub fn with(when: When, f: F) -> Self {
+        ScopeGuard { f: Some(f), when }
+    }
+
+    // 取消守卫，闭包不再执行
+    pub fn dismiss(mut self) {
+        self.f = None;
+    }
+}
+
+impl<F: FnOnce()> Drop for ScopeGuard<F> {
+    fn drop(&mut self) {
+        let run = match self.when {
+            When::Always => true,
+            When::OnUnwind => thread::panicking(),
+            When::OnSuccess => !thread::panicking(),
+        };
+        if let Some(f) = self.f.take() {
+            if run {
+                f();
+            }
+        }
+    }
+}
+
+// defer! { ... } —— 当前作用域结束时执行，多个 defer 按声明的相反顺序执行
+#[macro_export]
+macro_rules! defer {
+    ($($body:tt)*) => {
+        let _guard = $crate::guard::ScopeGuard::new(|| { $($body)* });
+    };
+}
+
+#[macro_export]
+macro_rules! defer_on_unwind {
+    ($($body:tt)*) => {
+        let _guard = $crate::guard::ScopeGuard::with($crate::guard::When::OnUnwind, || { $($body)* });
+    };
+}
+
+// 同一进程里多次创建时避免重名
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+fn unique_name(prefix: &str) -> String {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    format!("{prefix}-{}-{id}", std::process::id())
+}
+
+#[derive(Debug)]
+pub struct TempDir {
+    path: Option<PathBuf>,
+}
+
+impl TempDir {
+    pub fn new(prefix: &str) -> io::Result<TempDir> {
+        TempDir::new_in(std::env::temp_dir(), prefix)
+    }
+
+    pub fn new_in(parent: impl AsRef<Path>, prefix: &str) -> io::Result<TempDir> {
+        let path = parent.as_ref().join(unique_name(prefix));
+        fs::create_dir(&path)?;
+        Ok(TempDir { path: Some(path) })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.path.as_deref().unwrap()
+    }
+
+    // 保留目录，返回路径，之后由调用方负责删除
+    pub fn keep(mut self) -> PathBuf {
+        self.path.take().unwrap()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            // drop 里没法返回错误，删不掉也只能放弃
+            let _ = fs::remove_dir_all(path);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LockFile {
+    path: PathBuf,
+}
+
+impl LockFile {
+    // 文件已存在说明锁被别人持有，返回 AlreadyExists
+    pub fn acquire(path: impl AsRef<Path>) -> io::Result<LockFile> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        // 文件一建出来就交给守卫：后面写 pid 失败返回错误时，守卫 drop 会把文件删掉，
+        // 不然这个锁就永远残留了
+        let lock = LockFile { path };
+        // 写入持有者的 pid，方便排查残留的锁
+        writeln!(file, "{}", std::process::id())?;
+        Ok(lock)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// 每做一步就登记一个撤销动作，没有 commit 就在 drop 时倒序撤销
+pub struct Transaction<'a> {
+    undo: Vec<Box<dyn FnOnce() + 'a>>,
+}
+
+impl<'a> Transaction<'a> {
+    pub fn new() -> Self {
+        Transaction { undo: Vec::new() }
+    }
+
+    pub fn on_rollback(&mut self, f: impl FnOnce() + 'a) {
+        self.undo.push(Box::new(f));
+    }
+
+    pub fn commit(mut self) {
+        self.undo.clear();
+    }
+
+    pub fn rollback(self) {
+        // 交给 drop 处理
+    }
+}
+
+impl Default for Transaction<'_> {
+    fn default() -> Self {
+        Transaction::new()
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        while let Some(f) = self.undo.pop() {
+            f();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    type Log = Rc<RefCell<Vec<&'static str>>>;
+
+    fn log() -> Log {
+        Rc::new(RefCell::new(Vec::new()))
+    }
+
+    fn push(log: &Log, event: &'static str) {
+        log.borrow_mut().push(event);
+    }
+
+    struct Noisy(Log, &'static str);
+
+    impl Drop for Noisy {
+        fn drop(&mut self) {
+            push(&self.0, self.1);
+        }
+    }
+
+    #[test]
+    fn defer_runs_in_reverse_order() {
+        let events = log();
+        {
+            let _a = Noisy(events.clone(), "drop a");
+            defer! { push(&events, "defer 1") }
+            let _b = Noisy(events.clone(), "drop b");
+            defer! { push(&events, "defer 2") }
+            defer_on_unwind! { push(&events, "unwind only") }
+            push(&events, "body");
+        }
+        assert_eq!(
+            *events.borrow(),
+            ["body", "defer 2", "drop b", "defer 1", "drop a"]
+        );
+    }
+
+    #[test]
+    fn unwind_only_guard_runs_on_panic() {
+        let events = log();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            defer! { push(&events, "always") }
+            defer_on_unwind! { push(&events, "unwinding") }
+            let _success = ScopeGuard::with(When::OnSuccess, || push(&events, "success"));
+            push(&events, "before panic");
+            panic!("boom");
+        }));
+        assert!(result.is_err());
+        assert_eq!(*events.borrow(), ["before panic", "unwinding", "always"]);
+    }
+
+    #[test]
+    fn dismissed_guard_does_nothing() {
+        let events = log();
+        let guard = ScopeGuard::new(|| push(&events, "cleanup"));
+        guard.dismiss();
+        assert!(events.borrow().is_empty());
+    }
+
+    #[test]
+    fn temp_dir_removed_on_drop_and_on_panic() {
+        let dir = TempDir::new("x_library-guard").unwrap();
+        let path = dir.path().to_path_buf();
+        fs::create_dir(path.join("nested")).unwrap();
+        fs::write(path.join("nested/file.txt"), "data").unwrap();
+        drop(dir);
+        assert!(!path.exists());
+
+        let mut seen = None;
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+            let dir = TempDir::new("x_library-guard").unwrap();
+            seen = Some(dir.path().to_path_buf());
+            panic!("boom");
+        }));
+        assert!(!seen.unwrap().exists());
+
+        let kept = TempDir::new("x_library-guard").unwrap().keep();
+        assert!(kept.exists());
+        fs::remove_dir(kept).unwrap();
+    }
+
+    #[test]
+    fn lock_file_is_exclusive() {
+        let dir = TempDir::new("x_library-lock").unwrap();
+        let path = dir.path().join("app.lock");
+
+        let lock = LockFile::acquire(&path).unwrap();
+        let content = fs::read_to_string(lock.path()).unwrap();
+        assert_eq!(content.trim(), std::process::id().to_string());
+
+        let err = LockFile::acquire(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+
+        drop(lock);
+        assert!(!path.exists());
+        assert!(LockFile::acquire(&path).is_ok());
+    }
+
+    #[test]
+    fn transaction_rolls_back_unless_committed() {
+        let events = log();
+        let balance = RefCell::new(100);
+        {
+            let mut tx = Transaction::new();
+            *balance.borrow_mut() -= 30;
+            tx.on_rollback(|| {
+                *balance.borrow_mut() += 30;
+                push(&events, "undo withdraw");
+            });
+            push(&events, "withdraw");
+            tx.on_rollback(|| push(&events, "undo log"));
+            push(&events, "log");
+        }
+        assert_eq!(*balance.borrow(), 100);
+        assert_eq!(
+            *events.borrow(),
+            ["withdraw", "log", "undo log", "undo withdraw"]
+        );
+
+        events.borrow_mut().clear();
+        let mut tx = Transaction::new();
+        *balance.borrow_mut() -= 30;
+        tx.on_rollback(|| *balance.borrow_mut() += 30);
+        tx.commit();
+        assert_eq!(*balance.borrow(), 70);
+    }
+
+    #[test]
+    fn transaction_rolls_back_on_panic() {
+        let events = log();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut tx = Transaction::new();
+            tx.on_rollback(|| push(&events, "rollback"));
+            push(&events, "step");
+            panic!("failed halfway");
+        }));
+        assert!(result.is_err());
+        assert_eq!(*events.borrow(), ["step", "rollback"]);
+    }
+}
@@ -0,0 +1,1147 @@
+// 在 tokio_async_io_tcp_socket.rs 的监听循环上长出来的一个最小 HTTP/1.1 服务器
+//
+// - 请求行、请求头解析，带长度/数量上限（超限分别回 414 / 431 / 413）
+// - keep-alive：HTTP/1.1 默认复用连接，`Connection: close` 或 HTTP/1.0 时处理完就关闭
+// - 超时：keep-alive 连接空闲太久直接关闭；请求读到一半停住（请求头或 body 太慢）回 408
+// - chunked 响应：body 是一个 Stream，每个元素写成一个 chunk
+// - Router：按 method + path 分发到 async handler，`/prefix/*` 做前缀匹配
+// - StaticFiles：把仓库里的 `.md` 笔记当静态文件发出去
+
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use futures::{FutureExt, Stream, StreamExt};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+use tokio::net::TcpListener;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Method {
+    Get,
+    Head,
+    Post,
+    Put,
+    Delete,
+    Patch,
+    Options,
+}
+
+impl Method {
+    fn parse(s: &str) -> Option<Method> {
+        Some(match s {
+            "GET" => Method::Get,
+            "HEAD" => Method::Head,
+            "POST" => Method::Post,
+            "PUT" => Method::Put,
+            "DELETE" => Method::Delete,
+            "PATCH" => Method::Patch,
+            "OPTIONS" => Method::Options,
+            _ => return None,
+        })
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Delete => "DELETE",
+            Method::Patch => "PATCH",
+            Method::Options => "OPTIONS",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+    Http10,
+    Http11,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub max_request_line: usize,
+    pub max_headers: usize,
+    pub max_header_bytes: usize,
+    pub max_body: usize,
+    // keep-alive 连接上等待下一个请求第一个字节的最长时间，超时直接关闭
+    pub idle_timeout: Duration,
+    // 请求开始之后读完请求头和 body 的最长时间，超时回 408
+    pub request_timeout: Duration,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_request_line: 8 * 1024,
+            max_headers: 64,
+            max_header_bytes: 16 * 1024,
+            max_body: 1024 * 1024,
+            idle_timeout: Duration::from_secs(5),
+            request_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: Method,
+    pub version: Version,
+    // 已经做过百分号解码
+    pub path: String,
+    pub query: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    // 前缀路由 `/notes/*` 匹配后剩下的部分，精确路由时为空
+    pub tail: String,
+}
+
+impl Request {
+    // 头部名字大小写不敏感
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    // Connection 是逗号分隔、大小写不敏感的选项列表，比如 `Upgrade, Keep-Alive`，也可能分几行给
+    fn wants_keep_alive(&self) -> bool {
+        let has = |option: &str| {
+            self.headers
+                .iter()
+                .filter(|(k, _)| k.eq_ignore_ascii_case("connection"))
+                .flat_map(|(_, v)| v.split(','))
+                .any(|token| token.trim().eq_ignore_ascii_case(option))
+        };
+        match self.version {
+            Version::Http11 => !has("close"),
+            Version::Http10 => has("keep-alive"),
+        }
+    }
+}
+
+pub enum Body {
+    Full(Vec<u8>),
+    Chunked(BoxStream<'static, Vec<u8>>),
+}
+
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Body,
+}
+
+impl Response {
+    pub fn new(status: u16) -> Self {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: Body::Full(Vec::new()),
+        }
+    }
+
+    pub fn text(status: u16, text: impl Into<String>) -> Self {
+        Response::new(status)
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .body(text.into().into_bytes())
+    }
+
+    pub fn chunked<S>(status: u16, chunks: S) -> Self
+    where
+        S: Stream<Item = Vec<u8>> + Send + 'static,
+    {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: Body::Chunked(chunks.boxed()),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn body(mut self, body: Vec<u8>) -> Self {
+        self.body = Body::Full(body);
+        self
+    }
+}
+
+impl fmt::Debug for Response {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Response")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        429 => "Too Many Requests",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        503 => "Service Unavailable",
+        505 => "HTTP Version Not Supported",
+        _ => "",
+    }
+}
+
+#[derive(Debug)]
+pub enum HttpError {
+    Io(io::Error),
+    // 解析失败：带上要回给客户端的状态码
+    Status(u16, &'static str),
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HttpError::Io(e) => write!(f, "io error: {e}"),
+            HttpError::Status(code, msg) => write!(f, "{code} {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HttpError::Io(e) => Some(e),
+            HttpError::Status(..) => None,
+        }
+    }
+}
+
+impl From<io::Error> for HttpError {
+    fn from(e: io::Error) -> Self {
+        HttpError::Io(e)
+    }
+}
+
+// 读一行（到 \n 为止），超过 limit 立即报错而不是继续往内存里读
+async fn read_line<R>(
+    reader: &mut R,
+    limit: usize,
+    too_long: HttpError,
+) -> Result<Option<String>, HttpError>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut line = Vec::new();
+    loop {
+        let buf = reader.fill_buf().await?;
+        if buf.is_empty() {
+            return if line.is_empty() {
+                Ok(None)
+            } else {
+                Err(HttpError::Status(400, "unexpected end of request"))
+            };
+        }
+        let (used, done) = match buf.iter().position(|&b| b == b'\n') {
+            Some(i) => (i + 1, true),
+            None => (buf.len(), false),
+        };
+        line.extend_from_slice(&buf[..used]);
+        reader.consume(used);
+        if line.len() > limit + 2 {
+            return Err(too_long);
+        }
+        if done {
+            break;
+        }
+    }
+    line.pop();
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    if line.len() > limit {
+        return Err(too_long);
+    }
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|_| HttpError::Status(400, "request is not valid UTF-8"))
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            // from_str_radix 会接受 `+f` 这种带符号的写法，先确认两个都是十六进制数字
+            let hex = s.get(i + 1..i + 3)?;
+            if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+// 读取并解析一个完整请求；连接在请求之间被正常关闭时返回 Ok(None)
+pub async fn read_request<R>(reader: &mut R, limits: &Limits) -> Result<Option<Request>, HttpError>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let uri_too_long = HttpError::Status(414, "request line too long");
+    let Some(line) = read_line(reader, limits.max_request_line, uri_too_long).await? else {
+        return Ok(None);
+    };
+
+    let mut parts = line.split(' ');
+    let (Some(method), Some(target), Some(version), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(HttpError::Status(400, "malformed request line"));
+    };
+    let version = match version {
+        "HTTP/1.1" => Version::Http11,
+        "HTTP/1.0" => Version::Http10,
+        v if v.starts_with("HTTP/") => {
+            return Err(HttpError::Status(505, "unsupported HTTP version"))
+        }
+        _ => return Err(HttpError::Status(400, "malformed request line")),
+    };
+    let method = Method::parse(method).ok_or(HttpError::Status(501, "unknown method"))?;
+    if !target.starts_with('/') {
+        return Err(HttpError::Status(
+            400,
+            "request target must be an absolute path",
+        ));
+    }
+    let (raw_path, query) = match target.split_once('?') {
+        Some((p, q)) => (p, Some(q.to_string())),
+        None => (target, None),
+    };
+    let path = percent_decode(raw_path).ok_or(HttpError::Status(400, "bad percent-encoding"))?;
+
+    let mut headers = Vec::new();
+    let mut header_bytes = 0;
+    loop {
+        let too_large = HttpError::Status(431, "header line too long");
+        let line = read_line(reader, limits.max_header_bytes, too_large)
+            .await?
+            .ok_or(HttpError::Status(400, "unexpected end of headers"))?;
+        if line.is_empty() {
+            break;
+        }
+        header_bytes += line.len();
+        if headers.len() == limits.max_headers || header_bytes > limits.max_header_bytes {
+            return Err(HttpError::Status(431, "too many header bytes"));
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or(HttpError::Status(400, "malformed header"))?;
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(HttpError::Status(400, "malformed header name"));
+        }
+        headers.push((name.to_string(), value.trim().to_string()));
+    }
+
+    let mut request = Request {
+        method,
+        version,
+        path,
+        query,
+        headers,
+        body: Vec::new(),
+        tail: String::new(),
+    };
+    if request.header("transfer-encoding").is_some() {
+        return Err(HttpError::Status(
+            501,
+            "chunked request bodies are not supported",
+        ));
+    }
+    // 重复的 Content-Length（多行或者 `4, 4`）必须一致，否则两边对 body 到哪结束的理解会不同
+    let mut lengths = request
+        .headers
+        .iter()
+        .filter(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .flat_map(|(_, v)| v.split(','))
+        .map(|v| v.trim().parse::<usize>());
+    if let Some(len) = lengths.next() {
+        let len = len.map_err(|_| HttpError::Status(400, "bad content-length"))?;
+        if lengths.any(|other| other != Ok(len)) {
+            return Err(HttpError::Status(400, "conflicting content-length"));
+        }
+        if len > limits.max_body {
+            return Err(HttpError::Status(413, "request body too large"));
+        }
+        let mut body = vec![0; len];
+        reader.read_exact(&mut body).await?;
+        request.body = body;
+    }
+    Ok(Some(request))
+}
+
+// 写出响应。HTTP/1.0 客户端不认识 chunked，就先把 stream 收集起来按 Content-Length 发
+pub async fn write_response<W>(
+    writer: &mut W,
+    response: Response,
+    version: Version,
+    keep_alive: bool,
+    head_only: bool,
+) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\n",
+        response.status,
+        reason_phrase(response.status)
+    );
+    let body = match (response.body, version) {
+        (Body::Chunked(chunks), Version::Http10) => Body::Full(chunks.concat().await),
+        (body, _) => body,
+    };
+    let chunked = matches!(body, Body::Chunked(_));
+    let mut has_length = false;
+    for (name, value) in &response.headers {
+        let is_length = name.eq_ignore_ascii_case("content-length");
+        // chunked 的响应不能再带 Content-Length，handler 设了也要去掉
+        if is_length && chunked {
+            continue;
+        }
+        has_length |= is_length;
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str(if keep_alive {
+        "Connection: keep-alive\r\n"
+    } else {
+        "Connection: close\r\n"
+    });
+
+    match body {
+        Body::Full(bytes) => {
+            // handler 自己设了 Content-Length（比如 HEAD 要报真实长度）就不再重复
+            if !has_length {
+                head.push_str(&format!("Content-Length: {}\r\n", bytes.len()));
+            }
+            head.push_str("\r\n");
+            writer.write_all(head.as_bytes()).await?;
+            if !head_only {
+                writer.write_all(&bytes).await?;
+            }
+        }
+        Body::Chunked(mut chunks) => {
+            head.push_str("Transfer-Encoding: chunked\r\n\r\n");
+            writer.write_all(head.as_bytes()).await?;
+            if !head_only {
+                while let Some(chunk) = chunks.next().await {
+                    // 长度为 0 的 chunk 表示结束，不能提前发出去
+                    if chunk.is_empty() {
+                        continue;
+                    }
+                    writer
+                        .write_all(format!("{:x}\r\n", chunk.len()).as_bytes())
+                        .await?;
+                    writer.write_all(&chunk).await?;
+                    writer.write_all(b"\r\n").await?;
+                }
+                writer.write_all(b"0\r\n\r\n").await?;
+            }
+        }
+    }
+    writer.flush().await
+}
+
+type Handler = Arc<dyn Fn(Request) -> BoxFuture<'static, Response> + Send + Sync>;
+
+enum Pattern {
+    Exact(String),
+    // `/notes/*` 存成 `/notes/`
+    Prefix(String),
+}
+
+impl Pattern {
+    fn parse(pattern: &str) -> Pattern {
+        match pattern.strip_suffix('*') {
+            Some(prefix) => Pattern::Prefix(prefix.to_string()),
+            None => Pattern::Exact(pattern.to_string()),
+        }
+    }
+
+    // 匹配成功时返回 tail
+    fn matches<'p>(&self, path: &'p str) -> Option<&'p str> {
+        match self {
+            Pattern::Exact(p) => (p == path).then_some(""),
+            Pattern::Prefix(p) => path.strip_prefix(p.as_str()),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Router {
+    routes: Vec<(Method, Pattern, Handler)>,
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Router::default()
+    }
+
+    pub fn route<F, Fut>(mut self, method: Method, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Response> + Send + 'static,
+    {
+        let handler: Handler = Arc::new(move |req| handler(req).boxed());
+        self.routes.push((method, Pattern::parse(pattern), handler));
+        self
+    }
+
+    pub fn get<F, Fut>(self, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Response> + Send + 'static,
+    {
+        self.route(Method::Get, pattern, handler)
+    }
+
+    pub fn post<F, Fut>(self, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Response> + Send + 'static,
+    {
+        self.route(Method::Post, pattern, handler)
+    }
+
+    fn find(&self, method: Method, path: &str) -> Option<(&Handler, String)> {
+        self.routes.iter().find_map(|(m, pattern, handler)| {
+            let tail = pattern.matches(path)?;
+            (*m == method).then(|| (handler, tail.to_string()))
+        })
+    }
+
+    // 路径存在但方法不对时回 405，并在 Allow 里列出支持的方法
+    pub async fn dispatch(&self, mut req: Request) -> Response {
+        let found = self.find(req.method, &req.path).or_else(|| {
+            // 没有单独注册 HEAD 时借用 GET 的 handler，写响应时丢掉 body
+            (req.method == Method::Head)
+                .then(|| self.find(Method::Get, &req.path))
+                .flatten()
+        });
+        if let Some((handler, tail)) = found {
+            req.tail = tail;
+            return handler(req).await;
+        }
+
+        let mut allowed: Vec<&str> = self
+            .routes
+            .iter()
+            .filter(|(_, pattern, _)| pattern.matches(&req.path).is_some())
+            .map(|(m, _, _)| m.as_str())
+            .collect();
+        if allowed.is_empty() {
+            return Response::text(404, "not found\n");
+        }
+        allowed.sort_unstable();
+        allowed.dedup();
+        Response::text(405, "method not allowed\n").header("Allow", &allowed.join(", "))
+    }
+}
+
+// 把一个目录下指定扩展名的文件当静态资源，路径取自 Request::tail
+#[derive(Debug, Clone)]
+pub struct StaticFiles {
+    root: PathBuf,
+    extensions: Vec<String>,
+}
+
+impl StaticFiles {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        StaticFiles {
+            root: root.into(),
+            extensions: Vec::new(),
+        }
+    }
+
+    // 只允许这些扩展名；不调用时所有文件都允许
+    pub fn extension(mut self, ext: &str) -> Self {
+        self.extensions.push(ext.to_string());
+        self
+    }
+
+    // 只接受普通路径分量，`..`、绝对路径之类一律拒绝，防止跳出 root
+    fn resolve(&self, tail: &str) -> Option<PathBuf> {
+        let relative = Path::new(tail);
+        if tail.is_empty()
+            || !relative
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return None;
+        }
+        let ext = relative.extension()?.to_str()?;
+        if !self.extensions.is_empty() && !self.extensions.iter().any(|e| e == ext) {
+            return None;
+        }
+        Some(self.root.join(relative))
+    }
+
+    fn content_type(path: &Path) -> &'static str {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("md") => "text/markdown; charset=utf-8",
+            Some("rs") | Some("toml") | Some("txt") => "text/plain; charset=utf-8",
+            Some("html") => "text/html; charset=utf-8",
+            _ => "application/octet-stream",
+        }
+    }
+
+    pub async fn serve(&self, req: Request) -> Response {
+        let Some(path) = self.resolve(&req.tail) else {
+            return Response::text(404, "not found\n");
+        };
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => Response::new(200)
+                .header("Content-Type", Self::content_type(&path))
+                .body(bytes),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Response::text(404, "not found\n"),
+            Err(_) => Response::text(500, "cannot read file\n"),
+        }
+    }
+
+    pub fn handler(self) -> impl Fn(Request) -> BoxFuture<'static, Response> + Send + Sync + Clone {
+        let files = Arc::new(self);
+        move |req| {
+            let files = Arc::clone(&files);
+            async move { files.serve(req).await }.boxed()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Server {
+    router: Arc<Router>,
+    limits: Limits,
+}
+
+impl Server {
+    pub fn new(router: Router) -> Self {
+        Server {
+            router: Arc::new(router),
+            limits: Limits::default(),
+        }
+    }
+
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub async fn serve(self, listener: TcpListener) -> io::Result<()> {
+        loop {
+            let (socket, _) = listener.accept().await?;
+            let server = self.clone();
+            tokio::spawn(async move {
+                let _ = server.serve_connection(socket).await;
+            });
+        }
+    }
+
+    pub async fn serve_connection<S>(&self, stream: S) -> io::Result<()>
+    where
+        S: AsyncRead + AsyncWrite,
+    {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut reader = BufReader::new(reader);
+        loop {
+            // 空闲超时只管等下一个请求的第一个字节：超时直接关闭，不回任何东西
+            match tokio::time::timeout(self.limits.idle_timeout, reader.fill_buf()).await {
+                Err(_) => return Ok(()),
+                Ok(Err(e)) => return Err(e),
+                Ok(Ok([])) => return Ok(()),
+                Ok(Ok(_)) => {}
+            }
+            let read = read_request(&mut reader, &self.limits);
+            let request = match tokio::time::timeout(self.limits.request_timeout, read).await {
+                // 请求读到一半就停了（请求头或者 body 太慢）：回 408 然后关闭
+                Err(_) => {
+                    let response = Response::text(408, "request timeout\n");
+                    return write_response(&mut writer, response, Version::Http11, false, false)
+                        .await;
+                }
+                Ok(Ok(None)) => return Ok(()),
+                Ok(Ok(Some(request))) => request,
+                Ok(Err(HttpError::Io(e))) => return Err(e),
+                // 解析失败后流的位置已经不可信了，回一个错误然后关闭
+                Ok(Err(HttpError::Status(status, msg))) => {
+                    let response = Response::text(status, format!("{msg}\n"));
+                    return write_response(&mut writer, response, Version::Http11, false, false)
+                        .await;
+                }
+            };
+
+            let keep_alive = request.wants_keep_alive();
+            let version = request.version;
+            let head_only = request.method == Method::Head;
+            let response = self.router.dispatch(request).await;
+            write_response(&mut writer, response, version, keep_alive, head_only).await?;
+            if !keep_alive {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tokio::net::TcpStream;
+
+    fn parse_response_head(head: &str) -> (u16, HashMap<String, String>) {
+        let mut lines = head.split("\r\n");
+        let status = lines
+            .next()
+            .and_then(|l| l.split(' ').nth(1))
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0);
+        let headers = lines
+            .filter_map(|l| l.split_once(": "))
+            .map(|(k, v)| (k.to_ascii_lowercase(), v.to_string()))
+            .collect();
+        (status, headers)
+    }
+
+    fn notes_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../..")
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .get("/hello", |_| async { Response::text(200, "hello\n") })
+            .post("/echo", |req: Request| async move {
+                Response::new(200).body(req.body)
+            })
+            .get("/query", |req: Request| async move {
+                Response::text(200, req.query.unwrap_or_default())
+            })
+            .get("/stream", |_| async {
+                let chunks = ["hello", ", ", "", "chunked"].map(|s| s.as_bytes().to_vec());
+                Response::chunked(200, futures::stream::iter(chunks))
+            })
+            .get(
+                "/notes/*",
+                StaticFiles::new(notes_root()).extension("md").handler(),
+            )
+    }
+
+    async fn start(limits: Limits) -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(Server::new(app()).limits(limits).serve(listener));
+        addr
+    }
+
+    // 读一个响应：按 Content-Length 或 chunked 结束符判断边界
+    async fn read_response(
+        reader: &mut BufReader<TcpStream>,
+    ) -> (u16, HashMap<String, String>, Vec<u8>) {
+        let mut head = String::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).await.unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            head.push_str(&line);
+        }
+        let (status, headers) = parse_response_head(head.trim_end());
+        let mut body = Vec::new();
+        if let Some(len) = headers.get("content-length") {
+            body.resize(len.parse().unwrap(), 0);
+            reader.read_exact(&mut body).await.unwrap();
+        } else if headers.get("transfer-encoding").map(String::as_str) == Some("chunked") {
+            // 测试里直接保留原始 chunk 编码，方便断言线上的字节
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                body.extend_from_slice(line.as_bytes());
+                if line == "0\r\n" {
+                    reader.read_line(&mut line).await.unwrap();
+                    body.extend_from_slice(b"\r\n");
+                    break;
+                }
+                let len = usize::from_str_radix(line.trim_end(), 16).unwrap();
+                let mut chunk = vec![0; len + 2];
+                reader.read_exact(&mut chunk).await.unwrap();
+                body.extend_from_slice(&chunk);
+            }
+        }
+        (status, headers, body)
+    }
+
+    async fn connect(addr: std::net::SocketAddr) -> BufReader<TcpStream> {
+        BufReader::new(TcpStream::connect(addr).await.unwrap())
+    }
+
+    async fn send(conn: &mut BufReader<TcpStream>, raw: &str) {
+        conn.get_mut().write_all(raw.as_bytes()).await.unwrap();
+    }
+
+    async fn is_closed(conn: &mut BufReader<TcpStream>) -> bool {
+        let mut rest = Vec::new();
+        conn.read_to_end(&mut rest).await.unwrap() == 0
+    }
+
+    #[tokio::test]
+    async fn simple_get() {
+        let mut conn = connect(start(Limits::default()).await).await;
+        send(&mut conn, "GET /hello HTTP/1.1\r\nHost: x\r\n\r\n").await;
+        let (status, headers, body) = read_response(&mut conn).await;
+        assert_eq!(status, 200);
+        assert_eq!(headers["connection"], "keep-alive");
+        assert_eq!(body, b"hello\n");
+    }
+
+    #[tokio::test]
+    async fn keep_alive_reuses_connection() {
+        let mut conn = connect(start(Limits::default()).await).await;
+        // 两个请求一次性发出去（pipelining），服务器按顺序回答
+        send(
+            &mut conn,
+            "GET /hello HTTP/1.1\r\n\r\nPOST /echo HTTP/1.1\r\nContent-Length: 4\r\n\r\nping",
+        )
+        .await;
+        assert_eq!(read_response(&mut conn).await.2, b"hello\n");
+        assert_eq!(read_response(&mut conn).await.2, b"ping");
+
+        send(
+            &mut conn,
+            "GET /query?a=1&b=2 HTTP/1.1\r\nConnection: close\r\n\r\n",
+        )
+        .await;
+        let (_, headers, body) = read_response(&mut conn).await;
+        assert_eq!(headers["connection"], "close");
+        assert_eq!(body, b"a=1&b=2");
+        assert!(is_closed(&mut conn).await);
+    }
+
+    #[tokio::test]
+    async fn http10_closes_by_default() {
+        let mut conn = connect(start(Limits::default()).await).await;
+        send(&mut conn, "GET /hello HTTP/1.0\r\n\r\n").await;
+        let (status, headers, _) = read_response(&mut conn).await;
+        assert_eq!(status, 200);
+        assert_eq!(headers["connection"], "close");
+        assert!(is_closed(&mut conn).await);
+    }
+
+    #[tokio::test]
+    async fn chunked_response_on_the_wire() {
+        let mut conn = connect(start(Limits::default()).await).await;
+        send(&mut conn, "GET /stream HTTP/1.1\r\n\r\n").await;
+        let (status, headers, body) = read_response(&mut conn).await;
+        assert_eq!(status, 200);
+        assert!(!headers.contains_key("content-length"));
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "5\r\nhello\r\n2\r\n, \r\n7\r\nchunked\r\n0\r\n\r\n"
+        );
+
+        // HTTP/1.0 不支持 chunked，退化成 Content-Length
+        send(&mut conn, "GET /stream HTTP/1.0\r\n\r\n").await;
+        let (_, headers, body) = read_response(&mut conn).await;
+        assert_eq!(headers["content-length"], "14");
+        assert_eq!(body, b"hello, chunked");
+    }
+
+    #[tokio::test]
+    async fn head_uses_get_route_without_body() {
+        let mut conn = connect(start(Limits::default()).await).await;
+        send(
+            &mut conn,
+            "HEAD /hello HTTP/1.1\r\n\r\nGET /hello HTTP/1.1\r\n\r\n",
+        )
+        .await;
+        let mut head = String::new();
+        loop {
+            let mut line = String::new();
+            conn.read_line(&mut line).await.unwrap();
+            head.push_str(&line);
+            if line == "\r\n" {
+                break;
+            }
+        }
+        assert!(head.contains("Content-Length: 6\r\n"));
+        // 紧接着就是下一个响应，说明 HEAD 没有发 body
+        assert_eq!(read_response(&mut conn).await.2, b"hello\n");
+    }
+
+    #[tokio::test]
+    async fn router_404_and_405() {
+        let mut conn = connect(start(Limits::default()).await).await;
+        send(&mut conn, "GET /missing HTTP/1.1\r\n\r\n").await;
+        assert_eq!(read_response(&mut conn).await.0, 404);
+
+        send(&mut conn, "DELETE /hello HTTP/1.1\r\n\r\n").await;
+        let (status, headers, _) = read_response(&mut conn).await;
+        assert_eq!(status, 405);
+        assert_eq!(headers["allow"], "GET");
+    }
+
+    #[tokio::test]
+    async fn allow_lists_each_method_once() {
+        let ok = |_| async { Response::text(200, "") };
+        let router = Router::new()
+            .get("/a", ok)
+            .post("/a", ok)
+            .get("/*", ok)
+            .post("/*", ok);
+        let request = Request {
+            method: Method::Delete,
+            version: Version::Http11,
+            path: "/a".into(),
+            query: None,
+            headers: Vec::new(),
+            body: Vec::new(),
+            tail: String::new(),
+        };
+        let response = router.dispatch(request).await;
+        assert_eq!(response.status, 405);
+        let allow = response.headers.iter().find(|(k, _)| k == "Allow").unwrap();
+        assert_eq!(allow.1, "GET, POST");
+    }
+
+    #[tokio::test]
+    async fn handler_content_length_is_not_repeated() {
+        let response = Response::new(200)
+            .header("content-length", "5")
+            .body(b"hello".to_vec());
+        let mut out = Vec::new();
+        write_response(&mut out, response, Version::Http11, true, false)
+            .await
+            .unwrap();
+        let raw = String::from_utf8(out).unwrap();
+        assert_eq!(
+            raw.to_ascii_lowercase().matches("content-length").count(),
+            1
+        );
+        assert!(raw.ends_with("\r\n\r\nhello"));
+    }
+
+    #[tokio::test]
+    async fn chunked_response_drops_handler_content_length() {
+        let chunks = futures::stream::iter([b"hello".to_vec()]);
+        let response = Response::chunked(200, chunks).header("Content-Length", "5");
+        let mut out = Vec::new();
+        write_response(&mut out, response, Version::Http11, true, false)
+            .await
+            .unwrap();
+        let raw = String::from_utf8(out).unwrap().to_ascii_lowercase();
+        assert!(!raw.contains("content-length"));
+        assert!(raw.contains("transfer-encoding: chunked\r\n"));
+        assert!(raw.ends_with("5\r\nhello\r\n0\r\n\r\n"));
+    }
+
+    #[test]
+    fn connection_header_is_a_token_list() {
+        let request = |version, values: &[&str]| Request {
+            method: Method::Get,
+            version,
+            path: "/".to_string(),
+            query: None,
+            headers: values
+                .iter()
+                .map(|v| ("Connection".to_string(), v.to_string()))
+                .collect(),
+            body: Vec::new(),
+            tail: String::new(),
+        };
+        assert!(!request(Version::Http11, &["Upgrade, Close"]).wants_keep_alive());
+        assert!(!request(Version::Http11, &["upgrade", "CLOSE"]).wants_keep_alive());
+        assert!(request(Version::Http11, &["Upgrade"]).wants_keep_alive());
+        assert!(request(Version::Http10, &["TE, Keep-Alive"]).wants_keep_alive());
+        assert!(!request(Version::Http10, &["TE"]).wants_keep_alive());
+    }
+
+    #[tokio::test]
+    async fn duplicate_content_length_must_agree() {
+        let addr = start(Limits::default()).await;
+        let mut conn = connect(addr).await;
+        send(
+            &mut conn,
+            "POST /echo HTTP/1.1\r\nContent-Length: 4\r\ncontent-length: 4, 4\r\n\r\nping",
+        )
+        .await;
+        let (status, _, body) = read_response(&mut conn).await;
+        assert_eq!((status, body.as_slice()), (200, &b"ping"[..]));
+
+        let mut conn = connect(addr).await;
+        send(
+            &mut conn,
+            "POST /echo HTTP/1.1\r\nContent-Length: 4\r\nContent-Length: 40\r\n\r\nping",
+        )
+        .await;
+        assert_eq!(read_response(&mut conn).await.0, 400);
+        assert!(is_closed(&mut conn).await);
+    }
+
+    #[tokio::test]
+    async fn parse_errors_close_connection() {
+        let addr = start(Limits::default()).await;
+        for (raw, expected) in [
+            ("garbage\r\n\r\n", 400),
+            ("GET /hello HTTP/2.0\r\n\r\n", 505),
+            ("BREW /pot HTTP/1.1\r\n\r\n", 501),
+            ("GET /hello HTTP/1.1\r\nno-colon\r\n\r\n", 400),
+            ("GET /%zz HTTP/1.1\r\n\r\n", 400),
+            ("GET /%+f HTTP/1.1\r\n\r\n", 400),
+            (
+                "POST /echo HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n",
+                501,
+            ),
+        ] {
+            let mut conn = connect(addr).await;
+            send(&mut conn, raw).await;
+            assert_eq!(read_response(&mut conn).await.0, expected, "{raw:?}");
+            assert!(is_closed(&mut conn).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn limits_are_enforced() {
+        let limits = Limits {
+            max_request_line: 64,
+            max_headers: 4,
+            max_header_bytes: 128,
+            max_body: 16,
+            ..Limits::default()
+        };
+        let addr = start(limits).await;
+
+        let long_uri = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(100));
+        let many_headers = format!("GET /hello HTTP/1.1\r\n{}\r\n", "X-A: 1\r\n".repeat(5));
+        let big_header = format!("GET /hello HTTP/1.1\r\nX-Big: {}\r\n\r\n", "b".repeat(200));
+        let big_body = "POST /echo HTTP/1.1\r\nContent-Length: 17\r\n\r\n".to_string();
+        for (raw, expected) in [
+            (long_uri, 414),
+            (many_headers, 431),
+            (big_header, 431),
+            (big_body, 413),
+        ] {
+            let mut conn = connect(addr).await;
+            send(&mut conn, &raw).await;
+            assert_eq!(read_response(&mut conn).await.0, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn idle_keep_alive_connection_times_out() {
+        let limits = Limits {
+            idle_timeout: Duration::from_millis(50),
+            ..Limits::default()
+        };
+        let mut conn = connect(start(limits).await).await;
+        send(&mut conn, "GET /hello HTTP/1.1\r\n\r\n").await;
+        assert_eq!(read_response(&mut conn).await.0, 200);
+        assert!(is_closed(&mut conn).await);
+    }
+
+    #[tokio::test]
+    async fn slow_upload_is_not_an_idle_connection() {
+        let limits = Limits {
+            idle_timeout: Duration::from_millis(50),
+            ..Limits::default()
+        };
+        let mut conn = connect(start(limits).await).await;
+        send(
+            &mut conn,
+            "POST /echo HTTP/1.1\r\nContent-Length: 4\r\n\r\npi",
+        )
+        .await;
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        send(&mut conn, "ng").await;
+        let (status, _, body) = read_response(&mut conn).await;
+        assert_eq!((status, body.as_slice()), (200, &b"ping"[..]));
+    }
+
+    #[tokio::test]
+    async fn stalled_request_gets_408() {
+        let limits = Limits {
+            request_timeout: Duration::from_millis(50),
+            ..Limits::default()
+        };
+        let addr = start(limits).await;
+        for raw in [
+            "GET /hello HTTP/1.1\r\nHost: x\r\n",
+            "POST /echo HTTP/1.1\r\nContent-Length: 4\r\n\r\npi",
+        ] {
+            let mut conn = connect(addr).await;
+            send(&mut conn, raw).await;
+            assert_eq!(read_response(&mut conn).await.0, 408, "{raw:?}");
+            assert!(is_closed(&mut conn).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_repo_markdown_notes() {
+        let mut conn = connect(start(Limits::default()).await).await;
+        send(&mut conn, "GET /notes/RAII.md HTTP/1.1\r\n\r\n").await;
+        let (status, headers, body) = read_response(&mut conn).await;
+        assert_eq!(status, 200);
+        assert_eq!(headers["content-type"], "text/markdown; charset=utf-8");
+        assert_eq!(body, std::fs::read(notes_root().join("RAII.md")).unwrap());
+
+        // 文件名里有空格，需要百分号解码
+        send(
+            &mut conn,
+            "GET /notes/Rust%201.80.1%20Learning%20Outline.md HTTP/1.1\r\n\r\n",
+        )
+        .await;
+        assert_eq!(read_response(&mut conn).await.0, 200);
+
+        for path in [
+            "/notes/../space/Cargo.toml",
+            "/notes/%2e%2e/README.md",
+            "/notes/closure.rs",
+            "/notes/nope.md",
+            "/notes/",
+        ] {
+            send(&mut conn, &format!("GET {path} HTTP/1.1\r\n\r\n")).await;
+            assert_eq!(read_response(&mut conn).await.0, 404, "{path}");
+        }
+    }
+
+    #[tokio::test]
+    async fn serve_connection_over_duplex() {
+        let (client, server_io) = tokio::io::duplex(1024);
+        let server = Server::new(app());
+        tokio::spawn(async move { server.serve_connection(server_io).await });
+
+        let (r, mut w) = tokio::io::split(client);
+        w.write_all(b"GET /hello HTTP/1.0\r\n\r\n").await.unwrap();
+        let mut raw = String::new();
+        BufReader::new(r).read_to_string(&mut raw).await.unwrap();
+        assert!(raw.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(raw.ends_with("\r\n\r\nhello\n"));
+    }
+}
@@ -0,0 +1,567 @@
+// 内存布局检查器：把 closure.rs / raw-pointer-type.rs 里手写的
+//
+//   println!("{:?}", &a as *const String);  // 栈上 String 结构体的地址
+//   println!("{:?}", a.as_ptr());           // 堆上实际字符串数据的地址
+//
+// 收拢成一个可复用的报告：
+// - 类型的 size / align，以及用 `type_layout!` 取得的各字段偏移
+// - 值本身和它指向的缓冲区各自位于栈、堆还是静态区
+// - Vec / String 的 len 和 capacity
+//
+// 判断内存区域在 Linux 上靠 /proc/self/maps，是启发式的：
+// - 和当前函数的局部变量在同一个映射里，或者是 `[stack]`，算栈
+// - 映射到可执行文件/动态库的算静态区；匿名的 .bss 只认本程序自己的，
+//   靠一个零初始化的静态变量 `BSS_ANCHOR` 找到它所在的映射
+// - `[heap]` 和其余匿名可写映射算堆（大块 malloc 和其他线程的 malloc arena 都走 mmap）
+// 所以其他线程的栈和动态库的 .bss 会被归为堆。
+// `MemoryMap::read` 读一次映射表，可以用来判断多个地址；`region_of` 每次都重新读。
+// 其他平台只能分辨出当前栈，其余报 Unknown。
+
+use std::fmt;
+use std::mem;
+use std::sync::atomic::AtomicU8;
+
+// 全零初始化，链接进本程序的 .bss；它所在的映射就是 .bss 的位置
+static BSS_ANCHOR: AtomicU8 = AtomicU8::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    Stack,
+    Heap,
+    Static,
+    Unknown,
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Region::Stack => "stack",
+            Region::Heap => "heap",
+            Region::Static => "static",
+            Region::Unknown => "unknown",
+        };
+        f.write_str(s)
+    }
+}
+
+// /proc/self/maps 的一份快照
+#[derive(Debug, Clone, Default)]
+pub struct MemoryMap {
+    // (start, end, 可写, 文件路径或 [heap] 这类名字)
+    mappings: Vec<(usize, usize, bool, String)>,
+}
+
+impl MemoryMap {
+    // 读不到映射表（非 Linux 或者没有 /proc）时是空表，只能分辨出当前栈
+    pub fn read() -> MemoryMap {
+        #[cfg(target_os = "linux")]
+        if let Ok(maps) = std::fs::read_to_string("/proc/self/maps") {
+            return MemoryMap::parse(&maps);
+        }
+        MemoryMap::default()
+    }
+
+    // 每行：start-end perms offset dev inode [path]
+    fn parse(maps: &str) -> MemoryMap {
+        let mappings = maps
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let (start, end) = parts.next()?.split_once('-')?;
+                let perms = parts.next()?;
+                let path = parts.nth(3).unwrap_or("");
+                Some((
+                    usize::from_str_radix(start, 16).ok()?,
+                    usize::from_str_radix(end, 16).ok()?,
+                    perms.starts_with("rw"),
+                    path.to_string(),
+                ))
+            })
+            .collect();
+        MemoryMap { mappings }
+    }
+
+    #[inline(never)]
+    pub fn region_of<T: ?Sized>(&self, ptr: *const T) -> Region {
+        let addr = ptr as *const u8 as usize;
+        let local = 0u8;
+        let sp = std::hint::black_box(&local) as *const u8 as usize;
+        if self.mappings.is_empty() {
+            // 没有映射表可查，只能粗略认为离当前栈顶 8MB 以内的是栈
+            return if addr.abs_diff(sp) < 8 * 1024 * 1024 {
+                Region::Stack
+            } else {
+                Region::Unknown
+            };
+        }
+
+        let find = |a: usize| {
+            self.mappings
+                .iter()
+                .position(|&(s, e, _, _)| s <= a && a < e)
+        };
+        let Some(i) = find(addr) else {
+            return Region::Unknown;
+        };
+        let is_file = |path: &str| !path.is_empty() && !path.starts_with('[');
+        let (_, _, writable, path) = &self.mappings[i];
+        if find(sp) == Some(i) || path == "[stack]" {
+            return Region::Stack;
+        }
+        if path == "[heap]" {
+            return Region::Heap;
+        }
+        if is_file(path) {
+            return Region::Static;
+        }
+        // .bss 比数据段最后一页长时，多出来的部分是紧跟在这个文件映射后面的匿名映射
+        if let Some(anchor) = find(&BSS_ANCHOR as *const AtomicU8 as usize) {
+            let bss = if is_file(&self.mappings[anchor].3) {
+                Some(anchor + 1).filter(|&next| {
+                    next < self.mappings.len() && self.mappings[anchor].1 == self.mappings[next].0
+                })
+            } else {
+                Some(anchor)
+            };
+            if bss == Some(i) {
+                return Region::Static;
+            }
+        }
+        if path.is_empty() && *writable {
+            return Region::Heap;
+        }
+        Region::Unknown
+    }
+}
+
+#[inline(never)]
+pub fn region_of<T: ?Sized>(ptr: *const T) -> Region {
+    MemoryMap::read().region_of(ptr)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldLayout {
+    pub name: &'static str,
+    pub offset: usize,
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeLayout {
+    pub type_name: &'static str,
+    pub size: usize,
+    pub align: usize,
+    // 按偏移排序，可以直接看出编译器对字段的重排
+    pub fields: Vec<FieldLayout>,
+}
+
+impl TypeLayout {
+    pub fn of<T: ?Sized>(value: &T) -> TypeLayout {
+        TypeLayout {
+            type_name: std::any::type_name::<T>(),
+            size: mem::size_of_val(value),
+            align: mem::align_of_val(value),
+            fields: Vec::new(),
+        }
+    }
+
+    // 没有被任何字段覆盖的字节数（字段之间和末尾的填充）。
+    // 按偏移扫描而不是用 size 减去字段大小之和：union 或者重叠的字段加起来可能比 size 还大
+    pub fn padding(&self) -> usize {
+        let mut fields: Vec<_> = self.fields.iter().map(|f| (f.offset, f.size)).collect();
+        fields.sort_unstable();
+        let mut covered_to = 0;
+        let mut padding = 0;
+        for (offset, size) in fields {
+            padding += offset.saturating_sub(covered_to);
+            covered_to = covered_to.max(offset + size);
+        }
+        padding + self.size.saturating_sub(covered_to)
+    }
+}
+
+#[doc(hidden)]
+pub fn field_size<T, F>(_: fn(&T) -> &F) -> usize {
+    mem::size_of::<F>()
+}
+
+// type_layout!(Point { x, y }) —— 列出字段的 size / align / 偏移
+#[macro_export]
+macro_rules! type_layout {
+    ($ty:ty { $($field:ident),* $(,)? }) => {{
+        let mut fields = vec![$(
+            $crate::layout::FieldLayout {
+                name: stringify!($field),
+                offset: ::std::mem::offset_of!($ty, $field),
+                size: $crate::layout::field_size(|v: &$ty| &v.$field),
+            },
+        )*];
+        fields.sort_by_key(|f| f.offset);
+        $crate::layout::TypeLayout {
+            type_name: ::std::any::type_name::<$ty>(),
+            size: ::std::mem::size_of::<$ty>(),
+            align: ::std::mem::align_of::<$ty>(),
+            fields,
+        }
+    }};
+}
+
+// 值所拥有（或指向）的那块连续缓冲区
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Buffer {
+    pub ptr: usize,
+    pub len: usize,
+    pub capacity: usize,
+    pub elem_size: usize,
+}
+
+// 想在报告里显示缓冲区的类型实现这个 trait；不关心缓冲区的类型直接用 `inspect_value`，
+// 或者用 `impl_inspect!(A, B<T>)` 批量写空 impl
+pub trait Inspect {
+    fn buffer(&self) -> Option<Buffer> {
+        None
+    }
+}
+
+impl Inspect for String {
+    fn buffer(&self) -> Option<Buffer> {
+        Some(Buffer {
+            ptr: self.as_ptr() as usize,
+            len: self.len(),
+            capacity: self.capacity(),
+            elem_size: 1,
+        })
+    }
+}
+
+impl<T> Inspect for Vec<T> {
+    fn buffer(&self) -> Option<Buffer> {
+        Some(Buffer {
+            ptr: self.as_ptr() as usize,
+            len: self.len(),
+            capacity: self.capacity(),
+            elem_size: mem::size_of::<T>(),
+        })
+    }
+}
+
+impl Inspect for str {
+    fn buffer(&self) -> Option<Buffer> {
+        Some(Buffer {
+            ptr: self.as_ptr() as usize,
+            len: self.len(),
+            capacity: self.len(),
+            elem_size: 1,
+        })
+    }
+}
+
+impl<T> Inspect for [T] {
+    fn buffer(&self) -> Option<Buffer> {
+        Some(Buffer {
+            ptr: self.as_ptr() as usize,
+            len: self.len(),
+            capacity: self.len(),
+            elem_size: mem::size_of::<T>(),
+        })
+    }
+}
+
+impl<T: Inspect + ?Sized> Inspect for &T {
+    fn buffer(&self) -> Option<Buffer> {
+        (**self).buffer()
+    }
+}
+
+impl<T: ?Sized> Inspect for Box<T> {
+    fn buffer(&self) -> Option<Buffer> {
+        Some(Buffer {
+            ptr: &**self as *const T as *const u8 as usize,
+            len: 1,
+            capacity: 1,
+            elem_size: mem::size_of_val(&**self),
+        })
+    }
+}
+
+// impl_inspect!(Point, Wrapper<T>) —— 没有缓冲区的类型，生成空的 Inspect 实现
+#[macro_export]
+macro_rules! impl_inspect {
+    ($($name:ident $(<$($param:ident),+>)?),* $(,)?) => {
+        $(impl $(<$($param),+>)? $crate::layout::Inspect for $name $(<$($param),+>)? {})*
+    };
+}
+
+impl_inspect!(bool, char, f32, f64);
+impl_inspect!(i8, i16, i32, i64, i128, isize);
+impl_inspect!(u8, u16, u32, u64, u128, usize);
+impl Inspect for () {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub name: String,
+    pub layout: TypeLayout,
+    pub addr: usize,
+    pub region: Region,
+    pub buffer: Option<(Buffer, Region)>,
+}
+
+impl Report {
+    // 用 type_layout! 的结果替换掉只有 size/align 的默认布局
+    pub fn with_layout(mut self, layout: TypeLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+}
+
+pub fn inspect<T: Inspect + ?Sized>(name: &str, value: &T) -> Report {
+    let map = MemoryMap::read();
+    let mut report = report(&map, name, value);
+    report.buffer = value
+        .buffer()
+        .map(|b| (b, map.region_of(b.ptr as *const u8)));
+    report
+}
+
+// 任意类型都能用，只是报告里没有缓冲区
+pub fn inspect_value<T: ?Sized>(name: &str, value: &T) -> Report {
+    report(&MemoryMap::read(), name, value)
+}
+
+fn report<T: ?Sized>(map: &MemoryMap, name: &str, value: &T) -> Report {
+    Report {
+        name: name.to_string(),
+        layout: TypeLayout::of(value),
+        addr: value as *const T as *const u8 as usize,
+        region: map.region_of(value as *const T),
+        buffer: None,
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let layout = &self.layout;
+        writeln!(
+            f,
+            "{}: {} (size {}, align {})",
+            self.name, layout.type_name, layout.size, layout.align
+        )?;
+        writeln!(f, "  value  @ {:#x} [{}]", self.addr, self.region)?;
+        if let Some((buf, region)) = &self.buffer {
+            writeln!(
+                f,
+                "  buffer @ {:#x} [{}] len {}, capacity {} ({} bytes)",
+                buf.ptr,
+                region,
+                buf.len,
+                buf.capacity,
+                buf.capacity * buf.elem_size
+            )?;
+        }
+        for field in &layout.fields {
+            writeln!(
+                f,
+                "  +{:<3} {} ({} bytes)",
+                field.offset, field.name, field.size
+            )?;
+        }
+        if !layout.fields.is_empty() {
+            writeln!(f, "  padding: {} bytes", layout.padding())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU64;
+
+    static GREETING: [u8; 5] = *b"hello";
+    // 可变的全零静态变量进 .bss，超过一页，至少有一部分落在匿名映射里
+    static ZEROED: [AtomicU64; 1024] = [const { AtomicU64::new(0) }; 1024];
+
+    #[test]
+    fn string_struct_on_stack_data_on_heap() {
+        let a = "ab".to_string();
+        let report = inspect("a", &a);
+        assert_eq!(report.region, Region::Stack);
+        assert_eq!(report.addr, &a as *const String as usize);
+        assert_eq!(report.layout.size, 3 * mem::size_of::<usize>());
+
+        let (buf, region) = report.buffer.unwrap();
+        assert_eq!(buf.ptr, a.as_ptr() as usize);
+        #[cfg(target_os = "linux")]
+        assert_eq!(region, Region::Heap);
+        assert_eq!((buf.len, buf.capacity), (2, 2));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn literals_and_statics_are_static() {
+        let s: &str = "a string literal";
+        let report = inspect("s", &s);
+        assert_eq!(report.region, Region::Stack);
+        assert_eq!(report.buffer.unwrap().1, Region::Static);
+
+        assert_eq!(region_of(&GREETING), Region::Static);
+        assert_eq!(region_of(&ZEROED), Region::Static);
+        assert_eq!(region_of(&ZEROED[1023]), Region::Static);
+        assert_eq!(
+            region_of(inspect_plain_marker as fn() as *const u8),
+            Region::Static
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    fn inspect_plain_marker() {}
+
+    #[test]
+    fn box_points_into_heap() {
+        let b = Box::new(5u64);
+        let report = inspect("b", &b);
+        assert_eq!(report.region, Region::Stack);
+        let (buf, region) = report.buffer.unwrap();
+        #[cfg(target_os = "linux")]
+        assert_eq!(region, Region::Heap);
+        assert_eq!(buf.elem_size, 8);
+    }
+
+    // 测试线程的小分配落在 mmap 出来的 arena 里，大块分配单独 mmap，都要认成堆
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn heap_buffers_on_any_thread() {
+        let s = "x".repeat(64);
+        assert_eq!(region_of(s.as_ptr()), Region::Heap);
+        let big = vec![0u8; 1 << 20];
+        assert_eq!(region_of(big.as_ptr()), Region::Heap);
+        std::thread::spawn(|| {
+            let b = Box::new([1u32; 16]);
+            assert_eq!(region_of(&*b), Region::Heap);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn vec_capacity_grows() {
+        let mut v: Vec<u32> = Vec::with_capacity(2);
+        v.extend([1, 2]);
+        let before = inspect("v", &v).buffer.unwrap().0;
+        assert_eq!((before.len, before.capacity), (2, 2));
+        v.push(3);
+        let after = inspect("v", &v).buffer.unwrap().0;
+        assert_eq!(after.len, 3);
+        assert!(after.capacity >= 3);
+        assert!(inspect("v", &v).to_string().contains("len 3"));
+    }
+
+    #[allow(dead_code)]
+    struct Mixed {
+        a: u8,
+        b: u32,
+        c: u16,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MixedC {
+        a: u8,
+        b: u32,
+        c: u16,
+    }
+
+    #[test]
+    fn field_offsets_and_padding() {
+        // repr(C) 按声明顺序排，中间和末尾都有填充
+        let c = type_layout!(MixedC { a, b, c });
+        let offsets: Vec<_> = c.fields.iter().map(|f| (f.name, f.offset)).collect();
+        assert_eq!(offsets, [("a", 0), ("b", 4), ("c", 8)]);
+        assert_eq!((c.size, c.align), (12, 4));
+        assert_eq!(c.padding(), 5);
+
+        // 默认 repr(Rust) 允许编译器重排，把填充压到最少
+        let rust = type_layout!(Mixed { a, b, c });
+        assert_eq!(rust.size, 8);
+        assert_eq!(rust.padding(), 1);
+
+        let m = Mixed { a: 1, b: 2, c: 3 };
+        let text = inspect_value("m", &m).with_layout(rust).to_string();
+        assert!(text.contains("padding: 1 bytes"));
+        assert!(text.contains(" b (4 bytes)"));
+    }
+
+    // union 的字段互相重叠，大小之和超过 size 也不能下溢
+    #[test]
+    fn overlapping_fields_padding() {
+        let field = |name, size| FieldLayout {
+            name,
+            offset: 0,
+            size,
+        };
+        // 相当于 union { int: u32, bytes: [u8; 4], wide: u64 }
+        let bits = TypeLayout {
+            type_name: "Bits",
+            size: 8,
+            align: 8,
+            fields: vec![field("int", 4), field("bytes", 4), field("wide", 8)],
+        };
+        assert_eq!(bits.padding(), 0);
+
+        let half = TypeLayout {
+            fields: vec![field("low", 4)],
+            ..bits.clone()
+        };
+        assert_eq!(half.padding(), 4);
+    }
+
+    struct Wrapper<T>(#[allow(dead_code)] T);
+
+    impl_inspect!(Wrapper<T>);
+
+    #[test]
+    fn impl_inspect_for_generic_types() {
+        let w = Wrapper(String::from("x"));
+        let report = inspect("w", &w);
+        assert!(report.buffer.is_none());
+        assert_eq!(report.region, Region::Stack);
+    }
+
+    // 主线程的 [heap]；挨着别的库的匿名映射也是堆，不再当成 .bss
+    #[test]
+    fn heap_from_synthetic_maps() {
+        let map = MemoryMap::parse(
+            "1000-2000 r--p 00000000 fe:00 1 /usr/lib/libfoo.so\n\
+             2000-3000 rw-p 00000000 00:00 0 \n\
+             5000-9000 rw-p 00000000 00:00 0          [heap]\n",
+        );
+        assert_eq!(map.region_of(0x1800 as *const u8), Region::Static);
+        assert_eq!(map.region_of(0x2800 as *const u8), Region::Heap);
+        assert_eq!(map.region_of(0x6000 as *const u8), Region::Heap);
+        assert_eq!(map.region_of(0x4000 as *const u8), Region::Unknown);
+    }
+
+    // closure.rs：move 闭包捕获 String，栈上的 String 结构体被复制到闭包里，堆上数据不动
+    #[test]
+    fn move_closure_copies_header_not_data() {
+        let a = "ab".to_string();
+        let outside = inspect("a", &a);
+        let in_closure = move || inspect("a", &a);
+        let inside = in_closure();
+
+        assert_ne!(outside.addr, inside.addr);
+        assert_eq!(outside.buffer.unwrap().0.ptr, inside.buffer.unwrap().0.ptr);
+        #[cfg(target_os = "linux")]
+        assert_eq!(inside.buffer.unwrap().1, Region::Heap);
+    }
+
+    #[test]
+    fn other_thread_stack_is_stack_from_inside() {
+        std::thread::spawn(|| {
+            let x = 1u32;
+            assert_eq!(inspect("x", &x).region, Region::Stack);
+        })
+        .join()
+        .unwrap();
+    }
+}
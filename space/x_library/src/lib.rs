@@ -1,3 +1,34 @@
+pub mod admission;
+pub mod arena;
+pub mod bitset;
+pub mod cache;
+pub mod channel;
+pub mod counter;
+pub mod error;
+pub mod generator;
+pub mod graph;
+pub mod guard;
+pub mod http;
+pub mod layout;
+pub mod openmap;
+pub mod ordmap;
+pub mod panics;
+pub mod pinned;
+pub mod pipeline;
+pub mod protocol;
+pub mod rc;
+pub mod refcell;
+pub mod smallvec;
+pub mod tagged_union;
+pub mod text;
+
+// 各集合 FromIterator / Extend / IntoIterator 的统一测试
+#[cfg(test)]
+mod collect;
+
+#[cfg(test)]
+mod test_util;
+
 pub fn hw() {
     println!("Hello from lib in x_library crate");
 }
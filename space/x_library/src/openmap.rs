@@ -0,0 +1,1016 @@
+// 开放寻址的哈希表，对照 HashMap.md / hashmap-key.rs 里用的 std HashMap
+//
+// 所有元素直接放在一个槽数组里，冲突时往后找空槽（线性探测），有两种策略：
+// - `Probe::Linear`：删除留下墓碑，查找要跳过墓碑继续找；墓碑多了就原地重建（compaction）
+// - `Probe::RobinHood`：插入时离理想位置更远的元素可以抢占更近的槽（"劫富济贫"），
+//   探测长度更均匀；查找遇到比自己还"富"的元素就可以提前结束，删除时把后面的元素往前挪，不需要墓碑
+//
+// 哈希函数通过 BuildHasher 插拔：默认是 std 的 RandomState（SipHash-1-3，带随机种子，抗碰撞攻击），
+// 另外提供 FxHash 风格和 FNV-1a 两个更快但没有防护的实现。
+//
+// `max_load` 控制装载因子：槽被占用（含墓碑）超过这个比例就扩容或者重建。
+
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+use std::mem;
+use std::ops::Index;
+
+// std 的默认哈希就是 SipHash
+pub type SipBuildHasher = RandomState;
+pub type FxBuildHasher = BuildHasherDefault<FxHasher>;
+pub type FnvBuildHasher = BuildHasherDefault<FnvHasher>;
+
+// rustc 内部用的 FxHash：每个字 rotate、异或、乘常数，很快，但低位质量差，分布靠高位
+#[derive(Default, Clone, Copy)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+const FX_SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+impl FxHasher {
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(FX_SEED);
+    }
+}
+
+impl Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            self.add(u64::from_le_bytes(chunk.try_into().unwrap()));
+        }
+        for &b in chunks.remainder() {
+            self.add(b as u64);
+        }
+    }
+
+    fn write_u8(&mut self, n: u8) {
+        self.add(n as u64);
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.add(n as u64);
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.add(n);
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.add(n as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+// FNV-1a：逐字节异或再乘质数
+#[derive(Clone, Copy)]
+pub struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        FnvHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Probe {
+    Linear,
+    RobinHood,
+}
+
+const MIN_CAPACITY: usize = 8;
+const DEFAULT_MAX_LOAD: f64 = 0.875;
+
+#[derive(Clone)]
+struct Bucket<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+#[derive(Clone)]
+enum Slot<K, V> {
+    Empty,
+    // 只有 Linear 会出现
+    Tombstone,
+    Full(Bucket<K, V>),
+}
+
+#[derive(Clone)]
+pub struct OpenMap<K, V, S = RandomState> {
+    slots: Vec<Slot<K, V>>,
+    // 槽数是 2 的幂，理想位置取哈希的高位：hash >> shift
+    shift: u32,
+    len: usize,
+    tombstones: usize,
+    probe: Probe,
+    max_load: f64,
+    hasher: S,
+}
+
+impl<K, V> OpenMap<K, V> {
+    pub fn new() -> Self {
+        OpenMap::with_hasher(RandomState::new())
+    }
+}
+
+impl<K, V> Default for OpenMap<K, V> {
+    fn default() -> Self {
+        OpenMap::new()
+    }
+}
+
+impl<K, V, S> OpenMap<K, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        OpenMap {
+            slots: Vec::new(),
+            shift: 64,
+            len: 0,
+            tombstones: 0,
+            probe: Probe::RobinHood,
+            max_load: DEFAULT_MAX_LOAD,
+            hasher,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // 槽的总数
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn tombstones(&self) -> usize {
+        self.tombstones
+    }
+
+    pub fn probe(&self) -> Probe {
+        self.probe
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|s| *s = Slot::Empty);
+        self.len = 0;
+        self.tombstones = 0;
+    }
+
+    fn mask(&self) -> usize {
+        self.slots.len() - 1
+    }
+
+    fn ideal(&self, hash: u64) -> usize {
+        // shift 为 64 时表里没有槽，不会被调用
+        (hash >> self.shift) as usize
+    }
+
+    fn distance(&self, hash: u64, index: usize) -> usize {
+        index.wrapping_sub(self.ideal(hash)) & self.mask()
+    }
+
+    // 装载上限：占用的槽（含墓碑）最多这么多；因为 max_load < 1，总会留下空槽，探测一定会停
+    fn limit(&self, capacity: usize) -> usize {
+        (capacity as f64 * self.max_load) as usize
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            slots: self.slots.iter(),
+            remaining: self.len,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&K, &mut V)> {
+        self.slots.iter_mut().filter_map(|s| match s {
+            Slot::Full(b) => Some((&b.key, &mut b.value)),
+            _ => None,
+        })
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    // 平均探测长度：每个元素离理想位置有几步，衡量两种策略的效果
+    pub fn average_probe_len(&self) -> f64 {
+        if self.len == 0 {
+            return 0.0;
+        }
+        let total: usize = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| match s {
+                Slot::Full(b) => Some(self.distance(b.hash, i)),
+                _ => None,
+            })
+            .sum();
+        total as f64 / self.len as f64
+    }
+
+    pub fn max_probe_len(&self) -> usize {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| match s {
+                Slot::Full(b) => Some(self.distance(b.hash, i)),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn bucket_mut(&mut self, index: usize) -> &mut Bucket<K, V> {
+        match &mut self.slots[index] {
+            Slot::Full(b) => b,
+            _ => unreachable!("slot {index} is not occupied"),
+        }
+    }
+
+    fn bucket(&self, index: usize) -> &Bucket<K, V> {
+        match &self.slots[index] {
+            Slot::Full(b) => b,
+            _ => unreachable!("slot {index} is not occupied"),
+        }
+    }
+
+    // 调用方保证 key 不在表里，而且已经 reserve 过；返回新元素所在的槽
+    fn insert_new(&mut self, hash: u64, key: K, value: V) -> usize {
+        let mask = self.mask();
+        let mut index = self.ideal(hash);
+        let mut incoming = Bucket { hash, key, value };
+        self.len += 1;
+        match self.probe {
+            Probe::Linear => loop {
+                match self.slots[index] {
+                    Slot::Full(_) => index = (index + 1) & mask,
+                    Slot::Tombstone => {
+                        self.tombstones -= 1;
+                        self.slots[index] = Slot::Full(incoming);
+                        return index;
+                    }
+                    Slot::Empty => {
+                        self.slots[index] = Slot::Full(incoming);
+                        return index;
+                    }
+                }
+            },
+            Probe::RobinHood => {
+                let mut dist = 0;
+                let mut placed = None;
+                loop {
+                    let existing = match &mut self.slots[index] {
+                        Slot::Full(b) => b,
+                        slot => {
+                            *slot = Slot::Full(incoming);
+                            return *placed.get_or_insert(index);
+                        }
+                    };
+                    let theirs = index.wrapping_sub((existing.hash >> self.shift) as usize) & mask;
+                    // 占着这个槽的元素比我们离家更近：抢过来，接着替它找位置
+                    if theirs < dist {
+                        mem::swap(existing, &mut incoming);
+                        placed.get_or_insert(index);
+                        dist = theirs;
+                    }
+                    index = (index + 1) & mask;
+                    dist += 1;
+                }
+            }
+        }
+    }
+
+    fn remove_at(&mut self, index: usize) -> (K, V) {
+        let mask = self.mask();
+        let next = (index + 1) & mask;
+        let empty_after = matches!(self.slots[next], Slot::Empty);
+        let replacement = match self.probe {
+            // 后面紧跟空槽时不需要墓碑：没有哪次查找会越过这里继续往后找
+            Probe::Linear if !empty_after => {
+                self.tombstones += 1;
+                Slot::Tombstone
+            }
+            _ => Slot::Empty,
+        };
+        let Slot::Full(removed) = mem::replace(&mut self.slots[index], replacement) else {
+            unreachable!("slot {index} is not occupied");
+        };
+        self.len -= 1;
+
+        match self.probe {
+            Probe::Linear if empty_after => {
+                // 前面连着的墓碑现在也没用了
+                let mut i = index.wrapping_sub(1) & mask;
+                while matches!(self.slots[i], Slot::Tombstone) {
+                    self.slots[i] = Slot::Empty;
+                    self.tombstones -= 1;
+                    i = i.wrapping_sub(1) & mask;
+                }
+            }
+            Probe::Linear => {}
+            Probe::RobinHood => {
+                // 后移删除：后面不在理想位置上的元素依次往前挪一格
+                let (mut hole, mut i) = (index, next);
+                while let Slot::Full(b) = &self.slots[i] {
+                    if self.distance(b.hash, i) == 0 {
+                        break;
+                    }
+                    self.slots.swap(hole, i);
+                    hole = i;
+                    i = (i + 1) & mask;
+                }
+            }
+        }
+        (removed.key, removed.value)
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> OpenMap<K, V, S> {
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let mut map = OpenMap::with_hasher(hasher);
+        map.reserve(capacity);
+        map
+    }
+
+    // 切换探测策略；表里已有元素时按新策略重建
+    pub fn probing(mut self, probe: Probe) -> Self {
+        if self.probe != probe {
+            self.probe = probe;
+            self.rehash(self.capacity());
+        }
+        self
+    }
+
+    // 装载因子上限，必须在 (0, 1) 之间；调小之后可能马上扩容
+    pub fn max_load(mut self, load: f64) -> Self {
+        assert!(
+            load > 0.0 && load < 1.0,
+            "max load must be in (0, 1), got {load}"
+        );
+        self.max_load = load;
+        self.reserve(0);
+        self
+    }
+
+    fn hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        self.hasher.hash_one(key)
+    }
+
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let mask = self.mask();
+        let mut index = self.ideal(hash);
+        for dist in 0..self.slots.len() {
+            match &self.slots[index] {
+                Slot::Empty => return None,
+                Slot::Tombstone => {}
+                Slot::Full(b) => {
+                    if b.hash == hash && b.key.borrow() == key {
+                        return Some(index);
+                    }
+                    // Robin Hood 的不变式：要找的 key 不可能排在比它离家更近的元素后面
+                    if self.probe == Probe::RobinHood && self.distance(b.hash, index) < dist {
+                        return None;
+                    }
+                }
+            }
+            index = (index + 1) & mask;
+        }
+        None
+    }
+
+    // 保证再插入 additional 个元素不需要重新分配
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self.len.checked_add(additional).expect("capacity overflow");
+        if needed.saturating_add(self.tombstones) <= self.limit(self.capacity()) {
+            return;
+        }
+        if needed <= self.limit(self.capacity()) {
+            // 主要是墓碑占了地方：原地重建就够了
+            self.rehash(self.capacity());
+            return;
+        }
+        let mut capacity = self.capacity().max(MIN_CAPACITY);
+        while self.limit(capacity) < needed.max(1) {
+            capacity = capacity.checked_mul(2).expect("capacity overflow");
+        }
+        self.rehash(capacity);
+    }
+
+    // 清掉所有墓碑
+    pub fn compact(&mut self) {
+        if self.tombstones > 0 {
+            self.rehash(self.capacity());
+        }
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        let mut capacity = MIN_CAPACITY;
+        while self.limit(capacity) < self.len {
+            capacity *= 2;
+        }
+        if capacity < self.capacity() || self.tombstones > 0 {
+            self.rehash(capacity);
+        }
+    }
+
+    fn rehash(&mut self, capacity: usize) {
+        let capacity = capacity.max(MIN_CAPACITY);
+        debug_assert!(capacity.is_power_of_two());
+        let old = mem::replace(
+            &mut self.slots,
+            (0..capacity).map(|_| Slot::Empty).collect(),
+        );
+        self.shift = 64 - capacity.trailing_zeros();
+        self.len = 0;
+        self.tombstones = 0;
+        for slot in old {
+            if let Slot::Full(b) = slot {
+                self.insert_new(b.hash, b.key, b.value);
+            }
+        }
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut e) => Some(e.insert(value)),
+            Entry::Vacant(e) => {
+                e.insert(value);
+                None
+            }
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash(key), key)?;
+        Some(&self.bucket(index).value)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let b = self.bucket(self.find(self.hash(key), key)?);
+        Some((&b.key, &b.value))
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash(key), key)?;
+        Some(&mut self.bucket_mut(index).value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(self.hash(key), key).is_some()
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash(key), key)?;
+        Some(self.remove_at(index))
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&K, &mut V) -> bool) {
+        // 从某个空槽之后开始转一圈：Robin Hood 的后移删除只会把还没看过的元素挪过来，
+        // 挪动到空槽为止，不会把看过的元素再绕回来
+        let Some(empty) = self.slots.iter().position(|s| matches!(s, Slot::Empty)) else {
+            return;
+        };
+        let mask = self.mask();
+        let mut step = 1;
+        while step < self.slots.len() {
+            let index = (empty + step) & mask;
+            if let Slot::Full(b) = &mut self.slots[index] {
+                if !keep(&b.key, &mut b.value) {
+                    self.remove_at(index);
+                    // 后面的元素可能挪到了这里，要再看一遍
+                    if self.probe == Probe::RobinHood {
+                        continue;
+                    }
+                }
+            }
+            step += 1;
+        }
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        let hash = self.hash(&key);
+        match self.find(hash, &key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            None => {
+                // 只在 key 不存在时留好位置，VacantEntry::insert 就不会触发扩容；
+                // 查到已有的 key 不能让表变大（扩容会挪动槽位，index 也就失效了）
+                self.reserve(1);
+                Entry::Vacant(VacantEntry {
+                    map: self,
+                    hash,
+                    key,
+                })
+            }
+        }
+    }
+}
+
+pub enum Entry<'a, K, V, S> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+pub struct OccupiedEntry<'a, K, V, S> {
+    map: &'a mut OpenMap<K, V, S>,
+    index: usize,
+}
+
+pub struct VacantEntry<'a, K, V, S> {
+    map: &'a mut OpenMap<K, V, S>,
+    hash: u64,
+    key: K,
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify(mut self, f: impl FnOnce(&mut V)) -> Self {
+        if let Entry::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.map.bucket(self.index).key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.map.bucket(self.index).value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.bucket_mut(self.index).value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        let OccupiedEntry { map, index } = self;
+        &mut map.bucket_mut(index).value
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove_entry(self) -> (K, V) {
+        self.map.remove_at(self.index)
+    }
+
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+}
+
+impl<'a, K, V, S> VacantEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        let VacantEntry { map, hash, key } = self;
+        let index = map.insert_new(hash, key, value);
+        &mut map.bucket_mut(index).value
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for OpenMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for OpenMap<K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K, Q, V, S> Index<&Q> for OpenMap<K, V, S>
+where
+    K: Hash + Eq + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for OpenMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'a, K: Hash + Eq + Copy, V: Copy, S: BuildHasher> Extend<(&'a K, &'a V)> for OpenMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(k, v)| (*k, *v)));
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for OpenMap<K, V, S> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = OpenMap::with_hasher(S::default());
+        map.extend(iter);
+        map
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    slots: std::slice::Iter<'a, Slot<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for slot in self.slots.by_ref() {
+            if let Slot::Full(b) = slot {
+                self.remaining -= 1;
+                return Some((&b.key, &b.value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<'a, K, V, S> IntoIterator for &'a OpenMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+pub struct IntoIter<K, V> {
+    slots: std::vec::IntoIter<Slot<K, V>>,
+    remaining: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        for slot in self.slots.by_ref() {
+            if let Slot::Full(b) = slot {
+                self.remaining -= 1;
+                return Some((b.key, b.value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+impl<K, V, S> IntoIterator for OpenMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter {
+            remaining: self.len,
+            slots: self.slots.into_iter(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::lcg;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Account<'a> {
+        username: &'a str,
+        password: &'a str,
+    }
+
+    // 每个测试跑遍 探测策略 × 哈希函数
+    macro_rules! for_each_config {
+        ($body:ident) => {
+            for probe in [Probe::Linear, Probe::RobinHood] {
+                $body(OpenMap::with_hasher(SipBuildHasher::new()).probing(probe));
+                $body(OpenMap::with_hasher(FxBuildHasher::default()).probing(probe));
+                $body(OpenMap::with_hasher(FnvBuildHasher::default()).probing(probe));
+            }
+        };
+    }
+
+    #[test]
+    fn account_keys() {
+        fn check<S: BuildHasher>(mut accounts: OpenMap<Account<'static>, &'static str, S>) {
+            let account = Account {
+                username: "j.everyman",
+                password: "password123",
+            };
+            assert_eq!(accounts.insert(account.clone(), "John Everyman"), None);
+            let wrong = Account {
+                password: "psasword123",
+                ..account
+            };
+            assert_eq!(accounts.get(&wrong), None);
+            assert_eq!(accounts[&account], "John Everyman");
+            assert_eq!(
+                accounts.insert(account.clone(), "J. Everyman"),
+                Some("John Everyman")
+            );
+            assert_eq!(accounts.len(), 1);
+            assert_eq!(accounts.remove(&account), Some("J. Everyman"));
+            assert!(accounts.is_empty());
+        }
+        for_each_config!(check);
+    }
+
+    #[test]
+    fn random_operations_match_std() {
+        fn check<S: BuildHasher>(mut ours: OpenMap<u64, u64, S>) {
+            let mut std = HashMap::new();
+            let mut seed = 5;
+            for _ in 0..20_000 {
+                let key = lcg(&mut seed) % 500;
+                match lcg(&mut seed) % 4 {
+                    0 | 1 => assert_eq!(ours.insert(key, key * 3), std.insert(key, key * 3)),
+                    2 => assert_eq!(ours.remove(&key), std.remove(&key)),
+                    _ => assert_eq!(ours.get(&key), std.get(&key)),
+                }
+                assert_eq!(ours.len(), std.len());
+            }
+            let mut pairs: Vec<_> = ours.iter().map(|(k, v)| (*k, *v)).collect();
+            let mut expected: Vec<_> = std.into_iter().collect();
+            pairs.sort();
+            expected.sort();
+            assert_eq!(pairs, expected);
+        }
+        for_each_config!(check);
+    }
+
+    #[test]
+    fn entry_api_like_hashmap_md() {
+        fn check<S: BuildHasher>(mut map: OpenMap<&'static str, i32, S>) {
+            map.insert("苹果", 3);
+            match map.entry("苹果") {
+                Entry::Occupied(mut entry) => {
+                    assert_eq!(*entry.get(), 3);
+                    entry.insert(5);
+                }
+                Entry::Vacant(_) => unreachable!(),
+            }
+            match map.entry("香蕉") {
+                Entry::Occupied(_) => unreachable!(),
+                Entry::Vacant(entry) => {
+                    assert_eq!(*entry.key(), "香蕉");
+                    *entry.insert(2) += 1;
+                }
+            }
+            map.entry("苹果").or_insert(10);
+            *map.entry("poneyland").or_insert(3) *= 2;
+            map.entry("poneyland").and_modify(|v| *v += 1).or_default();
+            assert_eq!(map["苹果"], 5);
+            assert_eq!(map["香蕉"], 3);
+            assert_eq!(map["poneyland"], 7);
+            if let Entry::Occupied(e) = map.entry("香蕉") {
+                assert_eq!(e.remove(), 3);
+            }
+            assert!(!map.contains_key("香蕉"));
+        }
+        for_each_config!(check);
+    }
+
+    #[test]
+    fn char_counts_with_string_keys() {
+        fn check<S: BuildHasher>(mut map: OpenMap<String, usize, S>) {
+            for word in "hello world hello rust hello".split(' ') {
+                *map.entry(word.to_string()).or_default() += 1;
+            }
+            assert_eq!(map.get("hello"), Some(&3));
+            map.retain(|_, n| *n > 1);
+            assert_eq!(format!("{:?}", map), r#"{"hello": 3}"#);
+        }
+        for_each_config!(check);
+    }
+
+    #[test]
+    fn tombstones_are_compacted() {
+        let mut map: OpenMap<u32, u32> = OpenMap::new().probing(Probe::Linear);
+        for i in 0..100 {
+            map.insert(i, i);
+        }
+        let capacity = map.capacity();
+        // 插一个删一个，墓碑不断累积，但容量不应该一直涨
+        for i in 100..10_000 {
+            map.insert(i, i);
+            map.remove(&(i - 100));
+            assert!(map.len() + map.tombstones() <= map.limit(map.capacity()));
+        }
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.capacity(), capacity);
+        assert!((9900..10_000).all(|i| map[&i] == i));
+
+        map.compact();
+        assert_eq!(map.tombstones(), 0);
+        assert!((9900..10_000).all(|i| map[&i] == i));
+    }
+
+    #[test]
+    fn robin_hood_never_leaves_tombstones() {
+        let mut map: OpenMap<u32, u32, FxBuildHasher> = OpenMap::with_hasher(Default::default());
+        map.extend((0..1000).map(|i| (i, i)));
+        map.retain(|k, _| k % 3 == 0);
+        assert_eq!(map.tombstones(), 0);
+        assert_eq!(map.len(), 334);
+        assert!((0..1000).all(|i| map.contains_key(&i) == (i % 3 == 0)));
+        map.shrink_to_fit();
+        assert!(map.capacity() < 1024);
+        assert!((0..1000).step_by(3).all(|i| map[&i] == i));
+    }
+
+    #[test]
+    fn load_factor_controls_capacity() {
+        let mut loose: OpenMap<u32, ()> = OpenMap::new().max_load(0.5);
+        let mut tight: OpenMap<u32, ()> = OpenMap::new().max_load(0.95);
+        for i in 0..900 {
+            loose.insert(i, ());
+            tight.insert(i, ());
+        }
+        assert_eq!(loose.capacity(), 2048);
+        assert_eq!(tight.capacity(), 1024);
+        // 装得越满，探测越长
+        assert!(loose.average_probe_len() < tight.average_probe_len());
+    }
+
+    #[test]
+    fn switching_probe_rehashes() {
+        let mut map: OpenMap<u32, u32> = (0..200).map(|i| (i, i)).collect();
+        map = map.probing(Probe::Linear);
+        for i in 0..100 {
+            map.remove(&i);
+        }
+        map = map.probing(Probe::RobinHood);
+        assert_eq!(map.tombstones(), 0);
+        assert!((100..200).all(|i| map[&i] == i));
+        assert!((0..100).all(|i| !map.contains_key(&i)));
+    }
+
+    #[test]
+    fn entry_on_existing_key_does_not_grow() {
+        let mut map: OpenMap<u32, u32> = OpenMap::new();
+        let mut i = 0;
+        map.insert(i, i);
+        let capacity = map.capacity();
+        // 填到下一次插入就要扩容为止
+        while map.len() < capacity * 7 / 8 {
+            i += 1;
+            map.insert(i, i);
+        }
+        assert_eq!(map.capacity(), capacity);
+        *map.entry(0).or_insert(0) += 1;
+        assert_eq!(map.capacity(), capacity);
+        map.entry(i + 1).or_insert(0);
+        assert!(map.capacity() > capacity);
+        assert_eq!(map[&0], 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn reserve_overflow_panics() {
+        let mut map: OpenMap<u32, u32> = (0..10).map(|i| (i, i)).collect();
+        map.reserve(usize::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn reserve_huge_capacity_panics() {
+        let mut map: OpenMap<u32, u32> = OpenMap::new();
+        map.reserve(usize::MAX / 2);
+    }
+
+    #[test]
+    fn hasher_reference_values() {
+        let mut fnv = FnvHasher::default();
+        fnv.write(b"a");
+        assert_eq!(fnv.finish(), 0xaf63dc4c8601ec8c);
+        let mut fnv = FnvHasher::default();
+        fnv.write(b"foobar");
+        assert_eq!(fnv.finish(), 0x85944171f73967e8);
+
+        let mut fx = FxHasher::default();
+        fx.write_u64(1);
+        assert_eq!(fx.finish(), FX_SEED);
+        // 按字节写和按整数写的结果不一样，但同样的输入必须稳定
+        let hash = |s: &str| FxBuildHasher::default().hash_one(s);
+        assert_eq!(hash("account"), hash("account"));
+        assert_ne!(hash("account"), hash("accounts"));
+    }
+}
@@ -0,0 +1,799 @@
+// 支持顺序统计的有序映射，配合 BTreeMap.rs 里的 insert / remove / range 示例
+//
+// 用 treap 实现：按 key 是二叉搜索树，按随机优先级是堆，期望高度 O(log n)。
+// 每个节点记录子树大小，于是除了 BTreeMap 的常用操作之外还能做：
+// - `rank(k)`：小于 k 的 key 有几个；`select(i)`：第 i 小的元素
+// - `count_range(range)`：范围里有几个元素，不用真的遍历
+// - `floor(k)` / `ceiling(k)`：不大于 / 不小于 k 的最近的 key
+// - `split_off(k)` / `append(other)`：和 BTreeMap 同名同语义，key 区间不重叠时只要 O(log n)
+//
+// 所有修改都归结为两个基本操作：按位置把树切成两半（split_at），以及把两棵 key 不重叠的树拼起来（merge）。
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::ops::{Bound, Index, RangeBounds};
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+#[derive(Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    priority: u64,
+    // 以这个节点为根的子树里有几个元素
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+impl<K, V> Node<K, V> {
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |n| n.size)
+}
+
+// 前 n 个元素放左边，其余放右边
+fn split_at<K, V>(link: Link<K, V>, n: usize) -> (Link<K, V>, Link<K, V>) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+    let left = size(&node.left);
+    if n <= left {
+        let (a, b) = split_at(node.left.take(), n);
+        node.left = b;
+        node.update();
+        (a, Some(node))
+    } else {
+        let (a, b) = split_at(node.right.take(), n - left - 1);
+        node.right = a;
+        node.update();
+        (Some(node), b)
+    }
+}
+
+// 要求 a 里所有 key 都小于 b 里的；优先级大的留在上面
+fn merge<K, V>(a: Link<K, V>, b: Link<K, V>) -> Link<K, V> {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(mut a), Some(mut b)) => {
+            if a.priority >= b.priority {
+                a.right = merge(a.right.take(), Some(b));
+                a.update();
+                Some(a)
+            } else {
+                b.left = merge(Some(a), b.left.take());
+                b.update();
+                Some(b)
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct OrdMap<K, V> {
+    root: Link<K, V>,
+    rng: u64,
+}
+
+impl<K, V> OrdMap<K, V> {
+    pub fn new() -> Self {
+        OrdMap {
+            root: None,
+            // 种子随机，避免固定的插入顺序恰好构造出很深的树
+            rng: RandomState::new().hash_one(0u8) | 1,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn clear(&mut self) {
+        self.root = None;
+    }
+
+    // xorshift64*
+    fn next_priority(&mut self) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    // 第 i 小的元素（从 0 开始）
+    pub fn select(&self, mut index: usize) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        loop {
+            let left = size(&node.left);
+            match index.cmp(&left) {
+                Ordering::Less => node = node.left.as_deref()?,
+                Ordering::Equal => return Some((&node.key, &node.value)),
+                Ordering::Greater => {
+                    index -= left + 1;
+                    node = node.right.as_deref()?;
+                }
+            }
+        }
+    }
+
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.select(0)
+    }
+
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.select(self.len().checked_sub(1)?)
+    }
+
+    fn take_at(&mut self, index: usize) -> Option<(K, V)> {
+        if index >= self.len() {
+            return None;
+        }
+        let (a, rest) = split_at(self.root.take(), index);
+        let (mid, b) = split_at(rest, 1);
+        self.root = merge(a, b);
+        mid.map(|n| (n.key, n.value))
+    }
+
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        self.take_at(0)
+    }
+
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        self.take_at(self.len().checked_sub(1)?)
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.iter_from(0, self.len())
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    // 从第 index 个元素开始，一共取 remaining 个
+    fn iter_from(&self, mut index: usize, remaining: usize) -> Iter<'_, K, V> {
+        let mut stack = Vec::new();
+        let mut link = self.root.as_deref();
+        while let Some(node) = link {
+            let left = size(&node.left);
+            match index.cmp(&left) {
+                Ordering::Less => {
+                    stack.push(node);
+                    link = node.left.as_deref();
+                }
+                Ordering::Equal => {
+                    stack.push(node);
+                    break;
+                }
+                Ordering::Greater => {
+                    index -= left + 1;
+                    link = node.right.as_deref();
+                }
+            }
+        }
+        Iter { stack, remaining }
+    }
+}
+
+impl<K: Ord, V> OrdMap<K, V> {
+    // 比 key 小（inclusive 时是不大于）的元素个数
+    fn count_below<Q>(&self, key: &Q, inclusive: bool) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut count = 0;
+        let mut link = self.root.as_deref();
+        while let Some(node) = link {
+            let goes_left = match node.key.borrow().cmp(key) {
+                Ordering::Less => true,
+                Ordering::Equal => inclusive,
+                Ordering::Greater => false,
+            };
+            if goes_left {
+                count += size(&node.left) + 1;
+                link = node.right.as_deref();
+            } else {
+                link = node.left.as_deref();
+            }
+        }
+        count
+    }
+
+    // key 在有序序列里的位置：严格小于它的 key 的个数，key 不必存在
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.count_below(key, false)
+    }
+
+    // 范围对应的下标区间 [lo, hi)
+    fn bounds<Q, R>(&self, range: &R) -> (usize, usize)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let lo = match range.start_bound() {
+            Bound::Included(k) => self.count_below(k, false),
+            Bound::Excluded(k) => self.count_below(k, true),
+            Bound::Unbounded => 0,
+        };
+        let hi = match range.end_bound() {
+            Bound::Included(k) => self.count_below(k, true),
+            Bound::Excluded(k) => self.count_below(k, false),
+            Bound::Unbounded => self.len(),
+        };
+        (lo, hi)
+    }
+
+    pub fn count_range<Q, R>(&self, range: R) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (lo, hi) = self.bounds(&range);
+        hi.saturating_sub(lo)
+    }
+
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        // 和 BTreeMap 一样直接比较两个边界，和 map 里有没有这些 key 无关：
+        // 起点大于终点，或者两端相等又都不包含，算调用方的错
+        match (range.start_bound(), range.end_bound()) {
+            (Bound::Excluded(s), Bound::Excluded(e)) if s == e => {
+                panic!("range start and end are equal and excluded")
+            }
+            (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e))
+                if s > e =>
+            {
+                panic!("range start is greater than range end")
+            }
+            _ => {}
+        }
+        let (lo, hi) = self.bounds(&range);
+        self.iter_from(lo, hi - lo)
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<&Node<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = self.root.as_deref();
+        while let Some(node) = link {
+            link = match key.cmp(node.key.borrow()) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Equal => return Some(node),
+                Ordering::Greater => node.right.as_deref(),
+            };
+        }
+        None
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).map(|n| &n.value)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).map(|n| (&n.key, &n.value))
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = self.root.as_deref_mut();
+        while let Some(node) = link {
+            link = match key.cmp(node.key.borrow()) {
+                Ordering::Less => node.left.as_deref_mut(),
+                Ordering::Equal => return Some(&mut node.value),
+                Ordering::Greater => node.right.as_deref_mut(),
+            };
+        }
+        None
+    }
+
+    // 不大于 key 的最大元素
+    pub fn floor<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.select(self.count_below(key, true).checked_sub(1)?)
+    }
+
+    // 不小于 key 的最小元素
+    pub fn ceiling<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.select(self.count_below(key, false))
+    }
+
+    // key 已存在时替换 value 并返回旧值，key 本身保持不变（与 BTreeMap 一致）
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+        let index = self.rank(&key);
+        let node = Box::new(Node {
+            key,
+            value,
+            priority: self.next_priority(),
+            size: 1,
+            left: None,
+            right: None,
+        });
+        let (a, b) = split_at(self.root.take(), index);
+        self.root = merge(merge(a, Some(node)), b);
+        None
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if !self.contains_key(key) {
+            return None;
+        }
+        self.take_at(self.rank(key))
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    // 不小于 key 的元素全部移到返回的新 map 里
+    pub fn split_off<Q>(&mut self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.rank(key);
+        let (a, b) = split_at(self.root.take(), index);
+        self.root = a;
+        OrdMap {
+            root: b,
+            ..OrdMap::new()
+        }
+    }
+
+    // 把 other 的元素全部移进来，key 重复时 other 的值胜出（与 BTreeMap 一致）。
+    // 两边 key 区间不重叠时直接 merge 两棵树。
+    pub fn append(&mut self, other: &mut Self) {
+        let other_root = other.root.take();
+        let (Some(mine), Some(theirs)) = (self.root.as_deref(), other_root.as_deref()) else {
+            self.root = merge(self.root.take(), other_root);
+            return;
+        };
+        if rightmost(mine).key < leftmost(theirs).key {
+            self.root = merge(self.root.take(), other_root);
+        } else if rightmost(theirs).key < leftmost(mine).key {
+            self.root = merge(other_root, self.root.take());
+        } else {
+            // 优先级沿用原来的即可，rest 只是拿来逐个弹出
+            let rest = OrdMap {
+                root: other_root,
+                rng: 1,
+            };
+            self.extend(rest);
+        }
+    }
+}
+
+fn leftmost<K, V>(mut node: &Node<K, V>) -> &Node<K, V> {
+    while let Some(left) = node.left.as_deref() {
+        node = left;
+    }
+    node
+}
+
+fn rightmost<K, V>(mut node: &Node<K, V>) -> &Node<K, V> {
+    while let Some(right) = node.right.as_deref() {
+        node = right;
+    }
+    node
+}
+
+impl<K, V> Default for OrdMap<K, V> {
+    fn default() -> Self {
+        OrdMap::new()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for OrdMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// 树的形状取决于随机优先级，相等只看内容
+impl<K: PartialEq, V: PartialEq> PartialEq for OrdMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for OrdMap<K, V> {}
+
+impl<K, Q, V> Index<&Q> for OrdMap<K, V>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for OrdMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'a, K: Ord + Copy, V: Copy> Extend<(&'a K, &'a V)> for OrdMap<K, V> {
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(k, v)| (*k, *v)));
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for OrdMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = OrdMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V, const N: usize> From<[(K, V); N]> for OrdMap<K, V> {
+    fn from(entries: [(K, V); N]) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    // 还没访问的祖先，栈顶就是下一个元素
+    stack: Vec<&'a Node<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.stack.pop()?;
+        self.remaining -= 1;
+        let mut link = node.right.as_deref();
+        while let Some(next) = link {
+            self.stack.push(next);
+            link = next.left.as_deref();
+        }
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<'a, K, V> IntoIterator for &'a OrdMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+pub struct IntoIter<K, V> {
+    map: OrdMap<K, V>,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        self.map.pop_first()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.map.len(), Some(self.map.len()))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+impl<K, V> IntoIterator for OrdMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter { map: self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::lcg;
+    use std::collections::BTreeMap;
+
+    // 同一组测试分别跑在 std 的 BTreeMap 和 OrdMap 上，证明常用操作的行为一致
+    macro_rules! map_suite {
+        ($name:ident, $map:ident) => {
+            mod $name {
+                use super::*;
+
+                fn sample() -> $map<i32, &'static str> {
+                    let mut map = $map::new();
+                    map.insert(3, "three");
+                    map.insert(1, "one");
+                    map.insert(4, "four");
+                    map.insert(2, "two");
+                    map
+                }
+
+                #[test]
+                fn insert_get_remove() {
+                    let mut map = sample();
+                    assert_eq!(map.len(), 4);
+                    assert_eq!(
+                        format!("{:?}", map),
+                        r#"{1: "one", 2: "two", 3: "three", 4: "four"}"#
+                    );
+                    assert_eq!(map.get(&3), Some(&"three"));
+                    assert_eq!(map[&4], "four");
+                    assert_eq!(map.insert(3, "drei"), Some("three"));
+                    assert_eq!(map.remove(&2), Some("two"));
+                    assert_eq!(map.remove(&2), None);
+                    assert!(!map.contains_key(&2));
+                    *map.get_mut(&1).unwrap() = "eins";
+                    assert_eq!(format!("{:?}", map), r#"{1: "eins", 3: "drei", 4: "four"}"#);
+                }
+
+                #[test]
+                fn iteration_is_sorted() {
+                    let map: $map<i32, i32> =
+                        [5, 1, 9, 3, 7].into_iter().map(|k| (k, k * 10)).collect();
+                    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 3, 5, 7, 9]);
+                    assert_eq!(
+                        map.values().copied().collect::<Vec<_>>(),
+                        [10, 30, 50, 70, 90]
+                    );
+                    assert_eq!((&map).into_iter().len(), 5);
+                    assert_eq!(
+                        map.into_iter().map(|(k, _)| k).collect::<Vec<_>>(),
+                        [1, 3, 5, 7, 9]
+                    );
+                }
+
+                #[test]
+                fn range_bounds() {
+                    let map: $map<i32, ()> = (0..20).step_by(2).map(|k| (k, ())).collect();
+                    let keys =
+                        |it: Vec<(&i32, &())>| it.into_iter().map(|(k, _)| *k).collect::<Vec<_>>();
+                    assert_eq!(keys(map.range(1..=3).collect()), [2]);
+                    assert_eq!(keys(map.range(4..10).collect()), [4, 6, 8]);
+                    assert_eq!(keys(map.range(..4).collect()), [0, 2]);
+                    assert_eq!(keys(map.range(15..).collect()), [16, 18]);
+                    assert_eq!(
+                        keys(
+                            map.range((Bound::Excluded(4), Bound::Included(8)))
+                                .collect()
+                        ),
+                        [6, 8]
+                    );
+                    assert_eq!(map.range(100..).count(), 0);
+                    assert_eq!(map.range(..).count(), 10);
+                    assert_eq!(
+                        map.range((Bound::Included(5), Bound::Excluded(5))).count(),
+                        0
+                    );
+                    assert_eq!(
+                        map.range((Bound::Excluded(5), Bound::Included(5))).count(),
+                        0
+                    );
+                }
+
+                // 3 和 5 之间没有 key，按排名算出来是空区间，也要 panic：只看边界本身
+                #[test]
+                #[should_panic(expected = "range start is greater than range end")]
+                fn range_start_after_end_panics() {
+                    let map: $map<i32, ()> = [(0, ()), (10, ())].into_iter().collect();
+                    let _ = map.range((Bound::Included(5), Bound::Excluded(3)));
+                }
+
+                // 5 不在 map 里，算出来的区间是空的，也要 panic
+                #[test]
+                #[should_panic(expected = "range start and end are equal and excluded")]
+                fn range_equal_excluded_bounds_panic() {
+                    let map: $map<i32, ()> = (0..10).step_by(2).map(|k| (k, ())).collect();
+                    let _ = map.range((Bound::Excluded(5), Bound::Excluded(5)));
+                }
+
+                #[test]
+                fn first_last_and_pop() {
+                    let mut map = sample();
+                    assert_eq!(map.first_key_value(), Some((&1, &"one")));
+                    assert_eq!(map.last_key_value(), Some((&4, &"four")));
+                    assert_eq!(map.pop_first(), Some((1, "one")));
+                    assert_eq!(map.pop_last(), Some((4, "four")));
+                    assert_eq!(map.len(), 2);
+                    map.clear();
+                    assert!(map.is_empty());
+                    assert_eq!(map.pop_first(), None);
+                    assert_eq!(map.last_key_value(), None);
+                }
+
+                #[test]
+                fn split_off_and_append() {
+                    let mut low: $map<i32, i32> = (0..10).map(|k| (k, k)).collect();
+                    let mut high = low.split_off(&6);
+                    assert_eq!(low.keys().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5]);
+                    assert_eq!(high.keys().copied().collect::<Vec<_>>(), [6, 7, 8, 9]);
+
+                    // 重叠的 key 以 other 为准
+                    let mut overlap: $map<i32, i32> = [(5, 500), (20, 20)].into_iter().collect();
+                    high.append(&mut overlap);
+                    assert!(overlap.is_empty());
+                    low.append(&mut high);
+                    assert_eq!(low.len(), 11);
+                    assert_eq!(low[&5], 500);
+                    assert_eq!(low.last_key_value(), Some((&20, &20)));
+                }
+            }
+        };
+    }
+
+    map_suite!(std_btree, BTreeMap);
+    map_suite!(treap, OrdMap);
+
+    #[test]
+    fn random_operations_match_btree_map() {
+        let mut seed = 7;
+        let mut ours = OrdMap::new();
+        let mut std = BTreeMap::new();
+        for _ in 0..5000 {
+            let key = lcg(&mut seed) % 300;
+            match lcg(&mut seed) % 3 {
+                0 | 1 => assert_eq!(ours.insert(key, key * 2), std.insert(key, key * 2)),
+                _ => assert_eq!(ours.remove(&key), std.remove(&key)),
+            }
+            assert_eq!(ours.len(), std.len());
+        }
+        assert!(ours.iter().eq(std.iter()));
+        assert!(ours.range(50..150).eq(std.range(50..150)));
+    }
+
+    #[test]
+    fn order_statistics_match_btree_map() {
+        let mut seed = 42;
+        let std: BTreeMap<u64, ()> = (0..500).map(|_| (lcg(&mut seed) % 2000, ())).collect();
+        let ours: OrdMap<u64, ()> = std.iter().map(|(k, v)| (*k, *v)).collect();
+        let sorted: Vec<u64> = std.keys().copied().collect();
+
+        for (i, key) in sorted.iter().enumerate() {
+            assert_eq!(ours.select(i).map(|(k, _)| k), Some(key));
+            assert_eq!(ours.rank(key), i);
+        }
+        assert_eq!(ours.select(sorted.len()), None);
+
+        for probe in (0..2100).step_by(37) {
+            assert_eq!(ours.rank(&probe), std.range(..probe).count());
+            assert_eq!(
+                ours.floor(&probe).map(|(k, _)| *k),
+                std.range(..=probe).next_back().map(|(k, _)| *k)
+            );
+            assert_eq!(
+                ours.ceiling(&probe).map(|(k, _)| *k),
+                std.range(probe..).next().map(|(k, _)| *k)
+            );
+            let end = probe + 250;
+            assert_eq!(ours.count_range(probe..end), std.range(probe..end).count());
+            assert_eq!(
+                ours.count_range(probe..=end),
+                std.range(probe..=end).count()
+            );
+        }
+        // 反向的范围只是计数为 0，不像 range 那样 panic
+        assert_eq!(
+            ours.count_range((Bound::Excluded(10), Bound::Excluded(5))),
+            0
+        );
+    }
+
+    #[test]
+    fn borrowed_keys() {
+        let mut map: OrdMap<String, usize> = OrdMap::new();
+        for word in "the quick brown fox jumps over the lazy dog".split(' ') {
+            let n = map.get(word).copied().unwrap_or(0);
+            map.insert(word.to_string(), n + 1);
+        }
+        assert_eq!(map["the"], 2);
+        assert_eq!(map.rank("fox"), 2);
+        assert_eq!(map.floor("m").map(|(k, _)| k.as_str()), Some("lazy"));
+        assert_eq!(
+            map.count_range::<str, _>((Bound::Included("d"), Bound::Excluded("p"))),
+            5
+        );
+    }
+
+    #[test]
+    fn disjoint_split_and_append_keep_the_tree_balanced() {
+        let mut map: OrdMap<u32, ()> = (0..10_000).map(|k| (k, ())).collect();
+        let mut parts = Vec::new();
+        for cut in (1000..10_000).step_by(1000).rev() {
+            parts.push(map.split_off(&cut));
+        }
+        assert_eq!(map.len(), 1000);
+        for mut part in parts.into_iter().rev() {
+            map.append(&mut part);
+        }
+        assert_eq!(map.len(), 10_000);
+        assert!(map.keys().copied().eq(0..10_000));
+
+        fn height<K, V>(link: &Link<K, V>) -> usize {
+            link.as_ref()
+                .map_or(0, |n| 1 + height(&n.left).max(height(&n.right)))
+        }
+        // 期望高度约 3·log2(n) ≈ 40，宽松一点
+        assert!(height(&map.root) < 80, "height {}", height(&map.root));
+    }
+}
@@ -0,0 +1,378 @@
+// panic!.md 的配套：把 panic 变成结构化的报告，而不是直接打印到 stderr 后终止
+//
+// - 自定义 panic hook：收集消息、位置、线程名和（可选的）backtrace
+// - `catch`：用 catch_unwind 隔离一个闭包，panic 变成 `Err(PanicReport)`
+// - `PanicLog`：每个报告写成一行 JSON，方便测试脚本批量跑会故意 panic 的例子（比如 option.rs 里 unwrap None）
+//
+// hook 是进程级的，只安装一次，并且会链上原来的 hook：没有处在 `catch` 里的线程照常打印。
+// 报告存在线程局部变量里，hook 在 panic 的线程上执行，所以并发的 catch 互不干扰。
+
+use std::any::Any;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::panic::{self, PanicHookInfo, UnwindSafe};
+use std::path::Path;
+use std::sync::{Mutex, Once};
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PanicReport {
+    pub message: String,
+    // resume_unwind 重新抛出的 panic 不经过 hook，拿不到位置
+    pub location: Option<Location>,
+    pub thread: Option<String>,
+    pub backtrace: Option<String>,
+}
+
+impl PanicReport {
+    fn from_hook(info: &PanicHookInfo, backtrace: bool) -> Self {
+        let backtrace = if backtrace {
+            Some(Backtrace::force_capture())
+        } else {
+            let bt = Backtrace::capture();
+            (bt.status() == BacktraceStatus::Captured).then_some(bt)
+        };
+        PanicReport {
+            message: payload_message(info.payload()),
+            location: info.location().map(|l| Location {
+                file: l.file().to_string(),
+                line: l.line(),
+                column: l.column(),
+            }),
+            thread: thread::current().name().map(str::to_string),
+            backtrace: backtrace.map(|bt| bt.to_string()),
+        }
+    }
+
+    fn from_payload(payload: &(dyn Any + Send)) -> Self {
+        PanicReport {
+            message: payload_message(payload),
+            location: None,
+            thread: thread::current().name().map(str::to_string),
+            backtrace: None,
+        }
+    }
+
+    // 单行 JSON，不带换行
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{");
+        out.push_str(&format!("\"message\":{}", json_string(&self.message)));
+        match &self.location {
+            Some(l) => out.push_str(&format!(
+                ",\"file\":{},\"line\":{},\"column\":{}",
+                json_string(&l.file),
+                l.line,
+                l.column
+            )),
+            None => out.push_str(",\"file\":null,\"line\":null,\"column\":null"),
+        }
+        out.push_str(&format!(",\"thread\":{}", json_opt(self.thread.as_deref())));
+        out.push_str(&format!(
+            ",\"backtrace\":{}",
+            json_opt(self.backtrace.as_deref())
+        ));
+        out.push('}');
+        out
+    }
+}
+
+impl fmt::Display for PanicReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "thread '{}' panicked",
+            self.thread.as_deref().unwrap_or("<unnamed>")
+        )?;
+        if let Some(location) = &self.location {
+            write!(f, " at {location}")?;
+        }
+        write!(f, ":\n{}", self.message)?;
+        if let Some(bt) = &self.backtrace {
+            write!(f, "\nstack backtrace:\n{bt}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PanicReport {}
+
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_opt(s: Option<&str>) -> String {
+    s.map_or_else(|| "null".to_string(), json_string)
+}
+
+thread_local! {
+    // 嵌套的 catch 层数，大于 0 时 hook 不打印，只记录
+    static CAPTURING: Cell<usize> = const { Cell::new(0) };
+    static FORCE_BACKTRACE: Cell<bool> = const { Cell::new(false) };
+    static LAST_REPORT: RefCell<Option<PanicReport>> = const { RefCell::new(None) };
+}
+
+pub fn install_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if CAPTURING.get() == 0 {
+                return previous(info);
+            }
+            let report = PanicReport::from_hook(info, FORCE_BACKTRACE.get());
+            LAST_REPORT.set(Some(report));
+        }));
+    });
+}
+
+fn catch_inner<F, R>(f: F, backtrace: bool) -> Result<R, PanicReport>
+where
+    F: FnOnce() -> R + UnwindSafe,
+{
+    install_hook();
+    let saved_backtrace = FORCE_BACKTRACE.replace(backtrace);
+    CAPTURING.set(CAPTURING.get() + 1);
+    LAST_REPORT.set(None);
+    let result = panic::catch_unwind(f);
+    CAPTURING.set(CAPTURING.get() - 1);
+    FORCE_BACKTRACE.set(saved_backtrace);
+    let last = LAST_REPORT.take();
+
+    // hook 记下的不一定是这次的 panic：闭包里的 std catch_unwind 吞掉一个 panic 之后，
+    // 再用 resume_unwind 抛出别的 payload 不会经过 hook。消息对不上就只用 payload
+    result.map_err(|payload| {
+        let message = payload_message(&*payload);
+        last.filter(|report| report.message == message)
+            .unwrap_or_else(|| PanicReport::from_payload(&*payload))
+    })
+}
+
+// 运行闭包，panic 转成 Err；backtrace 是否采集取决于 RUST_BACKTRACE
+pub fn catch<F, R>(f: F) -> Result<R, PanicReport>
+where
+    F: FnOnce() -> R + UnwindSafe,
+{
+    catch_inner(f, false)
+}
+
+pub fn catch_with_backtrace<F, R>(f: F) -> Result<R, PanicReport>
+where
+    F: FnOnce() -> R + UnwindSafe,
+{
+    catch_inner(f, true)
+}
+
+// 每个被捕获的 panic 写一行 JSON，多出一个 "task" 字段记录是哪个任务
+pub struct PanicLog {
+    out: Mutex<Box<dyn Write + Send>>,
+}
+
+impl PanicLog {
+    pub fn new(out: impl Write + Send + 'static) -> Self {
+        PanicLog {
+            out: Mutex::new(Box::new(out)),
+        }
+    }
+
+    // 追加写入，不会清掉之前的记录
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(PanicLog::new(file))
+    }
+
+    pub fn record(&self, task: &str, report: &PanicReport) -> io::Result<()> {
+        let json = report.to_json();
+        let line = format!("{{\"task\":{},{}", json_string(task), &json[1..]);
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(out, "{line}")?;
+        out.flush()
+    }
+
+    // 隔离运行一个任务，panic 时记一行日志再把报告返回
+    pub fn run<F, R>(&self, task: &str, f: F) -> Result<R, PanicReport>
+    where
+        F: FnOnce() -> R + UnwindSafe,
+    {
+        catch(f).inspect_err(|report| {
+            // 日志写不进去不应该影响调用方拿到报告
+            let _ = self.record(task, report);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn captures_message_location_and_thread() {
+        let report = thread::Builder::new()
+            .name("worker-7".to_string())
+            .spawn(|| {
+                catch(|| {
+                    let none: Option<i32> = std::hint::black_box(None);
+                    none.unwrap()
+                })
+                .unwrap_err()
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        assert!(report
+            .message
+            .contains("`Option::unwrap()` on a `None` value"));
+        assert_eq!(report.thread.as_deref(), Some("worker-7"));
+        let location = report.location.unwrap();
+        assert!(location.file.ends_with("panics.rs"));
+        assert!(location.line > 0);
+    }
+
+    #[test]
+    fn ok_values_pass_through() {
+        assert_eq!(catch(|| 1 + 1), Ok(2));
+        let msg = catch(|| panic!("formatted {}", 42)).unwrap_err().message;
+        assert_eq!(msg, "formatted 42");
+    }
+
+    #[test]
+    fn forced_backtrace() {
+        let report = catch_with_backtrace(|| panic!("with trace")).unwrap_err();
+        assert!(report.backtrace.is_some());
+        assert!(report.to_string().contains("stack backtrace:"));
+    }
+
+    #[test]
+    fn nested_and_resumed_panics() {
+        let outer = catch(|| {
+            let inner = catch(|| panic!("inner")).unwrap_err();
+            assert_eq!(inner.message, "inner");
+            // resume_unwind 不调用 hook，只能从 payload 里取消息
+            panic::resume_unwind(Box::new("resumed".to_string()));
+        })
+        .unwrap_err();
+        assert_eq!(outer.message, "resumed");
+        assert_eq!(outer.location, None);
+    }
+
+    #[test]
+    fn swallowed_panic_is_not_reported() {
+        let report = catch(|| {
+            let _ = panic::catch_unwind(|| panic!("swallowed"));
+            panic::resume_unwind(Box::new("later"));
+        })
+        .unwrap_err();
+        assert_eq!(report.message, "later");
+        assert_eq!(report.location, None);
+
+        // 原样重新抛出同一个 payload 时，hook 记下的位置仍然是对的
+        let report = catch(|| {
+            let payload = panic::catch_unwind(|| panic!("rethrown")).unwrap_err();
+            panic::resume_unwind(payload);
+        })
+        .unwrap_err();
+        assert_eq!(report.message, "rethrown");
+        assert!(report.location.unwrap().file.ends_with("panics.rs"));
+    }
+
+    #[test]
+    fn json_escaping() {
+        let report = PanicReport {
+            message: "line \"one\"\n\tline\\two\u{1}".to_string(),
+            location: None,
+            thread: None,
+            backtrace: None,
+        };
+        assert_eq!(
+            report.to_json(),
+            r#"{"message":"line \"one\"\n\tline\\two\u0001","file":null,"line":null,"column":null,"thread":null,"backtrace":null}"#
+        );
+    }
+
+    // 写到共享内存里，测试结束后检查内容
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_lines_log() {
+        let buf = Shared::default();
+        let log = PanicLog::new(buf.clone());
+
+        assert_eq!(log.run("fine", || "ok"), Ok("ok"));
+        assert!(log
+            .run("unwrap_none", || std::hint::black_box(None::<i32>).unwrap())
+            .is_err());
+        assert!(log.run("divide", || panic!("Division by zero")).is_err());
+
+        let text = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(r#"{"task":"unwrap_none","message":"#));
+        assert!(lines[1].contains(r#""message":"Division by zero""#));
+        assert!(lines.iter().all(|l| l.ends_with('}')));
+    }
+
+    #[test]
+    fn log_file_appends() {
+        let dir = crate::guard::TempDir::new("x_library-panics").unwrap();
+        let path = dir.path().join("panics.jsonl");
+        for _ in 0..2 {
+            let log = PanicLog::open(&path).unwrap();
+            let _ = log.run("again", || panic!("again"));
+        }
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), 2);
+    }
+}
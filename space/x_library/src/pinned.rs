@@ -0,0 +1,275 @@
+// Pin.md 里说的「自引用结构体」的可运行版本
+//
+// - `SelfRef`：内联缓冲区 + 指向缓冲区内部的指针，带 `PhantomPinned`，只能以 `Pin<Box<_>>` 的形式构造
+// - `Counted`：手写的字段投影（pin projection），被 Pin 的字段投影成 `Pin<&mut F>`，普通字段投影成 `&mut`
+// - `Fill`：手写的 Future，第一次 poll 时才把游标指向自己的缓冲区，之后每次 poll 都靠这个指针写数据，
+//   这正是 async 状态机需要 Pin 的原因
+//
+// 缓冲区是内联数组而不是 String：堆上的数据本来就不会随结构体移动，放在结构体里面才会真正出问题。
+
+use std::future::Future;
+use std::marker::PhantomPinned;
+use std::pin::Pin;
+use std::ptr::NonNull;
+use std::task::{Context, Poll};
+
+pub const CAPACITY: usize = 64;
+
+pub struct SelfRef {
+    data: [u8; CAPACITY],
+    len: usize,
+    // 指向 data 里的某一段，构造时是第一个单词
+    view: NonNull<[u8]>,
+    _pin: PhantomPinned,
+}
+
+// SAFETY: view 只指向自己的 data，跟着整个值一起跨线程，不与别人共享
+unsafe impl Send for SelfRef {}
+unsafe impl Sync for SelfRef {}
+
+impl SelfRef {
+    // 先在堆上放好，再把指针指向堆上的 data；返回的 Pin 保证之后不会再移动
+    pub fn new(text: &str) -> Pin<Box<SelfRef>> {
+        assert!(text.len() <= CAPACITY, "text longer than {CAPACITY} bytes");
+        let mut data = [0; CAPACITY];
+        data[..text.len()].copy_from_slice(text.as_bytes());
+        let mut boxed = Box::pin(SelfRef {
+            data,
+            len: text.len(),
+            view: NonNull::from(&[][..]),
+            _pin: PhantomPinned,
+        });
+        let end = text.find(' ').unwrap_or(text.len());
+        boxed.as_mut().set_view(0..end);
+        boxed
+    }
+
+    pub fn text(self: Pin<&Self>) -> &str {
+        let this = self.get_ref();
+        std::str::from_utf8(&this.data[..this.len]).unwrap()
+    }
+
+    // 通过自引用指针读
+    pub fn view(self: Pin<&Self>) -> &str {
+        // SAFETY: view 总是指向 self.data 中已初始化的、位于字符边界上的一段，self 被 Pin 住不会移动
+        unsafe { std::str::from_utf8_unchecked(self.get_ref().view.as_ref()) }
+    }
+
+    pub fn set_view(self: Pin<&mut Self>, range: std::ops::Range<usize>) {
+        // SAFETY: 只改字段，不把 self 移出去
+        let this = unsafe { self.get_unchecked_mut() };
+        let text = std::str::from_utf8(&this.data[..this.len]).unwrap();
+        assert!(text.get(range.clone()).is_some(), "invalid view {range:?}");
+        this.view = NonNull::from(&this.data[range]);
+    }
+
+    // 追加内容不会让 view 失效：缓冲区是内联的，不会重新分配
+    pub fn push_str(self: Pin<&mut Self>, s: &str) {
+        // SAFETY: 同上
+        let this = unsafe { self.get_unchecked_mut() };
+        assert!(this.len + s.len() <= CAPACITY, "buffer full");
+        this.data[this.len..this.len + s.len()].copy_from_slice(s.as_bytes());
+        this.len += s.len();
+    }
+
+    // 自引用指针是否还落在自己的缓冲区里
+    pub fn is_consistent(self: Pin<&Self>) -> bool {
+        let this = self.get_ref();
+        let start = this.data.as_ptr() as usize;
+        let view = this.view.as_ptr() as *const u8 as usize;
+        start <= view && view + this.view.len() <= start + this.len
+    }
+}
+
+// 给一个 Future 数 poll 次数
+pub struct Counted<F> {
+    inner: F,
+    polls: usize,
+}
+
+// 投影结果：结构性 Pin 的字段是 Pin<&mut F>，其余字段是普通的 &mut
+pub struct CountedProj<'a, F> {
+    pub inner: Pin<&'a mut F>,
+    pub polls: &'a mut usize,
+}
+
+impl<F> Counted<F> {
+    pub fn new(inner: F) -> Self {
+        Counted { inner, polls: 0 }
+    }
+
+    pub fn polls(&self) -> usize {
+        self.polls
+    }
+
+    pub fn project(self: Pin<&mut Self>) -> CountedProj<'_, F> {
+        // SAFETY: inner 只以 Pin 的形式暴露出去，Counted 没有实现 Drop，也没有 Unpin 的手动实现，
+        // polls 不是结构性 Pin 的字段，给出 &mut 没有问题
+        let this = unsafe { self.get_unchecked_mut() };
+        CountedProj {
+            inner: unsafe { Pin::new_unchecked(&mut this.inner) },
+            polls: &mut this.polls,
+        }
+    }
+
+    pub fn project_ref(self: Pin<&Self>) -> Pin<&F> {
+        // SAFETY: 同 project
+        unsafe { self.map_unchecked(|this| &this.inner) }
+    }
+}
+
+impl<F: Future> Future for Counted<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let this = self.project();
+        *this.polls += 1;
+        this.inner.poll(cx)
+    }
+}
+
+// 每次 poll 把 chunk 个字节拷进自己的缓冲区，拷完返回整个字符串
+pub struct Fill {
+    src: Vec<u8>,
+    chunk: usize,
+    buf: [u8; CAPACITY],
+    // 第一次 poll 之前是 None：那时还没被 Pin 住，地址不可信
+    cursor: Option<NonNull<u8>>,
+    _pin: PhantomPinned,
+}
+
+// SAFETY: 同 SelfRef，cursor 只指向自己的 buf
+unsafe impl Send for Fill {}
+
+impl Fill {
+    pub fn new(src: &str, chunk: usize) -> Fill {
+        assert!(src.len() <= CAPACITY, "text longer than {CAPACITY} bytes");
+        assert!(chunk > 0);
+        Fill {
+            src: src.as_bytes().to_vec(),
+            chunk,
+            buf: [0; CAPACITY],
+            cursor: None,
+            _pin: PhantomPinned,
+        }
+    }
+
+    // 已经写入缓冲区的字节数，由游标和缓冲区起点算出来
+    pub fn filled(self: Pin<&Self>) -> usize {
+        let this = self.get_ref();
+        match this.cursor {
+            None => 0,
+            Some(c) => c.as_ptr() as usize - this.buf.as_ptr() as usize,
+        }
+    }
+}
+
+impl Future for Fill {
+    type Output = String;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<String> {
+        // SAFETY: 不会把 self 移出去
+        let this = unsafe { self.get_unchecked_mut() };
+        let start = this.buf.as_mut_ptr();
+        let cursor = *this
+            .cursor
+            .get_or_insert_with(|| NonNull::new(start).unwrap());
+
+        let filled = cursor.as_ptr() as usize - start as usize;
+        let n = this.chunk.min(this.src.len() - filled);
+        // SAFETY: cursor 指向 buf[filled..]，n 不会越过 src.len() <= CAPACITY
+        unsafe {
+            std::ptr::copy_nonoverlapping(this.src.as_ptr().add(filled), cursor.as_ptr(), n);
+            this.cursor = Some(NonNull::new_unchecked(cursor.as_ptr().add(n)));
+        }
+
+        if filled + n == this.src.len() {
+            Poll::Ready(String::from_utf8_lossy(&this.buf[..filled + n]).into_owned())
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Waker;
+
+    fn poll_once<F: Future + ?Sized>(fut: Pin<&mut F>) -> Poll<F::Output> {
+        fut.poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    #[test]
+    fn view_survives_moves_of_the_box() {
+        let s = SelfRef::new("hello pinned world");
+        let view_addr = s.as_ref().view().as_ptr();
+        assert_eq!(s.as_ref().view(), "hello");
+
+        // 移动的是 Box 这个指针，堆上的 SelfRef 不动
+        let moved = s;
+        let mut holder = vec![moved];
+        let s = holder.pop().unwrap();
+        let s = std::thread::spawn(move || s).join().unwrap();
+
+        assert_eq!(s.as_ref().view().as_ptr(), view_addr);
+        assert_eq!(s.as_ref().view(), "hello");
+        assert!(s.as_ref().is_consistent());
+    }
+
+    #[test]
+    fn mutate_through_pin() {
+        let mut s = SelfRef::new("ab");
+        s.as_mut().push_str(" cd");
+        assert_eq!(s.as_ref().text(), "ab cd");
+        assert_eq!(s.as_ref().view(), "ab");
+
+        s.as_mut().set_view(3..5);
+        assert_eq!(s.as_ref().view(), "cd");
+        assert!(s.as_ref().is_consistent());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid view")]
+    fn view_out_of_bounds() {
+        SelfRef::new("ab").as_mut().set_view(1..9);
+    }
+
+    #[test]
+    fn fill_keeps_cursor_across_moves() {
+        let mut fut = Box::pin(Fill::new("self referential", 5));
+        assert_eq!(fut.as_ref().filled(), 0);
+        assert!(poll_once(fut.as_mut()).is_pending());
+        assert_eq!(fut.as_ref().filled(), 5);
+
+        // 两次 poll 之间把 Box 挪来挪去，游标依然指向同一块缓冲区
+        let mut fut = std::thread::spawn(move || fut).join().unwrap();
+        assert!(poll_once(fut.as_mut()).is_pending());
+        let mut slot = Some(fut);
+        let mut fut = slot.take().unwrap();
+        assert!(poll_once(fut.as_mut()).is_pending());
+        assert_eq!(fut.as_ref().filled(), 15);
+        assert_eq!(
+            poll_once(fut.as_mut()),
+            Poll::Ready("self referential".to_string())
+        );
+    }
+
+    #[test]
+    fn counted_projection() {
+        let mut fut = Box::pin(Counted::new(Fill::new("abcdefg", 3)));
+        while poll_once(fut.as_mut()).is_pending() {}
+        assert_eq!(fut.polls(), 3);
+        assert_eq!(fut.as_ref().project_ref().filled(), 7);
+
+        let out = futures::executor::block_on(Counted::new(Fill::new("xyz", 1)));
+        assert_eq!(out, "xyz");
+    }
+
+    #[tokio::test]
+    async fn works_under_await() {
+        let fut = Counted::new(Fill::new("awaited", 2));
+        assert_eq!(fut.await, "awaited");
+    }
+}
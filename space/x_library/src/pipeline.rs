@@ -0,0 +1,403 @@
+// 异步流处理管道：producer -> 有界 channel -> map/filter/batch/throttle -> sink
+//
+// 每个阶段是一个独立的 tokio 任务，阶段之间用容量为 `capacity` 的有界 mpsc 连接。
+// sink 处理得慢时，channel 被填满，上游的 `send` 就会挂起 —— 这就是背压(backpressure)，
+// 它会一级一级传回 producer，而不是让数据无限堆积在内存里。
+//
+// 时间一律用 tokio::time::Instant，这样在 `start_paused = true` 的测试里结果是确定的。
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::{Stream, StreamExt};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+// 单个阶段的统计
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StageReport {
+    pub name: String,
+    pub items_in: u64,
+    pub items_out: u64,
+    // 阶段自身处理所花的总时间（map 的 future、sink 的 future 等）
+    pub busy: Duration,
+    // 下游 channel 已满、send 必须等待的次数和总时长
+    pub blocked_sends: u64,
+    pub blocked: Duration,
+}
+
+impl StageReport {
+    // 每个输入元素的平均处理延迟
+    pub fn mean_latency(&self) -> Duration {
+        if self.items_in == 0 {
+            return Duration::ZERO;
+        }
+        // 用纳秒做整数除法：items_in 超过 u32 也不会截断
+        let nanos = self.busy.as_nanos() / self.items_in as u128;
+        Duration::new(
+            (nanos / 1_000_000_000) as u64,
+            (nanos % 1_000_000_000) as u32,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PipelineReport {
+    pub stages: Vec<StageReport>,
+    pub elapsed: Duration,
+}
+
+impl PipelineReport {
+    pub fn stage(&self, name: &str) -> Option<&StageReport> {
+        self.stages.iter().find(|s| s.name == name)
+    }
+}
+
+type Stats = Arc<Mutex<StageReport>>;
+
+fn new_stats(name: &str) -> Stats {
+    Arc::new(Mutex::new(StageReport {
+        name: name.to_string(),
+        ..Default::default()
+    }))
+}
+
+// 先 try_send，满了才记一次背压再 await；返回 false 表示下游已经关闭
+async fn send_tracked<T>(tx: &mpsc::Sender<T>, item: T, stats: &Stats) -> bool {
+    match tx.try_send(item) {
+        Ok(()) => {}
+        Err(TrySendError::Closed(_)) => return false,
+        Err(TrySendError::Full(item)) => {
+            let start = Instant::now();
+            let sent = tx.send(item).await.is_ok();
+            let mut s = stats.lock().unwrap();
+            s.blocked_sends += 1;
+            s.blocked += start.elapsed();
+            if !sent {
+                return false;
+            }
+        }
+    }
+    stats.lock().unwrap().items_out += 1;
+    true
+}
+
+pub struct Pipeline<T> {
+    rx: mpsc::Receiver<T>,
+    capacity: usize,
+    stats: Vec<Stats>,
+    tasks: Vec<JoinHandle<()>>,
+    started: Instant,
+}
+
+impl<T: Send + 'static> Pipeline<T> {
+    // 以一个 Stream 作为 producer，名字固定为 "source"
+    pub fn from_stream<S>(source: S, capacity: usize) -> Self
+    where
+        S: Stream<Item = T> + Send + 'static,
+    {
+        assert!(capacity > 0, "channel capacity must be positive");
+        let (tx, rx) = mpsc::channel(capacity);
+        let stats = new_stats("source");
+        let task_stats = Arc::clone(&stats);
+        let task = tokio::spawn(async move {
+            futures::pin_mut!(source);
+            while let Some(item) = source.next().await {
+                task_stats.lock().unwrap().items_in += 1;
+                if !send_tracked(&tx, item, &task_stats).await {
+                    break;
+                }
+            }
+        });
+        Pipeline {
+            rx,
+            capacity,
+            stats: vec![stats],
+            tasks: vec![task],
+            started: Instant::now(),
+        }
+    }
+
+    // 追加一个阶段：`body` 拿到上游 receiver 和下游 sender，自己决定怎么转发
+    fn stage<U, F, Fut>(mut self, name: &str, body: F) -> Pipeline<U>
+    where
+        U: Send + 'static,
+        F: FnOnce(mpsc::Receiver<T>, mpsc::Sender<U>, Stats) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(self.capacity);
+        let stats = new_stats(name);
+        self.tasks
+            .push(tokio::spawn(body(self.rx, tx, Arc::clone(&stats))));
+        self.stats.push(stats);
+        Pipeline {
+            rx,
+            capacity: self.capacity,
+            stats: self.stats,
+            tasks: self.tasks,
+            started: self.started,
+        }
+    }
+
+    pub fn map<U, F, Fut>(self, name: &str, mut f: F) -> Pipeline<U>
+    where
+        U: Send + 'static,
+        F: FnMut(T) -> Fut + Send + 'static,
+        Fut: Future<Output = U> + Send,
+    {
+        self.stage(name, |mut rx, tx, stats| async move {
+            while let Some(item) = rx.recv().await {
+                let start = Instant::now();
+                stats.lock().unwrap().items_in += 1;
+                let out = f(item).await;
+                stats.lock().unwrap().busy += start.elapsed();
+                if !send_tracked(&tx, out, &stats).await {
+                    break;
+                }
+            }
+        })
+    }
+
+    pub fn filter<F, Fut>(self, name: &str, mut pred: F) -> Pipeline<T>
+    where
+        F: FnMut(&T) -> Fut + Send + 'static,
+        Fut: Future<Output = bool> + Send,
+    {
+        self.stage(name, |mut rx, tx, stats| async move {
+            while let Some(item) = rx.recv().await {
+                let start = Instant::now();
+                stats.lock().unwrap().items_in += 1;
+                let keep = pred(&item).await;
+                stats.lock().unwrap().busy += start.elapsed();
+                if keep && !send_tracked(&tx, item, &stats).await {
+                    break;
+                }
+            }
+        })
+    }
+
+    // 每攒够 `size` 个元素发一批，上游结束时把不足一批的剩余部分也发出去
+    pub fn batch(self, name: &str, size: usize) -> Pipeline<Vec<T>> {
+        assert!(size > 0, "batch size must be positive");
+        self.stage(name, move |mut rx, tx, stats| async move {
+            let mut buf = Vec::with_capacity(size);
+            while let Some(item) = rx.recv().await {
+                stats.lock().unwrap().items_in += 1;
+                buf.push(item);
+                if buf.len() == size {
+                    let full = std::mem::replace(&mut buf, Vec::with_capacity(size));
+                    if !send_tracked(&tx, full, &stats).await {
+                        return;
+                    }
+                }
+            }
+            if !buf.is_empty() {
+                send_tracked(&tx, buf, &stats).await;
+            }
+        })
+    }
+
+    // 相邻两个元素的发出间隔至少为 `interval`
+    pub fn throttle(self, name: &str, interval: Duration) -> Pipeline<T> {
+        self.stage(name, move |mut rx, tx, stats| async move {
+            let mut next_slot = Instant::now();
+            while let Some(item) = rx.recv().await {
+                let start = Instant::now();
+                stats.lock().unwrap().items_in += 1;
+                tokio::time::sleep_until(next_slot).await;
+                stats.lock().unwrap().busy += start.elapsed();
+                next_slot = Instant::now() + interval;
+                if !send_tracked(&tx, item, &stats).await {
+                    break;
+                }
+            }
+        })
+    }
+
+    // 以 sink 结束管道，等所有阶段退出后返回各阶段的统计
+    pub async fn run<F, Fut>(mut self, name: &str, mut sink: F) -> PipelineReport
+    where
+        F: FnMut(T) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let stats = new_stats(name);
+        while let Some(item) = self.rx.recv().await {
+            let start = Instant::now();
+            sink(item).await;
+            let mut s = stats.lock().unwrap();
+            s.items_in += 1;
+            s.busy += start.elapsed();
+        }
+        for task in self.tasks {
+            task.await.expect("pipeline stage panicked");
+        }
+        self.stats.push(stats);
+
+        PipelineReport {
+            stages: self
+                .stats
+                .iter()
+                .map(|s| s.lock().unwrap().clone())
+                .collect(),
+            elapsed: self.started.elapsed(),
+        }
+    }
+
+    // 不用 sink，直接把尾部当作 Stream 交给调用者
+    pub fn into_stream(self) -> impl Stream<Item = T> {
+        futures::stream::unfold(self.rx, |mut rx| async move {
+            rx.recv().await.map(|item| (item, rx))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn mean_latency_with_huge_counts() {
+        let report = |items_in, busy| StageReport {
+            items_in,
+            busy,
+            ..StageReport::default()
+        };
+        assert_eq!(
+            report(0, Duration::from_secs(1)).mean_latency(),
+            Duration::ZERO
+        );
+        assert_eq!(
+            report(1 << 32, Duration::from_secs(1 << 32)).mean_latency(),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            report((1 << 32) + 1, Duration::from_nanos(3 * ((1 << 32) + 1))).mean_latency(),
+            Duration::from_nanos(3)
+        );
+        assert_eq!(report(1, Duration::MAX).mean_latency(), Duration::MAX);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn map_filter_batch_output() {
+        let mut out = Vec::new();
+        let report = Pipeline::from_stream(stream::iter(1..=10), 2)
+            .map("square", |x: i32| async move { x * x })
+            .filter("even", |x: &i32| {
+                let even = x % 2 == 0;
+                async move { even }
+            })
+            .batch("batch", 2)
+            .run("sink", |b| {
+                out.push(b);
+                async {}
+            })
+            .await;
+
+        assert_eq!(out, vec![vec![4, 16], vec![36, 64], vec![100]]);
+        assert_eq!(report.stage("source").unwrap().items_out, 10);
+        assert_eq!(report.stage("even").unwrap().items_in, 10);
+        assert_eq!(report.stage("even").unwrap().items_out, 5);
+        assert_eq!(report.stage("batch").unwrap().items_out, 3);
+        assert_eq!(report.stage("sink").unwrap().items_in, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_sink_applies_backpressure() {
+        const CAPACITY: usize = 2;
+        let produced = Arc::new(AtomicUsize::new(0));
+        let consumed = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+        let p = Arc::clone(&produced);
+        let source = stream::iter(0..20).inspect(move |_| {
+            p.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let (c, m, p) = (
+            Arc::clone(&consumed),
+            Arc::clone(&max_in_flight),
+            Arc::clone(&produced),
+        );
+        let report = Pipeline::from_stream(source, CAPACITY)
+            .map("identity", |x: i32| async move { x })
+            .run("sink", move |_| {
+                let in_flight = p.load(Ordering::SeqCst) - c.load(Ordering::SeqCst);
+                m.fetch_max(in_flight, Ordering::SeqCst);
+                let c = Arc::clone(&c);
+                async move {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    c.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+            .await;
+
+        // 两个 channel 各 CAPACITY 个，加上每个阶段手里各攥着一个
+        assert!(max_in_flight.load(Ordering::SeqCst) <= 2 * CAPACITY + 3);
+        let source = report.stage("source").unwrap();
+        assert!(source.blocked_sends > 0);
+        assert!(source.blocked >= Duration::from_millis(1000));
+        assert!(report.stage("identity").unwrap().blocked_sends > 0);
+        assert_eq!(report.elapsed, Duration::from_millis(2000));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fast_sink_has_no_backpressure() {
+        let report = Pipeline::from_stream(stream::iter(0..5), 8)
+            .map("identity", |x: i32| async move { x })
+            .run("sink", |_| async {})
+            .await;
+
+        for stage in &report.stages {
+            assert_eq!(stage.blocked_sends, 0, "stage {}", stage.name);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn throttle_spaces_out_items() {
+        let start = Instant::now();
+        let mut stamps = Vec::new();
+        Pipeline::from_stream(stream::iter(0..5), 4)
+            .throttle("throttle", Duration::from_millis(100))
+            .run("sink", |_| {
+                stamps.push(start.elapsed());
+                async {}
+            })
+            .await;
+
+        let expected: Vec<_> = (0..5).map(|i| Duration::from_millis(i * 100)).collect();
+        assert_eq!(stamps, expected);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn per_stage_latency() {
+        let report = Pipeline::from_stream(stream::iter(0..4), 4)
+            .map("slow", |x: i32| async move {
+                tokio::time::sleep(Duration::from_millis(30)).await;
+                x
+            })
+            .map("fast", |x: i32| async move { x + 1 })
+            .run("sink", |_| async {})
+            .await;
+
+        assert_eq!(
+            report.stage("slow").unwrap().mean_latency(),
+            Duration::from_millis(30)
+        );
+        assert_eq!(report.stage("fast").unwrap().mean_latency(), Duration::ZERO);
+        assert_eq!(report.elapsed, Duration::from_millis(120));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn into_stream_yields_tail() {
+        let v: Vec<i32> = Pipeline::from_stream(stream::iter(1..=3), 1)
+            .map("neg", |x: i32| async move { -x })
+            .into_stream()
+            .collect()
+            .await;
+        assert_eq!(v, [-1, -2, -3]);
+    }
+}
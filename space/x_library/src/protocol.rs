@@ -0,0 +1,727 @@
+// 长度前缀的二进制分帧协议 + 多路复用的请求/响应客户端
+//
+// echo 服务器里 `let mut buffer = [0; 1024]` 一次 read 的结果并不等于一条消息：
+// TCP 是字节流，大消息会被拆开，小消息可能被合并。分帧就是在字节流上重新划出消息边界。
+//
+// 帧格式（整数都是大端序）：
+//
+//   | len: u32 | kind: u8 | id: u32 | payload ... |
+//
+// `len` 是 len 字段之后的字节数（1 + 4 + payload.len()）。
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, oneshot};
+
+const HEADER_LEN: usize = 1 + 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    Request = 0,
+    Response = 1,
+    // 协议层面的错误（比如请求解码失败），payload 是 UTF-8 的错误描述
+    Error = 2,
+}
+
+impl TryFrom<u8> for MessageKind {
+    type Error = FrameError;
+
+    fn try_from(tag: u8) -> Result<Self, FrameError> {
+        match tag {
+            0 => Ok(MessageKind::Request),
+            1 => Ok(MessageKind::Response),
+            2 => Ok(MessageKind::Error),
+            other => Err(FrameError::UnknownKind(other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub kind: MessageKind,
+    pub id: u32,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum FrameError {
+    Io(io::Error),
+    TooLarge { len: usize, max: usize },
+    UnknownKind(u8),
+    // len 字段比头部还短
+    Malformed(usize),
+    Decode(String),
+    Remote(String),
+    ConnectionClosed,
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FrameError::Io(e) => write!(f, "io error: {e}"),
+            FrameError::TooLarge { len, max } => {
+                write!(f, "frame of {len} bytes exceeds maximum of {max}")
+            }
+            FrameError::UnknownKind(tag) => write!(f, "unknown message kind {tag}"),
+            FrameError::Malformed(len) => write!(f, "frame length {len} is shorter than header"),
+            FrameError::Decode(msg) => write!(f, "cannot decode payload: {msg}"),
+            FrameError::Remote(msg) => write!(f, "remote error: {msg}"),
+            FrameError::ConnectionClosed => write!(f, "connection closed"),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FrameError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl FrameError {
+    // 连接断开时要把同一个错误交给每个还在等待的调用者，io::Error 不能 Clone，只能照着重建一份
+    fn duplicate(&self) -> FrameError {
+        match self {
+            FrameError::Io(e) => FrameError::Io(io::Error::new(e.kind(), e.to_string())),
+            FrameError::TooLarge { len, max } => FrameError::TooLarge {
+                len: *len,
+                max: *max,
+            },
+            FrameError::UnknownKind(tag) => FrameError::UnknownKind(*tag),
+            FrameError::Malformed(len) => FrameError::Malformed(*len),
+            FrameError::Decode(msg) => FrameError::Decode(msg.clone()),
+            FrameError::Remote(msg) => FrameError::Remote(msg.clone()),
+            FrameError::ConnectionClosed => FrameError::ConnectionClosed,
+        }
+    }
+}
+
+impl From<io::Error> for FrameError {
+    fn from(e: io::Error) -> Self {
+        FrameError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FrameCodec {
+    // 单帧（不含 len 字段本身）允许的最大字节数
+    pub max_frame_size: usize,
+}
+
+impl Default for FrameCodec {
+    fn default() -> Self {
+        FrameCodec {
+            max_frame_size: 8 * 1024 * 1024,
+        }
+    }
+}
+
+impl FrameCodec {
+    pub fn new(max_frame_size: usize) -> Self {
+        FrameCodec { max_frame_size }
+    }
+
+    fn check_len(&self, len: usize) -> Result<(), FrameError> {
+        // len 字段只有 u32，max_frame_size 设得再大也写不下更长的帧
+        let max = self.max_frame_size.min(u32::MAX as usize);
+        if len > max {
+            return Err(FrameError::TooLarge { len, max });
+        }
+        if len < HEADER_LEN {
+            return Err(FrameError::Malformed(len));
+        }
+        Ok(())
+    }
+
+    pub fn encode(&self, frame: &Frame, dst: &mut Vec<u8>) -> Result<(), FrameError> {
+        let len = HEADER_LEN + frame.payload.len();
+        self.check_len(len)?;
+        dst.reserve(4 + len);
+        dst.extend_from_slice(&(len as u32).to_be_bytes());
+        dst.push(frame.kind as u8);
+        dst.extend_from_slice(&frame.id.to_be_bytes());
+        dst.extend_from_slice(&frame.payload);
+        Ok(())
+    }
+
+    // 从缓冲区头部解出一帧并消费掉对应字节；数据还不够一帧时返回 Ok(None)
+    pub fn decode(&self, src: &mut Vec<u8>) -> Result<Option<Frame>, FrameError> {
+        if src.len() < 4 {
+            return Ok(None);
+        }
+        let len = u32::from_be_bytes(src[..4].try_into().unwrap()) as usize;
+        self.check_len(len)?;
+        if src.len() < 4 + len {
+            return Ok(None);
+        }
+        let kind = MessageKind::try_from(src[4])?;
+        let id = u32::from_be_bytes(src[5..9].try_into().unwrap());
+        let payload = src[4 + HEADER_LEN..4 + len].to_vec();
+        src.drain(..4 + len);
+        Ok(Some(Frame { kind, id, payload }))
+    }
+
+    // 从异步流里读一整帧；对端在帧边界处正常关闭时返回 Ok(None)
+    pub async fn read_frame<R>(&self, reader: &mut R) -> Result<Option<Frame>, FrameError>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut len_buf = [0u8; 4];
+        match reader.read_exact(&mut len_buf).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        let len = u32::from_be_bytes(len_buf) as usize;
+        // 先检查长度再分配，防止对端用一个巨大的 len 让我们分配内存
+        self.check_len(len)?;
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body).await?;
+        Ok(Some(Frame {
+            kind: MessageKind::try_from(body[0])?,
+            id: u32::from_be_bytes(body[1..5].try_into().unwrap()),
+            payload: body.split_off(HEADER_LEN),
+        }))
+    }
+
+    pub async fn write_frame<W>(&self, writer: &mut W, frame: &Frame) -> Result<(), FrameError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut buf = Vec::new();
+        self.encode(frame, &mut buf)?;
+        writer.write_all(&buf).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+}
+
+// 可以放进 payload 的类型
+pub trait Wire: Sized {
+    fn to_bytes(&self) -> Vec<u8>;
+    fn from_bytes(bytes: &[u8]) -> Result<Self, FrameError>;
+}
+
+impl Wire for Vec<u8> {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, FrameError> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl Wire for String {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, FrameError> {
+        String::from_utf8(bytes.to_vec()).map_err(|e| FrameError::Decode(e.to_string()))
+    }
+}
+
+impl Wire for u64 {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, FrameError> {
+        let arr: [u8; 8] = bytes
+            .try_into()
+            .map_err(|_| FrameError::Decode(format!("expected 8 bytes, got {}", bytes.len())))?;
+        Ok(u64::from_be_bytes(arr))
+    }
+}
+
+type Pending = Arc<Mutex<Option<HashMap<u32, oneshot::Sender<Result<Vec<u8>, FrameError>>>>>>;
+
+// 在一条连接上并发发起多个请求，按 id 把响应交还给对应的调用者
+pub struct Client<Req, Resp> {
+    next_id: AtomicU32,
+    // None 表示连接已经断开，之后的请求直接失败
+    pending: Pending,
+    // 已经编码好的帧；超长的请求在 call 里就被拒掉，不会进到写任务
+    outgoing: mpsc::Sender<Vec<u8>>,
+    codec: FrameCodec,
+    _types: PhantomData<fn(Req) -> Resp>,
+}
+
+impl<Req: Wire, Resp: Wire> Client<Req, Resp> {
+    pub fn new<S>(stream: S, codec: FrameCodec) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (mut reader, mut writer) = tokio::io::split(stream);
+        let (outgoing, mut rx) = mpsc::channel::<Vec<u8>>(64);
+        let pending: Pending = Arc::new(Mutex::new(Some(HashMap::new())));
+
+        // 写任务：所有请求排队经过同一个 writer，避免帧交错；写失败时和读任务一样让所有等待者失败
+        let failed = Arc::clone(&pending);
+        tokio::spawn(async move {
+            while let Some(buf) = rx.recv().await {
+                let written = async {
+                    writer.write_all(&buf).await?;
+                    writer.flush().await
+                };
+                if let Err(e) = written.await {
+                    fail_pending(&failed, FrameError::Io(e));
+                    break;
+                }
+            }
+        });
+
+        // 读任务：按 id 分发响应；连接断开时让所有还在等待的请求失败
+        let dispatch = Arc::clone(&pending);
+        tokio::spawn(async move {
+            let reason = loop {
+                match codec.read_frame(&mut reader).await {
+                    Ok(Some(frame)) => {
+                        let waiter = dispatch
+                            .lock()
+                            .unwrap()
+                            .as_mut()
+                            .and_then(|map| map.remove(&frame.id));
+                        let Some(waiter) = waiter else { continue };
+                        let result = match frame.kind {
+                            MessageKind::Response => Ok(frame.payload),
+                            MessageKind::Error => Err(FrameError::Remote(
+                                String::from_utf8_lossy(&frame.payload).into_owned(),
+                            )),
+                            MessageKind::Request => Err(FrameError::Decode(
+                                "server sent a request frame".to_string(),
+                            )),
+                        };
+                        let _ = waiter.send(result);
+                    }
+                    Ok(None) => break FrameError::ConnectionClosed,
+                    Err(e) => break e,
+                }
+            };
+            fail_pending(&dispatch, reason);
+        });
+
+        Client {
+            next_id: AtomicU32::new(1),
+            pending,
+            outgoing,
+            codec,
+            _types: PhantomData,
+        }
+    }
+
+    pub async fn call(&self, request: Req) -> Result<Resp, FrameError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        // 先编码：请求太大只让这一次调用失败，连接和其他调用不受影响
+        let frame = Frame {
+            kind: MessageKind::Request,
+            id,
+            payload: request.to_bytes(),
+        };
+        let mut buf = Vec::new();
+        self.codec.encode(&frame, &mut buf)?;
+
+        let (tx, rx) = oneshot::channel();
+        match self.pending.lock().unwrap().as_mut() {
+            Some(map) => map.insert(id, tx),
+            None => return Err(FrameError::ConnectionClosed),
+        };
+        // 无论正常返回、出错还是 future 被丢掉（比如超时取消），都把登记撤掉
+        let _entry = PendingEntry {
+            pending: &self.pending,
+            id,
+        };
+
+        if self.outgoing.send(buf).await.is_err() {
+            return Err(FrameError::ConnectionClosed);
+        }
+
+        let payload = rx.await.map_err(|_| FrameError::ConnectionClosed)??;
+        Resp::from_bytes(&payload)
+    }
+}
+
+// 连接不能再用了：本地的解码 / IO 错误原样交给每个等待者，不要当成对端返回的错误
+fn fail_pending(pending: &Pending, reason: FrameError) {
+    let drained = pending.lock().unwrap().take();
+    for (_, waiter) in drained.into_iter().flatten() {
+        let _ = waiter.send(Err(reason.duplicate()));
+    }
+}
+
+struct PendingEntry<'a> {
+    pending: &'a Pending,
+    id: u32,
+}
+
+impl Drop for PendingEntry<'_> {
+    fn drop(&mut self) {
+        // 响应到达时读任务已经删掉了，这里删不到也没关系
+        if let Some(map) = self.pending.lock().unwrap().as_mut() {
+            map.remove(&self.id);
+        }
+    }
+}
+
+// 服务一条连接：每个请求一个任务，所以慢请求不会挡住后面的快请求，响应可能乱序返回
+pub async fn serve_connection<S, Req, Resp, H, Fut>(
+    stream: S,
+    codec: FrameCodec,
+    handler: H,
+) -> Result<(), FrameError>
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+    Req: Wire + Send + 'static,
+    Resp: Wire + Send + 'static,
+    H: Fn(Req) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Resp> + Send + 'static,
+{
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let (tx, mut rx) = mpsc::channel::<Vec<u8>>(64);
+    let writer_task = tokio::spawn(async move {
+        while let Some(buf) = rx.recv().await {
+            writer.write_all(&buf).await?;
+            writer.flush().await?;
+        }
+        Ok::<_, FrameError>(())
+    });
+
+    let handler = Arc::new(handler);
+    let result = loop {
+        let read = tokio::select! {
+            read = codec.read_frame(&mut reader) => read,
+            // 写任务出错退出后，再读请求也回不了，直接结束，错误由下面的 writer_task 带出去
+            () = tx.closed() => break Ok(()),
+        };
+        let frame = match read {
+            Ok(Some(frame)) => frame,
+            Ok(None) => break Ok(()),
+            Err(e) => break Err(e),
+        };
+        let tx = tx.clone();
+        let handler = Arc::clone(&handler);
+        tokio::spawn(async move {
+            let reply = match Req::from_bytes(&frame.payload) {
+                Ok(req) if frame.kind == MessageKind::Request => Frame {
+                    kind: MessageKind::Response,
+                    id: frame.id,
+                    payload: handler(req).await.to_bytes(),
+                },
+                Ok(_) => Frame {
+                    kind: MessageKind::Error,
+                    id: frame.id,
+                    payload: b"expected a request frame".to_vec(),
+                },
+                Err(e) => Frame {
+                    kind: MessageKind::Error,
+                    id: frame.id,
+                    payload: e.to_string().into_bytes(),
+                },
+            };
+            let _ = tx.send(encode_reply(&codec, reply)).await;
+        });
+    };
+
+    drop(tx);
+    let written = writer_task.await.expect("writer task panicked");
+    result.and(written)
+}
+
+// 响应超过 max_frame_size 时改回一个 Error 帧，只让这一个请求失败，不拖垮整条连接
+fn encode_reply(codec: &FrameCodec, reply: Frame) -> Vec<u8> {
+    let mut buf = Vec::new();
+    let Err(e) = codec.encode(&reply, &mut buf) else {
+        return buf;
+    };
+    let mut payload = e.to_string().into_bytes();
+    // max_frame_size 小到连错误描述都放不下时只能截断
+    payload.truncate(codec.max_frame_size.saturating_sub(HEADER_LEN));
+    let error = Frame {
+        kind: MessageKind::Error,
+        id: reply.id,
+        payload,
+    };
+    buf.clear();
+    codec
+        .encode(&error, &mut buf)
+        .expect("truncated error frame fits");
+    buf
+}
+
+// tokio_async_io_tcp_socket.rs 的分帧版本：每个连接一个任务
+pub async fn serve<Req, Resp, H, Fut>(
+    listener: TcpListener,
+    codec: FrameCodec,
+    handler: H,
+) -> io::Result<()>
+where
+    Req: Wire + Send + 'static,
+    Resp: Wire + Send + 'static,
+    H: Fn(Req) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Resp> + Send + 'static,
+{
+    loop {
+        let (socket, _) = listener.accept().await?;
+        let handler = handler.clone();
+        tokio::spawn(async move {
+            let _ = serve_connection(socket, codec, handler).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::net::TcpStream;
+
+    fn frame(kind: MessageKind, id: u32, payload: &[u8]) -> Frame {
+        Frame {
+            kind,
+            id,
+            payload: payload.to_vec(),
+        }
+    }
+
+    #[test]
+    fn encode_layout() {
+        let mut buf = Vec::new();
+        FrameCodec::default()
+            .encode(&frame(MessageKind::Response, 0x0102_0304, b"hi"), &mut buf)
+            .unwrap();
+        assert_eq!(buf, [0, 0, 0, 7, 1, 1, 2, 3, 4, b'h', b'i']);
+    }
+
+    #[test]
+    fn decode_waits_for_complete_frame() {
+        let codec = FrameCodec::default();
+        let mut wire = Vec::new();
+        codec
+            .encode(&frame(MessageKind::Request, 1, b"first"), &mut wire)
+            .unwrap();
+        codec
+            .encode(&frame(MessageKind::Request, 2, b"second"), &mut wire)
+            .unwrap();
+
+        // 一个字节一个字节地喂，模拟 TCP 任意拆包
+        let mut buf = Vec::new();
+        let mut out = Vec::new();
+        for byte in wire {
+            buf.push(byte);
+            if let Some(f) = codec.decode(&mut buf).unwrap() {
+                out.push(f);
+            }
+        }
+        assert!(buf.is_empty());
+        assert_eq!(
+            out,
+            [
+                frame(MessageKind::Request, 1, b"first"),
+                frame(MessageKind::Request, 2, b"second")
+            ]
+        );
+    }
+
+    #[test]
+    fn max_frame_size_enforced() {
+        let codec = FrameCodec::new(16);
+        let mut buf = Vec::new();
+        let err = codec
+            .encode(&frame(MessageKind::Request, 1, &[0; 12]), &mut buf)
+            .unwrap_err();
+        assert!(matches!(err, FrameError::TooLarge { len: 17, max: 16 }));
+        assert!(buf.is_empty());
+
+        let mut incoming = vec![0, 0, 1, 0];
+        assert!(matches!(
+            codec.decode(&mut incoming),
+            Err(FrameError::TooLarge { len: 256, max: 16 })
+        ));
+
+        // 不管 max_frame_size 多大，都不能超过 len 字段的 u32
+        let huge = FrameCodec::new(usize::MAX);
+        assert!(matches!(
+            huge.check_len(u32::MAX as usize + 1),
+            Err(FrameError::TooLarge { max, .. }) if max == u32::MAX as usize
+        ));
+
+        let mut incoming = vec![0, 0, 0, 2, 0, 0];
+        assert!(matches!(
+            codec.decode(&mut incoming),
+            Err(FrameError::Malformed(2))
+        ));
+    }
+
+    #[test]
+    fn unknown_kind_rejected() {
+        let mut incoming = vec![0, 0, 0, 5, 9, 0, 0, 0, 1];
+        assert!(matches!(
+            FrameCodec::default().decode(&mut incoming),
+            Err(FrameError::UnknownKind(9))
+        ));
+    }
+
+    // 处理时间与数值成反比，越早发出的请求越晚完成，响应一定乱序到达
+    async fn slow_echo(n: u64) -> u64 {
+        tokio::time::sleep(Duration::from_millis(50 - n * 5)).await;
+        n * 10
+    }
+
+    #[tokio::test]
+    async fn multiplexed_calls_over_duplex() {
+        let (client_io, server_io) = tokio::io::duplex(64);
+        let codec = FrameCodec::default();
+        tokio::spawn(serve_connection(server_io, codec, slow_echo));
+
+        let client = Client::<u64, u64>::new(client_io, codec);
+        let calls = (0..8).map(|n| client.call(n));
+        let results = futures::future::join_all(calls).await;
+        let results: Vec<u64> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(results, [0, 10, 20, 30, 40, 50, 60, 70]);
+    }
+
+    #[tokio::test]
+    async fn large_message_over_loopback() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let codec = FrameCodec::default();
+        tokio::spawn(serve(listener, codec, |s: String| async move {
+            s.to_uppercase()
+        }));
+
+        let client = Client::<String, String>::new(TcpStream::connect(addr).await.unwrap(), codec);
+        // 远大于 echo 服务器的 1024 字节缓冲区
+        let big = "abc".repeat(100_000);
+        let (a, b) = tokio::join!(client.call(big.clone()), client.call("small".to_string()));
+        assert_eq!(a.unwrap(), big.to_uppercase());
+        assert_eq!(b.unwrap(), "SMALL");
+    }
+
+    #[tokio::test]
+    async fn decode_failure_returns_error_frame() {
+        let (client_io, server_io) = tokio::io::duplex(64);
+        let codec = FrameCodec::default();
+        tokio::spawn(serve_connection(
+            server_io,
+            codec,
+            |n: u64| async move { n },
+        ));
+
+        // 用 String 客户端给 u64 服务器发 3 个字节
+        let client = Client::<String, u64>::new(client_io, codec);
+        let err = client.call("abc".to_string()).await.unwrap_err();
+        assert!(matches!(err, FrameError::Remote(ref m) if m.contains("expected 8 bytes")));
+    }
+
+    #[tokio::test]
+    async fn oversized_frame_closes_server_connection() {
+        let (mut client_io, server_io) = tokio::io::duplex(64);
+        let server = tokio::spawn(serve_connection(
+            server_io,
+            FrameCodec::new(32),
+            |v: Vec<u8>| async move { v },
+        ));
+
+        client_io.write_all(&[0, 0x10, 0, 0]).await.unwrap();
+        let result = server.await.unwrap();
+        assert!(matches!(
+            result,
+            Err(FrameError::TooLarge {
+                len: 0x10_0000,
+                max: 32
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn oversized_call_fails_only_that_call() {
+        let (client_io, server_io) = tokio::io::duplex(64);
+        let codec = FrameCodec::new(32);
+        tokio::spawn(serve_connection(
+            server_io,
+            codec,
+            |v: Vec<u8>| async move { v },
+        ));
+
+        let client = Client::<Vec<u8>, Vec<u8>>::new(client_io, codec);
+        let calls = async {
+            let err = client.call(vec![0; 100]).await.unwrap_err();
+            assert!(matches!(err, FrameError::TooLarge { len: 105, max: 32 }));
+            // 连接还能继续用
+            assert_eq!(client.call(vec![1, 2, 3]).await.unwrap(), [1, 2, 3]);
+        };
+        tokio::time::timeout(Duration::from_secs(5), calls)
+            .await
+            .expect("oversized call hung");
+    }
+
+    #[tokio::test]
+    async fn oversized_response_becomes_error_frame() {
+        let (client_io, server_io) = tokio::io::duplex(64);
+        let server_codec = FrameCodec::new(64);
+        tokio::spawn(serve_connection(
+            server_io,
+            server_codec,
+            |n: u64| async move { vec![0u8; n as usize] },
+        ));
+
+        let client = Client::<u64, Vec<u8>>::new(client_io, FrameCodec::default());
+        let calls = async {
+            let err = client.call(100).await.unwrap_err();
+            assert!(matches!(err, FrameError::Remote(ref m) if m.contains("exceeds maximum")));
+            assert_eq!(client.call(3).await.unwrap(), [0, 0, 0]);
+        };
+        tokio::time::timeout(Duration::from_secs(5), calls)
+            .await
+            .expect("oversized response hung");
+    }
+
+    #[tokio::test]
+    async fn pending_calls_fail_when_server_goes_away() {
+        let (client_io, server_io) = tokio::io::duplex(64);
+        let client = Client::<String, String>::new(client_io, FrameCodec::default());
+        let call = client.call("hello".to_string());
+        let drop_server = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            drop(server_io);
+        };
+        let (result, ()) = tokio::join!(call, drop_server);
+        // 连接断开是本地看到的错误，不是对端发来的
+        assert!(matches!(result, Err(FrameError::ConnectionClosed)));
+        assert!(matches!(
+            client.call("again".to_string()).await,
+            Err(FrameError::ConnectionClosed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn cancelled_call_is_unregistered() {
+        let (client_io, server_io) = tokio::io::duplex(64);
+        // 服务器一直不回
+        tokio::spawn(serve_connection(
+            server_io,
+            FrameCodec::default(),
+            |n: u64| async move {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                n
+            },
+        ));
+        let client = Client::<u64, u64>::new(client_io, FrameCodec::default());
+        let pending = || client.pending.lock().unwrap().as_ref().unwrap().len();
+
+        let timed_out = tokio::time::timeout(Duration::from_millis(20), client.call(1)).await;
+        assert!(timed_out.is_err());
+        assert_eq!(pending(), 0);
+    }
+}
@@ -0,0 +1,765 @@
+// 手写的引用计数智能指针，对应 smart_pointers.md 里的 Rc / Weak
+//
+// 堆上的布局和 std 一样：
+//
+//   RcBox { strong, weak, value }
+//
+// - strong 是强引用个数，归零时 drop value
+// - weak 是弱引用个数，所有强引用合起来额外算 1 个弱引用，归零时释放整块内存
+//
+// debug 构建下每个分配都会登记到当前线程的注册表里，`leak_report()` 可以列出
+// 还活着的分配，并找出只被彼此引用、外部已经拿不到的环（典型的 Rc<RefCell<…>> 泄漏）。
+// release 构建下注册表是空操作。
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+
+// repr(C) 保证 strong/weak 在最前面，注册表不需要知道 T 就能读出计数
+#[repr(C)]
+struct RcBox<T> {
+    strong: Cell<usize>,
+    weak: Cell<usize>,
+    value: ManuallyDrop<T>,
+}
+
+// 计数加一。和 std 一样溢出时直接 abort：用 mem::forget 反复 clone 就能把计数绕回 0，
+// 之后一次 drop 就会释放还在用的内存；panic 也不行，unwind 途中可能还会再 clone
+fn increment(count: &Cell<usize>) {
+    match count.get().checked_add(1) {
+        Some(n) => count.set(n),
+        None => std::process::abort(),
+    }
+}
+
+pub struct Rc<T> {
+    ptr: NonNull<RcBox<T>>,
+    // 告诉 drop check 我们逻辑上拥有一个 RcBox<T>
+    _marker: PhantomData<RcBox<T>>,
+}
+
+pub struct Weak<T> {
+    ptr: NonNull<RcBox<T>>,
+    _marker: PhantomData<RcBox<T>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AllocId(usize);
+
+impl<T> Rc<T> {
+    fn allocate(value: T) -> NonNull<RcBox<T>> {
+        let boxed = Box::new(RcBox {
+            strong: Cell::new(1),
+            weak: Cell::new(1),
+            value: ManuallyDrop::new(value),
+        });
+        // SAFETY: Box::into_raw 不会返回空指针
+        unsafe { NonNull::new_unchecked(Box::into_raw(boxed)) }
+    }
+
+    fn from_inner(ptr: NonNull<RcBox<T>>) -> Self {
+        Rc {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    // 不参与环检测；需要环检测时用 `new_traced`
+    pub fn new(value: T) -> Self {
+        let ptr = Self::allocate(value);
+        registry::register::<T>(ptr.as_ptr() as usize, None);
+        Self::from_inner(ptr)
+    }
+
+    fn inner(&self) -> &RcBox<T> {
+        // SAFETY: 只要还有一个 Rc，RcBox 就一定有效
+        unsafe { self.ptr.as_ref() }
+    }
+
+    pub fn id(this: &Self) -> AllocId {
+        AllocId(this.ptr.as_ptr() as usize)
+    }
+
+    pub fn strong_count(this: &Self) -> usize {
+        this.inner().strong.get()
+    }
+
+    // 与 std 一致，不包括强引用共同持有的那 1 个
+    pub fn weak_count(this: &Self) -> usize {
+        this.inner().weak.get() - 1
+    }
+
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.ptr == other.ptr
+    }
+
+    pub fn downgrade(this: &Self) -> Weak<T> {
+        increment(&this.inner().weak);
+        Weak {
+            ptr: this.ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    // 只有唯一的强引用、且没有弱引用时才能拿到 &mut T
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if Rc::strong_count(this) == 1 && Rc::weak_count(this) == 0 {
+            // SAFETY: 没有别的 Rc / Weak 能看到这个值
+            Some(unsafe { &mut (*this.ptr.as_ptr()).value })
+        } else {
+            None
+        }
+    }
+
+    // 唯一的强引用时把值取出来，否则原样还回去
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        if Rc::strong_count(&this) != 1 {
+            return Err(this);
+        }
+        let this = ManuallyDrop::new(this);
+        let inner = this.inner();
+        // SAFETY: strong 马上置 0，之后不会再有人读 value
+        let value = unsafe { ptr::read(&*inner.value) };
+        inner.strong.set(0);
+        registry::unregister(this.ptr.as_ptr() as usize);
+        // 去掉强引用共同持有的那个弱引用，可能需要释放内存
+        unsafe { release_weak(this.ptr) };
+        Ok(value)
+    }
+}
+
+impl<T: Clone> Rc<T> {
+    // clone-on-write：被共享时先复制一份再改；只剩弱引用共享时把值搬走，
+    // 旧分配留给那些弱引用（它们之后 upgrade 会失败）
+    pub fn make_mut(this: &mut Self) -> &mut T {
+        if Rc::strong_count(this) != 1 {
+            *this = Rc::new_like(this, (**this).clone());
+        } else if Rc::weak_count(this) != 0 {
+            let traced = registry::tracer_of(this.ptr.as_ptr() as usize);
+            let old = this.ptr;
+            // SAFETY: 唯一的强引用，值可以搬走；旧分配的 strong 置 0，
+            // 相当于对它执行了一次不 drop value 的强引用释放
+            let value = unsafe { ptr::read(&*old.as_ref().value) };
+            unsafe { old.as_ref() }.strong.set(0);
+            registry::unregister(old.as_ptr() as usize);
+            let new = Self::allocate(value);
+            registry::register::<T>(new.as_ptr() as usize, traced);
+            this.ptr = new;
+            unsafe { release_weak(old) };
+        }
+        // SAFETY: 走到这里一定是唯一的强引用且没有弱引用
+        unsafe { &mut (*this.ptr.as_ptr()).value }
+    }
+
+    fn new_like(this: &Self, value: T) -> Self {
+        let ptr = Self::allocate(value);
+        let traced = registry::tracer_of(this.ptr.as_ptr() as usize);
+        registry::register::<T>(ptr.as_ptr() as usize, traced);
+        Self::from_inner(ptr)
+    }
+}
+
+impl<T: Trace> Rc<T> {
+    // 登记时带上 T 的 trace 函数，环检测才能看到这个分配指向谁
+    pub fn new_traced(value: T) -> Self {
+        let ptr = Self::allocate(value);
+        registry::register::<T>(ptr.as_ptr() as usize, Some(trace_erased::<T>));
+        Self::from_inner(ptr)
+    }
+}
+
+// 释放一个弱引用，归零时释放内存
+unsafe fn release_weak<T>(ptr: NonNull<RcBox<T>>) {
+    let weak = &ptr.as_ref().weak;
+    weak.set(weak.get() - 1);
+    if weak.get() == 0 {
+        // value 是 ManuallyDrop，这里只释放内存
+        drop(Box::from_raw(ptr.as_ptr()));
+    }
+}
+
+impl<T> Clone for Rc<T> {
+    fn clone(&self) -> Self {
+        increment(&self.inner().strong);
+        Self::from_inner(self.ptr)
+    }
+}
+
+impl<T> Drop for Rc<T> {
+    fn drop(&mut self) {
+        let strong = &self.inner().strong;
+        strong.set(strong.get() - 1);
+        if strong.get() == 0 {
+            registry::unregister(self.ptr.as_ptr() as usize);
+            // SAFETY: 最后一个强引用，value 只会在这里 drop 一次。
+            // drop value 可能会连带 drop 指向自己的 Weak，所以先 drop 再 release
+            unsafe {
+                ManuallyDrop::drop(&mut (*self.ptr.as_ptr()).value);
+                release_weak(self.ptr);
+            }
+        }
+    }
+}
+
+impl<T> Deref for Rc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner().value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Rc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Rc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T> Weak<T> {
+    fn inner(&self) -> &RcBox<T> {
+        // SAFETY: 只要还有一个 Weak，RcBox 的内存就不会被释放（value 可能已 drop）
+        unsafe { self.ptr.as_ref() }
+    }
+
+    pub fn upgrade(&self) -> Option<Rc<T>> {
+        let strong = &self.inner().strong;
+        if strong.get() == 0 {
+            return None;
+        }
+        increment(strong);
+        Some(Rc::from_inner(self.ptr))
+    }
+
+    pub fn strong_count(&self) -> usize {
+        self.inner().strong.get()
+    }
+
+    pub fn weak_count(&self) -> usize {
+        let inner = self.inner();
+        if inner.strong.get() == 0 {
+            inner.weak.get()
+        } else {
+            inner.weak.get() - 1
+        }
+    }
+}
+
+impl<T> Clone for Weak<T> {
+    fn clone(&self) -> Self {
+        increment(&self.inner().weak);
+        Weak {
+            ptr: self.ptr,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for Weak<T> {
+    fn drop(&mut self) {
+        unsafe { release_weak(self.ptr) };
+    }
+}
+
+impl<T> fmt::Debug for Weak<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(Weak)")
+    }
+}
+
+// 让环检测能看到一个值里持有的强引用。弱引用不算边，因为它不会让对方活着
+pub trait Trace {
+    fn trace(&self, visit: &mut dyn FnMut(AllocId));
+}
+
+impl<T> Trace for Rc<T> {
+    fn trace(&self, visit: &mut dyn FnMut(AllocId)) {
+        visit(Rc::id(self));
+    }
+}
+
+impl<T> Trace for Weak<T> {
+    fn trace(&self, _visit: &mut dyn FnMut(AllocId)) {}
+}
+
+impl<T: Trace> Trace for RefCell<T> {
+    fn trace(&self, visit: &mut dyn FnMut(AllocId)) {
+        // 正在被可变借用时看不到内部，只能跳过
+        if let Ok(inner) = self.try_borrow() {
+            inner.trace(visit);
+        }
+    }
+}
+
+impl<T: Trace> Trace for Option<T> {
+    fn trace(&self, visit: &mut dyn FnMut(AllocId)) {
+        if let Some(inner) = self {
+            inner.trace(visit);
+        }
+    }
+}
+
+impl<T: Trace> Trace for Vec<T> {
+    fn trace(&self, visit: &mut dyn FnMut(AllocId)) {
+        for item in self {
+            item.trace(visit);
+        }
+    }
+}
+
+impl<T: Trace> Trace for Box<T> {
+    fn trace(&self, visit: &mut dyn FnMut(AllocId)) {
+        (**self).trace(visit);
+    }
+}
+
+macro_rules! trace_leaf {
+    ($($t:ty),*) => {
+        $(impl Trace for $t {
+            fn trace(&self, _visit: &mut dyn FnMut(AllocId)) {}
+        })*
+    };
+}
+
+trace_leaf!(
+    bool,
+    char,
+    i8,
+    i16,
+    i32,
+    i64,
+    u8,
+    u16,
+    u32,
+    u64,
+    usize,
+    isize,
+    f32,
+    f64,
+    String,
+    &'static str
+);
+
+type TraceFn = unsafe fn(usize, &mut dyn FnMut(AllocId));
+
+unsafe fn trace_erased<T: Trace>(addr: usize, visit: &mut dyn FnMut(AllocId)) {
+    let inner = &*(addr as *const RcBox<T>);
+    inner.value.trace(visit);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllocInfo {
+    pub id: AllocId,
+    pub type_name: &'static str,
+    pub strong: usize,
+    pub weak: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LeakReport {
+    pub live: Vec<AllocInfo>,
+    // 每一组是一个强连通分量：组内互相强引用，且外部已经没有强引用能到达它们。
+    // 组内按地址排序，不是引用的先后顺序
+    pub cycles: Vec<Vec<AllocId>>,
+}
+
+impl LeakReport {
+    pub fn is_clean(&self) -> bool {
+        self.live.is_empty()
+    }
+}
+
+impl fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} live Rc allocation(s)", self.live.len())?;
+        for info in &self.live {
+            writeln!(
+                f,
+                "  {:#x} {} (strong = {}, weak = {})",
+                info.id.0, info.type_name, info.strong, info.weak
+            )?;
+        }
+        for (i, cycle) in self.cycles.iter().enumerate() {
+            // 打印成集合：地址顺序不是真实的引用路径
+            let ids: Vec<String> = cycle.iter().map(|id| format!("{:#x}", id.0)).collect();
+            writeln!(f, "  cycle #{}: {{{}}}", i + 1, ids.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+// 当前线程上还活着的分配和泄漏的环
+pub fn leak_report() -> LeakReport {
+    registry::report()
+}
+
+// 放在 main 的第一行：main 结束时 drop，有泄漏就打印到 stderr
+pub struct LeakCheck {
+    _not_send: PhantomData<*const ()>,
+}
+
+impl LeakCheck {
+    pub fn new() -> Self {
+        LeakCheck {
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Default for LeakCheck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for LeakCheck {
+    fn drop(&mut self) {
+        let report = leak_report();
+        if !report.is_clean() {
+            eprint!("{report}");
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+mod registry {
+    use super::{AllocId, AllocInfo, LeakReport, TraceFn};
+    use std::cell::{Cell, RefCell};
+    use std::collections::{BTreeMap, BTreeSet};
+
+    struct Entry {
+        type_name: &'static str,
+        trace: Option<TraceFn>,
+    }
+
+    thread_local! {
+        static LIVE: RefCell<BTreeMap<usize, Entry>> = const { RefCell::new(BTreeMap::new()) };
+    }
+
+    pub(super) fn register<T>(addr: usize, trace: Option<TraceFn>) {
+        let entry = Entry {
+            type_name: std::any::type_name::<T>(),
+            trace,
+        };
+        // 线程退出阶段 thread_local 可能已经销毁，这时直接放弃登记
+        let _ = LIVE.try_with(|live| live.borrow_mut().insert(addr, entry));
+    }
+
+    pub(super) fn unregister(addr: usize) {
+        let _ = LIVE.try_with(|live| live.borrow_mut().remove(&addr));
+    }
+
+    pub(super) fn tracer_of(addr: usize) -> Option<TraceFn> {
+        LIVE.try_with(|live| live.borrow().get(&addr).and_then(|e| e.trace))
+            .ok()
+            .flatten()
+    }
+
+    // SAFETY: 登记在册的地址都指向 strong > 0 的 RcBox，头部是 repr(C) 的两个 Cell<usize>
+    unsafe fn counts(addr: usize) -> (usize, usize) {
+        let header = addr as *const Cell<usize>;
+        ((*header).get(), (*header.add(1)).get() - 1)
+    }
+
+    pub(super) fn report() -> LeakReport {
+        // 先把边收集出来再释放借用，trace 过程中不能再碰注册表
+        let nodes: Vec<(usize, &'static str, Option<TraceFn>)> = LIVE.with(|live| {
+            live.borrow()
+                .iter()
+                .map(|(&addr, e)| (addr, e.type_name, e.trace))
+                .collect()
+        });
+
+        let mut live = Vec::new();
+        let mut edges: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        let mut internal: BTreeMap<usize, usize> = BTreeMap::new();
+        let known: BTreeSet<usize> = nodes.iter().map(|n| n.0).collect();
+        for &(addr, type_name, trace) in &nodes {
+            let (strong, weak) = unsafe { counts(addr) };
+            live.push(AllocInfo {
+                id: AllocId(addr),
+                type_name,
+                strong,
+                weak,
+            });
+            let mut out = Vec::new();
+            if let Some(trace) = trace {
+                unsafe { trace(addr, &mut |AllocId(to)| out.push(to)) };
+            }
+            out.retain(|to| known.contains(to));
+            for &to in &out {
+                *internal.entry(to).or_default() += 1;
+            }
+            edges.insert(addr, out);
+        }
+
+        // 强引用数多于"被其他分配持有的次数"，说明栈上或别处还有人拿着它：这是根
+        let mut reachable: BTreeSet<usize> = live
+            .iter()
+            .filter(|info| info.strong > internal.get(&info.id.0).copied().unwrap_or(0))
+            .map(|info| info.id.0)
+            .collect();
+        let mut stack: Vec<usize> = reachable.iter().copied().collect();
+        while let Some(addr) = stack.pop() {
+            for &to in &edges[&addr] {
+                if reachable.insert(to) {
+                    stack.push(to);
+                }
+            }
+        }
+
+        // 剩下的都是泄漏；在它们之间找强连通分量，大小 > 1 或有自环的就是环
+        let leaked: BTreeSet<usize> = known.difference(&reachable).copied().collect();
+        let reach_within = |from: usize| {
+            let mut seen = BTreeSet::new();
+            let mut stack = vec![from];
+            while let Some(addr) = stack.pop() {
+                for &to in &edges[&addr] {
+                    if leaked.contains(&to) && seen.insert(to) {
+                        stack.push(to);
+                    }
+                }
+            }
+            seen
+        };
+        let reach: BTreeMap<usize, BTreeSet<usize>> =
+            leaked.iter().map(|&a| (a, reach_within(a))).collect();
+        let mut assigned = BTreeSet::new();
+        let mut cycles = Vec::new();
+        for &a in &leaked {
+            if assigned.contains(&a) || !reach[&a].contains(&a) {
+                continue;
+            }
+            let component: Vec<AllocId> = reach[&a]
+                .iter()
+                .filter(|&&b| reach[&b].contains(&a))
+                .map(|&b| {
+                    assigned.insert(b);
+                    AllocId(b)
+                })
+                .collect();
+            cycles.push(component);
+        }
+
+        LeakReport { live, cycles }
+    }
+}
+
+#[cfg(not(debug_assertions))]
+mod registry {
+    use super::{LeakReport, TraceFn};
+
+    pub(super) fn register<T>(_addr: usize, _trace: Option<TraceFn>) {}
+
+    pub(super) fn unregister(_addr: usize) {}
+
+    pub(super) fn tracer_of(_addr: usize) -> Option<TraceFn> {
+        None
+    }
+
+    pub(super) fn report() -> LeakReport {
+        LeakReport::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // drop 时往共享日志里记一笔，用来确认 drop 恰好发生一次
+    struct Noisy(&'static str, std::rc::Rc<RefCell<Vec<&'static str>>>);
+
+    impl Drop for Noisy {
+        fn drop(&mut self) {
+            self.1.borrow_mut().push(self.0);
+        }
+    }
+
+    #[test]
+    fn counts_like_smart_pointers_md() {
+        let a = Rc::new(String::from("hello"));
+        assert_eq!(Rc::strong_count(&a), 1);
+        let b = Rc::clone(&a);
+        {
+            let _c = Rc::clone(&b);
+            assert_eq!(Rc::strong_count(&a), 3);
+        }
+        assert_eq!(Rc::strong_count(&a), 2);
+        assert!(Rc::ptr_eq(&a, &b));
+        assert_eq!(*b, "hello");
+
+        let w = Rc::downgrade(&a);
+        assert_eq!(Rc::weak_count(&a), 1);
+        assert_eq!(w.strong_count(), 2);
+    }
+
+    #[test]
+    fn upgrade_fails_after_last_strong_drop() {
+        let log = std::rc::Rc::new(RefCell::new(Vec::new()));
+        let a = Rc::new(Noisy("a", log.clone()));
+        let w = Rc::downgrade(&a);
+        let w2 = w.clone();
+        assert!(w.upgrade().is_some());
+
+        drop(a);
+        assert_eq!(*log.borrow(), ["a"]);
+        assert!(w.upgrade().is_none());
+        assert_eq!(w2.strong_count(), 0);
+        assert_eq!(w2.weak_count(), 2);
+    }
+
+    #[test]
+    fn make_mut_clones_when_shared() {
+        let mut a = Rc::new(vec![1, 2, 3]);
+        let b = Rc::clone(&a);
+        Rc::make_mut(&mut a).push(4);
+        assert_eq!(*a, [1, 2, 3, 4]);
+        assert_eq!(*b, [1, 2, 3]);
+        assert!(!Rc::ptr_eq(&a, &b));
+
+        // 已经唯一，原地修改
+        let before = Rc::id(&a);
+        Rc::make_mut(&mut a).push(5);
+        assert_eq!(Rc::id(&a), before);
+    }
+
+    #[test]
+    fn make_mut_detaches_weak_refs() {
+        let mut a = Rc::new(1);
+        let w = Rc::downgrade(&a);
+        *Rc::make_mut(&mut a) += 1;
+        assert_eq!(*a, 2);
+        assert!(w.upgrade().is_none());
+        assert_eq!(Rc::weak_count(&a), 0);
+    }
+
+    #[test]
+    fn try_unwrap_and_get_mut() {
+        let a = Rc::new(String::from("x"));
+        let b = Rc::clone(&a);
+        let a = Rc::try_unwrap(a).unwrap_err();
+        drop(b);
+        let w = Rc::downgrade(&a);
+        assert_eq!(Rc::try_unwrap(a).unwrap(), "x");
+        assert!(w.upgrade().is_none());
+
+        let mut c = Rc::new(5);
+        *Rc::get_mut(&mut c).unwrap() += 1;
+        let _d = Rc::clone(&c);
+        assert!(Rc::get_mut(&mut c).is_none());
+    }
+
+    #[test]
+    fn value_dropped_exactly_once() {
+        let log = std::rc::Rc::new(RefCell::new(Vec::new()));
+        let a = Rc::new(Noisy("once", log.clone()));
+        let clones: Vec<_> = (0..5).map(|_| a.clone()).collect();
+        let weak = Rc::downgrade(&a);
+        drop(a);
+        drop(clones);
+        drop(weak);
+        assert_eq!(*log.borrow(), ["once"]);
+    }
+
+    struct Node {
+        name: &'static str,
+        next: RefCell<Option<Rc<Node>>>,
+        parent: RefCell<Option<Weak<Node>>>,
+    }
+
+    impl Trace for Node {
+        fn trace(&self, visit: &mut dyn FnMut(AllocId)) {
+            self.next.trace(visit);
+            self.parent.trace(visit);
+        }
+    }
+
+    fn node(name: &'static str) -> Rc<Node> {
+        Rc::new_traced(Node {
+            name,
+            next: RefCell::new(None),
+            parent: RefCell::new(None),
+        })
+    }
+
+    // 每个测试跑在自己的线程里，注册表是线程局部的，互不干扰
+    #[test]
+    fn registry_lists_live_allocations() {
+        assert!(leak_report().is_clean());
+        let a = Rc::new(7u8);
+        let _b = Rc::clone(&a);
+        let report = leak_report();
+        assert_eq!(report.live.len(), 1);
+        assert_eq!(report.live[0].type_name, "u8");
+        assert_eq!(report.live[0].strong, 2);
+        assert!(report.cycles.is_empty());
+        drop(a);
+        drop(_b);
+        assert!(leak_report().is_clean());
+    }
+
+    #[test]
+    fn detects_leaked_cycle() {
+        let a = node("a");
+        let b = node("b");
+        let c = node("c");
+        *a.next.borrow_mut() = Some(b.clone());
+        *b.next.borrow_mut() = Some(c.clone());
+        *c.next.borrow_mut() = Some(a.clone());
+        let (ida, idb, idc) = (Rc::id(&a), Rc::id(&b), Rc::id(&c));
+
+        // 栈上还拿着 a，环能被到达，不算泄漏
+        drop(b);
+        drop(c);
+        assert!(leak_report().cycles.is_empty());
+
+        drop(a);
+        let report = leak_report();
+        assert_eq!(report.live.len(), 3);
+        assert_eq!(report.cycles.len(), 1);
+        let mut cycle = report.cycles[0].clone();
+        cycle.sort();
+        let mut expected = vec![ida, idb, idc];
+        expected.sort();
+        assert_eq!(cycle, expected);
+        let text = report.to_string();
+        assert!(text.contains("cycle #1: {0x"));
+        assert!(!text.contains(" -> "));
+    }
+
+    #[test]
+    fn weak_back_edge_does_not_leak() {
+        let parent = node("parent");
+        let child = node("child");
+        *parent.next.borrow_mut() = Some(child.clone());
+        *child.parent.borrow_mut() = Some(Rc::downgrade(&parent));
+        drop(child);
+
+        let name = parent
+            .next
+            .borrow()
+            .as_ref()
+            .map(|c| c.parent.borrow().as_ref().unwrap().upgrade().unwrap().name);
+        assert_eq!(name, Some("parent"));
+
+        drop(parent);
+        assert!(leak_report().is_clean());
+    }
+
+    #[test]
+    fn self_loop_is_a_cycle() {
+        let a = node("self");
+        *a.next.borrow_mut() = Some(a.clone());
+        let id = Rc::id(&a);
+        drop(a);
+        assert_eq!(leak_report().cycles, vec![vec![id]]);
+    }
+}
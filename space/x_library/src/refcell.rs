@@ -0,0 +1,419 @@
+// 会记录借用位置的 RefCell，接口与 std::cell::RefCell 对齐
+//
+// std 的 RefCell 借用冲突时只会 panic "already borrowed"，不会告诉你之前那个借用是在哪里拿的。
+// 这里 borrow / borrow_mut 都标了 #[track_caller]，每个活着的借用都记下调用位置，
+// 冲突时同时报告新借用和所有仍未归还的借用的位置。
+
+use std::cell::{Cell, UnsafeCell};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::panic::Location;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorrowKind {
+    Shared,
+    Mutable,
+}
+
+impl fmt::Display for BorrowKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BorrowKind::Shared => write!(f, "shared"),
+            BorrowKind::Mutable => write!(f, "mutable"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BorrowSite {
+    pub kind: BorrowKind,
+    pub location: &'static Location<'static>,
+}
+
+impl fmt::Display for BorrowSite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} borrow at {}", self.kind, self.location)
+    }
+}
+
+// 借用冲突：requested 是这次失败的借用，outstanding 是挡住它的那些借用
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BorrowConflict {
+    pub requested: BorrowSite,
+    pub outstanding: Vec<BorrowSite>,
+}
+
+impl fmt::Display for BorrowConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot take {}: already borrowed (", self.requested)?;
+        for (i, site) in self.outstanding.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "outstanding {site}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+// 与 std::cell::BorrowError / BorrowMutError 对应的两种错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BorrowError(pub BorrowConflict);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BorrowMutError(pub BorrowConflict);
+
+impl fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for BorrowMutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for BorrowError {}
+impl std::error::Error for BorrowMutError {}
+
+pub struct RefCell<T: ?Sized> {
+    // 正数：共享借用个数；-1：有一个可变借用；0：空闲
+    state: Cell<isize>,
+    // 活着的借用，(借用编号, 位置)；只在方法内部短暂借用，不会跨调用持有
+    sites: std::cell::RefCell<Vec<(u64, BorrowSite)>>,
+    next_id: Cell<u64>,
+    value: UnsafeCell<T>,
+}
+
+impl<T> RefCell<T> {
+    pub fn new(value: T) -> Self {
+        RefCell {
+            state: Cell::new(0),
+            sites: std::cell::RefCell::new(Vec::new()),
+            next_id: Cell::new(0),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    #[track_caller]
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace(&mut *self.borrow_mut(), value)
+    }
+
+    #[track_caller]
+    pub fn take(&self) -> T
+    where
+        T: Default,
+    {
+        self.replace(T::default())
+    }
+}
+
+impl<T: ?Sized> RefCell<T> {
+    fn push_site(&self, site: BorrowSite) -> u64 {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.sites.borrow_mut().push((id, site));
+        id
+    }
+
+    fn remove_site(&self, id: u64) {
+        self.sites.borrow_mut().retain(|(i, _)| *i != id);
+    }
+
+    // 按借用的先后顺序
+    pub fn outstanding_borrows(&self) -> Vec<BorrowSite> {
+        self.sites.borrow().iter().map(|(_, site)| *site).collect()
+    }
+
+    fn conflict(&self, kind: BorrowKind, location: &'static Location<'static>) -> BorrowConflict {
+        BorrowConflict {
+            requested: BorrowSite { kind, location },
+            outstanding: self.outstanding_borrows(),
+        }
+    }
+
+    #[track_caller]
+    pub fn try_borrow(&self) -> Result<Ref<'_, T>, BorrowError> {
+        let location = Location::caller();
+        let state = self.state.get();
+        if state < 0 {
+            return Err(BorrowError(self.conflict(BorrowKind::Shared, location)));
+        }
+        self.state.set(state + 1);
+        let site = BorrowSite {
+            kind: BorrowKind::Shared,
+            location,
+        };
+        let id = self.push_site(site);
+        Ok(Ref {
+            cell: self,
+            id,
+            site,
+        })
+    }
+
+    #[track_caller]
+    pub fn try_borrow_mut(&self) -> Result<RefMut<'_, T>, BorrowMutError> {
+        let location = Location::caller();
+        if self.state.get() != 0 {
+            return Err(BorrowMutError(self.conflict(BorrowKind::Mutable, location)));
+        }
+        self.state.set(-1);
+        let site = BorrowSite {
+            kind: BorrowKind::Mutable,
+            location,
+        };
+        let id = self.push_site(site);
+        Ok(RefMut {
+            cell: self,
+            id,
+            site,
+        })
+    }
+
+    #[track_caller]
+    pub fn borrow(&self) -> Ref<'_, T> {
+        match self.try_borrow() {
+            Ok(r) => r,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    #[track_caller]
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        match self.try_borrow_mut() {
+            Ok(r) => r,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    // 有 &mut self 就不可能有别的借用，不需要检查
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+impl<T: Default> Default for RefCell<T> {
+    fn default() -> Self {
+        RefCell::new(T::default())
+    }
+}
+
+impl<T: Clone> Clone for RefCell<T> {
+    #[track_caller]
+    fn clone(&self) -> Self {
+        RefCell::new(self.borrow().clone())
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RefCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.try_borrow() {
+            Ok(value) => f.debug_struct("RefCell").field("value", &&*value).finish(),
+            Err(_) => f
+                .debug_struct("RefCell")
+                .field("value", &format_args!("<borrowed>"))
+                .finish(),
+        }
+    }
+}
+
+pub struct Ref<'a, T: ?Sized> {
+    cell: &'a RefCell<T>,
+    id: u64,
+    site: BorrowSite,
+}
+
+impl<T: ?Sized> Ref<'_, T> {
+    pub fn site(this: &Self) -> BorrowSite {
+        this.site
+    }
+}
+
+impl<T: ?Sized> Deref for Ref<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: state > 0 期间不会有可变借用
+        unsafe { &*self.cell.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for Ref<'_, T> {
+    fn drop(&mut self) {
+        self.cell.state.set(self.cell.state.get() - 1);
+        self.cell.remove_site(self.id);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Ref<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+pub struct RefMut<'a, T: ?Sized> {
+    cell: &'a RefCell<T>,
+    id: u64,
+    site: BorrowSite,
+}
+
+impl<T: ?Sized> RefMut<'_, T> {
+    pub fn site(this: &Self) -> BorrowSite {
+        this.site
+    }
+}
+
+impl<T: ?Sized> Deref for RefMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: state == -1 期间只有这一个借用
+        unsafe { &*self.cell.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RefMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.cell.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RefMut<'_, T> {
+    fn drop(&mut self) {
+        self.cell.state.set(0);
+        self.cell.remove_site(self.id);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RefMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_usage_as_refcell_vs_cell_md() {
+        let v = RefCell::new(vec![1, 2, 3]);
+        {
+            let mut v_mut = v.borrow_mut();
+            v_mut.push(4);
+        }
+        assert_eq!(*v.borrow(), [1, 2, 3, 4]);
+        assert_eq!(v.replace(vec![]), [1, 2, 3, 4]);
+        assert!(v.take().is_empty());
+    }
+
+    #[test]
+    fn shared_borrows_coexist() {
+        let c = RefCell::new(5);
+        let a = c.borrow();
+        let b = c.borrow();
+        assert_eq!(*a + *b, 10);
+        assert_eq!(c.outstanding_borrows().len(), 2);
+        drop(a);
+        drop(b);
+        assert!(c.outstanding_borrows().is_empty());
+        *c.borrow_mut() += 1;
+        assert_eq!(c.into_inner(), 6);
+    }
+
+    #[test]
+    fn try_borrow_reports_outstanding_mutable_site() {
+        let c = RefCell::new(String::new());
+        let held = c.borrow_mut();
+        let held_line = line!() - 1;
+
+        let err = c.try_borrow().unwrap_err();
+        let err_line = line!() - 1;
+
+        let conflict = &err.0;
+        assert_eq!(conflict.requested.kind, BorrowKind::Shared);
+        assert_eq!(conflict.requested.location.line(), err_line);
+        assert_eq!(conflict.requested.location.file(), file!());
+        assert_eq!(conflict.outstanding.len(), 1);
+        assert_eq!(conflict.outstanding[0].kind, BorrowKind::Mutable);
+        assert_eq!(conflict.outstanding[0].location.line(), held_line);
+        assert_eq!(RefMut::site(&held), conflict.outstanding[0]);
+    }
+
+    #[test]
+    fn try_borrow_mut_reports_every_shared_site() {
+        let c = RefCell::new(0);
+        let first = c.borrow();
+        let second = c.borrow();
+        let lines = [line!() - 2, line!() - 1];
+
+        let err = c.try_borrow_mut().unwrap_err();
+        let seen: Vec<u32> = err
+            .0
+            .outstanding
+            .iter()
+            .map(|s| s.location.line())
+            .collect();
+        assert_eq!(seen, lines);
+        assert_eq!(err.0.requested.kind, BorrowKind::Mutable);
+
+        drop(first);
+        let err = c.try_borrow_mut().unwrap_err();
+        assert_eq!(err.0.outstanding, [Ref::site(&second)]);
+
+        drop(second);
+        assert!(c.try_borrow_mut().is_ok());
+    }
+
+    #[test]
+    fn panic_message_names_both_sites() {
+        let c = RefCell::new(());
+        let _held = c.borrow_mut();
+        let held_at = format!("{}:{}", file!(), line!() - 1);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _again = c.borrow_mut();
+        }));
+        let again_at = format!("{}:{}", file!(), line!() - 2);
+
+        let payload = result.unwrap_err();
+        let message = payload.downcast_ref::<String>().unwrap();
+        assert!(
+            message.contains(&format!("cannot take mutable borrow at {again_at}")),
+            "{message}"
+        );
+        assert!(
+            message.contains(&format!("outstanding mutable borrow at {held_at}")),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn track_caller_propagates_through_helpers() {
+        #[track_caller]
+        fn read_len(c: &RefCell<Vec<u8>>) -> Result<usize, BorrowError> {
+            c.try_borrow().map(|v| v.len())
+        }
+
+        let c = RefCell::new(vec![1]);
+        let _w = c.borrow_mut();
+        let err = read_len(&c).unwrap_err();
+        assert_eq!(err.0.requested.location.line(), line!() - 1);
+    }
+
+    #[test]
+    fn debug_does_not_panic_while_mutably_borrowed() {
+        let c = RefCell::new(1);
+        assert_eq!(format!("{c:?}"), "RefCell { value: 1 }");
+        let _w = c.borrow_mut();
+        assert_eq!(format!("{c:?}"), "RefCell { value: <borrowed> }");
+    }
+}
@@ -0,0 +1,1121 @@
+// collection-vector.md 里 `Vec` 的手写版本：容量、扩容和 from_iter 从内部实现一遍
+//
+// - `RawVec<T>`：只管一块堆内存的分配、扩容和释放，不知道里面有几个元素是初始化过的
+// - `Growth`：扩容倍数，用分数表示，默认和 Vec 一样翻倍
+// - `SmallVec<T, N>`：前 N 个元素直接放在值里，不分配；放不下时才搬到 RawVec 上
+//
+// 会调用用户代码的地方（元素的 Drop、retain 的闭包、迭代器、clone）都先把 len 设成安全的值，
+// 或者用局部守卫在展开时修好状态：panic 时最多少 drop 几个元素（泄漏），不会重复 drop，
+// 也不会读到未初始化的内存。
+
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::mem::{self, ManuallyDrop, MaybeUninit};
+use std::ops::{Bound, Deref, DerefMut, RangeBounds};
+use std::ptr::{self, NonNull};
+use std::slice;
+
+// 第一次上堆时至少分配这么多个，避免 1、2、3 这样一个个地长
+const MIN_HEAP_CAPACITY: usize = 4;
+
+pub struct RawVec<T> {
+    ptr: NonNull<T>,
+    cap: usize,
+    _owns: PhantomData<T>,
+}
+
+// 和 Box<[T]> 一样，只在 T 满足时才能跨线程
+unsafe impl<T: Send> Send for RawVec<T> {}
+unsafe impl<T: Sync> Sync for RawVec<T> {}
+
+impl<T> RawVec<T> {
+    const IS_ZST: bool = mem::size_of::<T>() == 0;
+
+    // 零大小类型不需要内存，容量视为无限
+    pub const fn new() -> Self {
+        RawVec {
+            ptr: NonNull::dangling(),
+            cap: if Self::IS_ZST { usize::MAX } else { 0 },
+            _owns: PhantomData,
+        }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        let mut raw = RawVec::new();
+        raw.resize(cap);
+        raw
+    }
+
+    pub fn ptr(&self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    // 重新分配到正好 new_cap 个元素；缩小时调用方负责先处理掉放不下的元素
+    pub fn resize(&mut self, new_cap: usize) {
+        if Self::IS_ZST || new_cap == self.cap {
+            return;
+        }
+        if new_cap == 0 {
+            // SAFETY: cap > 0 时 ptr 是用 current_layout 分配的
+            unsafe { alloc::dealloc(self.ptr.as_ptr().cast(), self.current_layout()) };
+            self.ptr = NonNull::dangling();
+            self.cap = 0;
+            return;
+        }
+        let layout = Layout::array::<T>(new_cap).expect("capacity overflow");
+        // SAFETY: layout 大小非零；realloc 的旧指针和旧 layout 与分配时一致
+        let ptr = unsafe {
+            if self.cap == 0 {
+                alloc::alloc(layout)
+            } else {
+                alloc::realloc(
+                    self.ptr.as_ptr().cast(),
+                    self.current_layout(),
+                    layout.size(),
+                )
+            }
+        };
+        self.ptr = NonNull::new(ptr.cast()).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        self.cap = new_cap;
+    }
+
+    fn current_layout(&self) -> Layout {
+        // 分配成功过的容量一定合法
+        Layout::array::<T>(self.cap).unwrap()
+    }
+}
+
+impl<T> Default for RawVec<T> {
+    fn default() -> Self {
+        RawVec::new()
+    }
+}
+
+impl<T> Drop for RawVec<T> {
+    // 只释放内存，元素由持有者负责 drop
+    fn drop(&mut self) {
+        self.resize(0);
+    }
+}
+
+// 扩容倍数 num / den，必须大于 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Growth {
+    num: usize,
+    den: usize,
+}
+
+impl Growth {
+    // Vec 的策略
+    pub const DOUBLE: Growth = Growth::new(2, 1);
+    // 更省内存，扩容次数多一些
+    pub const ONE_AND_HALF: Growth = Growth::new(3, 2);
+
+    pub const fn new(num: usize, den: usize) -> Self {
+        assert!(den > 0 && num > den, "growth factor must be greater than 1");
+        Growth { num, den }
+    }
+
+    pub fn factor(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    // 当前容量 cap 放不下 required 个时，下一次分配的容量
+    pub fn next_capacity(&self, cap: usize, required: usize) -> usize {
+        let grown = cap
+            .checked_mul(self.num)
+            .map_or(usize::MAX, |n| n / self.den);
+        // 3/2 这类倍数在容量很小时可能长不动
+        grown.max(cap + 1).max(required).max(MIN_HEAP_CAPACITY)
+    }
+}
+
+impl Default for Growth {
+    fn default() -> Self {
+        Growth::DOUBLE
+    }
+}
+
+enum Data<T, const N: usize> {
+    Inline([MaybeUninit<T>; N]),
+    Heap(RawVec<T>),
+}
+
+pub struct SmallVec<T, const N: usize> {
+    // [0, len) 已初始化
+    len: usize,
+    data: Data<T, N>,
+    growth: Growth,
+}
+
+impl<T, const N: usize> SmallVec<T, N> {
+    pub const fn new() -> Self {
+        SmallVec {
+            len: 0,
+            data: Data::Inline([const { MaybeUninit::uninit() }; N]),
+            growth: Growth::DOUBLE,
+        }
+    }
+
+    // cap 超过 N 时直接在堆上分配
+    pub fn with_capacity(cap: usize) -> Self {
+        let mut vec = SmallVec::new();
+        vec.reserve_exact(cap);
+        vec
+    }
+
+    pub fn growth_factor(mut self, growth: Growth) -> Self {
+        self.growth = growth;
+        self
+    }
+
+    pub fn growth(&self) -> Growth {
+        self.growth
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        match &self.data {
+            Data::Inline(_) if RawVec::<T>::IS_ZST => usize::MAX,
+            Data::Inline(_) => N,
+            Data::Heap(raw) => raw.capacity(),
+        }
+    }
+
+    pub fn is_inline(&self) -> bool {
+        matches!(self.data, Data::Inline(_))
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+
+    // 不经过切片拿指针，len 为 0（处理中）时也能用
+    fn as_ptr(&self) -> *const T {
+        match &self.data {
+            Data::Inline(buf) => buf.as_ptr().cast(),
+            Data::Heap(raw) => raw.ptr(),
+        }
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        match &mut self.data {
+            Data::Inline(buf) => buf.as_mut_ptr().cast(),
+            Data::Heap(raw) => raw.ptr(),
+        }
+    }
+
+    // 容量不够时按倍数扩容
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len.checked_add(additional).expect("capacity overflow");
+        if required > self.capacity() {
+            self.grow_to(self.growth.next_capacity(self.capacity(), required));
+        }
+    }
+
+    // 容量不够时正好扩到需要的大小
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let required = self.len.checked_add(additional).expect("capacity overflow");
+        if required > self.capacity() {
+            self.grow_to(required);
+        }
+    }
+
+    fn grow_to(&mut self, new_cap: usize) {
+        match &mut self.data {
+            Data::Heap(raw) => raw.resize(new_cap),
+            Data::Inline(buf) => {
+                let raw = RawVec::with_capacity(new_cap);
+                // SAFETY: 新内存至少能放下 len 个，两块内存不重叠；搬走之后内联的副本不再被读
+                unsafe { ptr::copy_nonoverlapping(buf.as_ptr().cast(), raw.ptr(), self.len) };
+                self.data = Data::Heap(raw);
+            }
+        }
+    }
+
+    // 放得回 N 个时搬回内联，否则把堆内存缩到正好 len 个
+    pub fn shrink_to_fit(&mut self) {
+        let len = self.len;
+        let Data::Heap(raw) = &mut self.data else {
+            return;
+        };
+        if len <= N {
+            let mut buf = [const { MaybeUninit::uninit() }; N];
+            // SAFETY: 同 grow_to，方向相反；旧的 RawVec 只释放内存
+            unsafe { ptr::copy_nonoverlapping(raw.ptr(), buf.as_mut_ptr().cast(), len) };
+            self.data = Data::Inline(buf);
+        } else {
+            raw.resize(len);
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        if self.len == self.capacity() {
+            self.reserve(1);
+        }
+        // SAFETY: len < capacity
+        unsafe { self.as_mut_ptr().add(self.len).write(value) };
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // SAFETY: 原来的最后一个元素已初始化，len 减掉之后不会再被读到
+        Some(unsafe { self.as_ptr().add(self.len).read() })
+    }
+
+    // index 及之后的元素整体右移一格
+    pub fn insert(&mut self, index: usize, value: T) {
+        let len = self.len;
+        assert!(
+            index <= len,
+            "insertion index (is {index}) should be <= len (is {len})"
+        );
+        self.reserve(1);
+        // SAFETY: 扩容之后 len + 1 <= capacity；ptr::copy 允许重叠
+        unsafe {
+            let at = self.as_mut_ptr().add(index);
+            ptr::copy(at, at.add(1), len - index);
+            at.write(value);
+        }
+        self.len = len + 1;
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len;
+        assert!(
+            index < len,
+            "removal index (is {index}) should be < len (is {len})"
+        );
+        // SAFETY: index 处已初始化，读出来之后用后面的元素盖掉
+        unsafe {
+            let at = self.as_mut_ptr().add(index);
+            let value = at.read();
+            ptr::copy(at.add(1), at, len - index - 1);
+            self.len = len - 1;
+            value
+        }
+    }
+
+    // 用最后一个元素补位，O(1)，不保持顺序
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let len = self.len;
+        assert!(
+            index < len,
+            "swap_remove index (is {index}) should be < len (is {len})"
+        );
+        self.swap(index, len - 1);
+        self.pop().unwrap()
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let tail = self.len - len;
+        // 先改 len：某个元素的 drop panic 时剩下的也会被 drop_in_place 处理完，不会再 drop 第二次
+        self.len = len;
+        // SAFETY: [len, 原 len) 已初始化，现在已经不属于 self
+        unsafe {
+            let tail = ptr::slice_from_raw_parts_mut(self.as_mut_ptr().add(len), tail);
+            ptr::drop_in_place(tail);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    // 移除 range 内的元素，按顺序返回；迭代器 drop 时把没取走的丢掉，并把后面的元素接上
+    pub fn drain(&mut self, range: impl RangeBounds<usize>) -> Drain<'_, T, N> {
+        let len = self.len;
+        let start = match range.start_bound() {
+            Bound::Included(&i) => i,
+            Bound::Excluded(&i) => i.checked_add(1).expect("range start overflow"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&i) => i.checked_add(1).expect("range end overflow"),
+            Bound::Excluded(&i) => i,
+            Bound::Unbounded => len,
+        };
+        assert!(
+            start <= end,
+            "slice index starts at {start} but ends at {end}"
+        );
+        assert!(
+            end <= len,
+            "range end index {end} out of range for length {len}"
+        );
+
+        // 迭代期间 vec 只认前 start 个；Drain 被 mem::forget 时后面的元素只是泄漏
+        self.len = start;
+        Drain {
+            vec: self,
+            next: start,
+            end,
+            tail_start: end,
+            tail_len: len - end,
+        }
+    }
+
+    // 只保留 keep 返回 true 的元素，顺序不变
+    pub fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) {
+        self.retain_mut(|x| keep(x));
+    }
+
+    pub fn retain_mut(&mut self, mut keep: impl FnMut(&mut T) -> bool) {
+        // 处理到一半时 [0, processed - deleted) 是保留下来的，[processed, len) 还没处理，
+        // 中间是空洞。闭包或 drop panic 时，守卫把没处理的部分挪过来填上空洞
+        struct Guard<'a, T, const N: usize> {
+            vec: &'a mut SmallVec<T, N>,
+            len: usize,
+            processed: usize,
+            deleted: usize,
+        }
+
+        impl<T, const N: usize> Drop for Guard<'_, T, N> {
+            fn drop(&mut self) {
+                if self.deleted > 0 {
+                    let base = self.vec.as_mut_ptr();
+                    // SAFETY: 两段都在 [0, len) 里，ptr::copy 允许重叠
+                    unsafe {
+                        ptr::copy(
+                            base.add(self.processed),
+                            base.add(self.processed - self.deleted),
+                            self.len - self.processed,
+                        );
+                    }
+                }
+                self.vec.len = self.len - self.deleted;
+            }
+        }
+
+        let len = self.len;
+        // 处理期间 len 为 0，就算守卫没机会运行（比如被 forget）也只是泄漏
+        self.len = 0;
+        let mut guard = Guard {
+            vec: self,
+            len,
+            processed: 0,
+            deleted: 0,
+        };
+        // 指针在守卫建好之后再取，循环里不再经过 guard.vec，指针一直有效
+        let base = guard.vec.as_mut_ptr();
+
+        while guard.processed < len {
+            // SAFETY: processed < len，元素已初始化，且没有别的引用指向它
+            let cur = unsafe { &mut *base.add(guard.processed) };
+            if !keep(cur) {
+                // 先记下来再 drop：drop panic 时这个元素已经算是删掉了
+                guard.processed += 1;
+                guard.deleted += 1;
+                // SAFETY: 之后不会再读这个位置，除非被后面的元素覆盖
+                unsafe { ptr::drop_in_place(cur) };
+                continue;
+            }
+            if guard.deleted > 0 {
+                // SAFETY: 目标位置的元素已经 drop 或者搬走了
+                unsafe {
+                    ptr::copy_nonoverlapping(cur, base.add(guard.processed - guard.deleted), 1)
+                };
+            }
+            guard.processed += 1;
+        }
+    }
+
+    // 放在堆上的元素直接变成 Vec；内联的会分配一次
+    pub fn into_vec(self) -> Vec<T> {
+        let mut this = ManuallyDrop::new(self);
+        let len = this.len;
+        match mem::replace(&mut this.data, Data::Heap(RawVec::new())) {
+            Data::Heap(raw) if !RawVec::<T>::IS_ZST => {
+                let raw = ManuallyDrop::new(raw);
+                // SAFETY: 内存由全局分配器按 Layout::array::<T>(cap) 分配，和 Vec 的约定相同
+                unsafe { Vec::from_raw_parts(raw.ptr(), len, raw.capacity()) }
+            }
+            data => {
+                let mut out = Vec::with_capacity(len);
+                let src = match &data {
+                    Data::Inline(buf) => buf.as_ptr().cast(),
+                    Data::Heap(raw) => raw.ptr().cast_const(),
+                };
+                // SAFETY: 元素按位搬进 Vec，data 里剩下的只有内存（或者什么都没有）会被释放
+                unsafe {
+                    ptr::copy_nonoverlapping(src, out.as_mut_ptr(), len);
+                    out.set_len(len);
+                }
+                out
+            }
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for SmallVec<T, N> {
+    fn drop(&mut self) {
+        // 元素 drop panic 时 data 字段照样会被 drop，堆内存不会泄漏
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Default for SmallVec<T, N> {
+    fn default() -> Self {
+        SmallVec::new()
+    }
+}
+
+impl<T, const N: usize> Deref for SmallVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // SAFETY: [0, len) 已初始化
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+}
+
+impl<T, const N: usize> DerefMut for SmallVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // SAFETY: 同 deref
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for SmallVec<T, N> {
+    // 逐个 push：某个 clone panic 时，已经 clone 出来的会随着半成品一起被 drop
+    fn clone(&self) -> Self {
+        let mut out = SmallVec::with_capacity(self.len).growth_factor(self.growth);
+        out.extend(self.iter().cloned());
+        out
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for SmallVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize, const M: usize> PartialEq<SmallVec<T, M>> for SmallVec<T, N> {
+    fn eq(&self, other: &SmallVec<T, M>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for SmallVec<T, N> {}
+
+impl<T: PartialEq, const N: usize> PartialEq<[T]> for SmallVec<T, N> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: PartialEq, const N: usize, const M: usize> PartialEq<[T; M]> for SmallVec<T, N> {
+    fn eq(&self, other: &[T; M]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq<Vec<T>> for SmallVec<T, N> {
+    fn eq(&self, other: &Vec<T>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T, const N: usize> Extend<T> for SmallVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a, const N: usize> Extend<&'a T> for SmallVec<T, N> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for SmallVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = SmallVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T, const N: usize, const M: usize> From<[T; M]> for SmallVec<T, N> {
+    fn from(items: [T; M]) -> Self {
+        items.into_iter().collect()
+    }
+}
+
+impl<T, const N: usize> From<SmallVec<T, N>> for Vec<T> {
+    fn from(vec: SmallVec<T, N>) -> Self {
+        vec.into_vec()
+    }
+}
+
+pub struct Drain<'a, T, const N: usize> {
+    vec: &'a mut SmallVec<T, N>,
+    // [next, end) 还没被取走；从 tail_start 开始的 tail_len 个元素最后要接到 vec.len 后面。
+    // next_back 会让 end 往前移，所以尾部的起点单独记
+    next: usize,
+    end: usize,
+    tail_start: usize,
+    tail_len: usize,
+}
+
+impl<T, const N: usize> Iterator for Drain<'_, T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+        self.next += 1;
+        // SAFETY: 每个位置只读出一次
+        Some(unsafe { self.vec.as_ptr().add(self.next - 1).read() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.end - self.next;
+        (n, Some(n))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for Drain<'_, T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+        self.end -= 1;
+        // SAFETY: 同 next
+        Some(unsafe { self.vec.as_ptr().add(self.end).read() })
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for Drain<'_, T, N> {}
+
+impl<T, const N: usize> FusedIterator for Drain<'_, T, N> {}
+
+impl<T, const N: usize> Drop for Drain<'_, T, N> {
+    fn drop(&mut self) {
+        // 剩下的元素 drop 时 panic，也要把尾部搬回来
+        struct MoveTail<'r, 'a, T, const N: usize>(&'r mut Drain<'a, T, N>);
+
+        impl<T, const N: usize> Drop for MoveTail<'_, '_, T, N> {
+            fn drop(&mut self) {
+                let drain = &mut *self.0;
+                let start = drain.vec.len;
+                // SAFETY: 尾部元素已初始化，搬到空洞处，ptr::copy 允许重叠
+                unsafe {
+                    let base = drain.vec.as_mut_ptr();
+                    ptr::copy(base.add(drain.tail_start), base.add(start), drain.tail_len);
+                }
+                drain.vec.len = start + drain.tail_len;
+            }
+        }
+
+        let guard = MoveTail(self);
+        let drain = &mut *guard.0;
+        let remaining = drain.end - drain.next;
+        // SAFETY: [next, end) 还没被读出，丢掉之后标记为取完
+        unsafe {
+            let rest =
+                ptr::slice_from_raw_parts_mut(drain.vec.as_mut_ptr().add(drain.next), remaining);
+            drain.next = drain.end;
+            ptr::drop_in_place(rest);
+        }
+    }
+}
+
+// 按值迭代：元素归迭代器所有，vec 的 len 设为 0，只负责释放内存
+pub struct IntoIter<T, const N: usize> {
+    vec: SmallVec<T, N>,
+    next: usize,
+    end: usize,
+}
+
+impl<T, const N: usize> IntoIter<T, N> {
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: [next, end) 还没被取走
+        unsafe { slice::from_raw_parts(self.vec.as_ptr().add(self.next), self.end - self.next) }
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+        self.next += 1;
+        // SAFETY: 每个位置只读出一次
+        Some(unsafe { self.vec.as_ptr().add(self.next - 1).read() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.end - self.next;
+        (n, Some(n))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+        self.end -= 1;
+        // SAFETY: 同 next
+        Some(unsafe { self.vec.as_ptr().add(self.end).read() })
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for IntoIter<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IntoIter").field(&self.as_slice()).finish()
+    }
+}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        let remaining = self.end - self.next;
+        // SAFETY: [next, end) 还没被取走；先标记为取完，drop panic 时也不会再碰它们
+        unsafe {
+            let rest =
+                ptr::slice_from_raw_parts_mut(self.vec.as_mut_ptr().add(self.next), remaining);
+            self.next = self.end;
+            ptr::drop_in_place(rest);
+        }
+    }
+}
+
+impl<T, const N: usize> IntoIterator for SmallVec<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(mut self) -> IntoIter<T, N> {
+        let end = mem::replace(&mut self.len, 0);
+        IntoIter {
+            vec: self,
+            next: 0,
+            end,
+        }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a SmallVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut SmallVec<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> slice::IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+// 这里的测试不碰 FFI、尺寸也小，改了 unsafe 代码之后要在 Miri 下跑一遍：
+//
+//   cargo +nightly miri test -p x_library smallvec
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::lcg;
+    use std::cell::Cell;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    // 记录 drop 次数；panic_on_drop 的元素 drop 时 panic（计数照样加一）
+    #[derive(Debug)]
+    struct Tracked {
+        id: u32,
+        drops: Rc<Cell<usize>>,
+        panic_on_drop: bool,
+    }
+
+    impl Tracked {
+        fn new(id: u32, drops: &Rc<Cell<usize>>) -> Self {
+            Tracked {
+                id,
+                drops: drops.clone(),
+                panic_on_drop: false,
+            }
+        }
+    }
+
+    impl Clone for Tracked {
+        // id 为 99 的元素 clone 时 panic
+        fn clone(&self) -> Self {
+            assert_ne!(self.id, 99, "clone failed");
+            Tracked::new(self.id, &self.drops)
+        }
+    }
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+            if self.panic_on_drop {
+                panic!("drop failed");
+            }
+        }
+    }
+
+    fn tracked<const N: usize>(
+        ids: impl IntoIterator<Item = u32>,
+    ) -> (SmallVec<Tracked, N>, Rc<Cell<usize>>) {
+        let drops = Rc::new(Cell::new(0));
+        let vec = ids.into_iter().map(|id| Tracked::new(id, &drops)).collect();
+        (vec, drops)
+    }
+
+    fn ids<const N: usize>(vec: &SmallVec<Tracked, N>) -> Vec<u32> {
+        vec.iter().map(|t| t.id).collect()
+    }
+
+    fn catch(f: impl FnOnce()) {
+        assert!(panic::catch_unwind(AssertUnwindSafe(f)).is_err());
+    }
+
+    #[test]
+    fn stays_inline_until_full() {
+        let mut vec: SmallVec<i32, 4> = SmallVec::new();
+        assert_eq!(vec.capacity(), 4);
+        for i in 0..4 {
+            vec.push(i);
+        }
+        assert!(vec.is_inline());
+        vec.push(4);
+        assert!(!vec.is_inline());
+        assert_eq!(vec.capacity(), 8);
+        assert_eq!(vec, [0, 1, 2, 3, 4]);
+
+        // 删到放得下之后 shrink_to_fit 搬回内联
+        vec.truncate(3);
+        vec.shrink_to_fit();
+        assert!(vec.is_inline());
+        assert_eq!(vec, [0, 1, 2]);
+        assert_eq!(vec.pop(), Some(2));
+        assert_eq!(vec.pop(), Some(1));
+        assert_eq!(vec.pop(), Some(0));
+        assert_eq!(vec.pop(), None);
+    }
+
+    #[test]
+    fn growth_factor_controls_capacity() {
+        let capacities = |growth: Growth| {
+            let mut vec: SmallVec<u8, 2> = SmallVec::new().growth_factor(growth);
+            let mut seen = vec![vec.capacity()];
+            for i in 0..40 {
+                vec.push(i);
+                if seen.last() != Some(&vec.capacity()) {
+                    seen.push(vec.capacity());
+                }
+            }
+            seen
+        };
+        assert_eq!(capacities(Growth::DOUBLE), [2, 4, 8, 16, 32, 64]);
+        assert_eq!(
+            capacities(Growth::ONE_AND_HALF),
+            [2, 4, 6, 9, 13, 19, 28, 42]
+        );
+        assert_eq!(capacities(Growth::new(4, 1)), [2, 8, 32, 128]);
+
+        // 批量 reserve 时至少要够用
+        assert_eq!(Growth::DOUBLE.next_capacity(8, 100), 100);
+        assert_eq!(Growth::ONE_AND_HALF.next_capacity(1, 2), 4);
+        assert_eq!(Growth::new(11, 10).next_capacity(5, 6), 6);
+        assert_eq!(Growth::ONE_AND_HALF.factor(), 1.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "growth factor must be greater than 1")]
+    fn growth_factor_must_grow() {
+        Growth::new(1, 1);
+    }
+
+    #[test]
+    fn with_capacity_goes_straight_to_heap() {
+        let vec: SmallVec<u64, 4> = SmallVec::with_capacity(3);
+        assert!(vec.is_inline());
+        let vec: SmallVec<u64, 4> = SmallVec::with_capacity(10);
+        assert!(!vec.is_inline());
+        assert_eq!(vec.capacity(), 10);
+
+        // N = 0 时总在堆上
+        let mut vec: SmallVec<String, 0> = SmallVec::new();
+        assert_eq!(vec.capacity(), 0);
+        vec.push("a".into());
+        assert!(!vec.is_inline());
+        assert_eq!(vec.capacity(), 4);
+    }
+
+    // 和 Vec 做同样的随机操作，结果应当完全一致
+    #[test]
+    fn matches_vec_under_random_ops() {
+        let mut seed = 7u64;
+        let mut rand = |n: usize| lcg(&mut seed) as usize % n;
+        let mut ours: SmallVec<String, 3> = SmallVec::new().growth_factor(Growth::ONE_AND_HALF);
+        let mut std = Vec::new();
+        for step in 0..400 {
+            match rand(7) {
+                0 | 1 => {
+                    ours.push(step.to_string());
+                    std.push(step.to_string());
+                }
+                2 => assert_eq!(ours.pop(), std.pop()),
+                3 => {
+                    let i = rand(std.len() + 1);
+                    ours.insert(i, step.to_string());
+                    std.insert(i, step.to_string());
+                }
+                4 if !std.is_empty() => {
+                    let i = rand(std.len());
+                    assert_eq!(ours.remove(i), std.remove(i));
+                }
+                5 if !std.is_empty() => {
+                    let i = rand(std.len());
+                    assert_eq!(ours.swap_remove(i), std.swap_remove(i));
+                }
+                6 => {
+                    let m = rand(3) + 2;
+                    ours.retain(|s| s.len() % m != 0);
+                    std.retain(|s| s.len() % m != 0);
+                }
+                _ => {}
+            }
+            assert_eq!(ours, std);
+            assert!(ours.len() <= ours.capacity());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index (is 3) should be <= len (is 2)")]
+    fn insert_out_of_bounds() {
+        let mut vec: SmallVec<i32, 2> = SmallVec::from([1, 2]);
+        vec.insert(3, 0);
+    }
+
+    #[test]
+    fn drain_removes_range_and_closes_gap() {
+        let mut vec: SmallVec<i32, 4> = (0..10).collect();
+        let drained: Vec<_> = vec.drain(2..5).collect();
+        assert_eq!(drained, [2, 3, 4]);
+        assert_eq!(vec, [0, 1, 5, 6, 7, 8, 9]);
+
+        // 两头各取一个，剩下的在 drop 时丢掉
+        let mut drain = vec.drain(1..=5);
+        assert_eq!(drain.next(), Some(1));
+        assert_eq!(drain.next_back(), Some(8));
+        assert_eq!(drain.len(), 3);
+        drop(drain);
+        assert_eq!(vec, [0, 9]);
+
+        vec.drain(..);
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn drain_drops_unconsumed_and_leaks_when_forgotten() {
+        let (mut vec, drops) = tracked::<2>(0..6);
+        let mut drain = vec.drain(1..4);
+        let first = drain.next().unwrap();
+        drop(drain);
+        assert_eq!(drops.get(), 2);
+        drop(first);
+        assert_eq!(drops.get(), 3);
+        assert_eq!(ids(&vec), [0, 4, 5]);
+
+        // forget 掉 Drain：尾部泄漏，但 vec 仍然可以安全使用
+        mem::forget(vec.drain(1..2));
+        assert_eq!(ids(&vec), [0]);
+        drop(vec);
+        assert_eq!(drops.get(), 4);
+
+        // 泄漏的 4、5 各自还拿着一份计数器的 Rc；手动还回去，Miri 的泄漏检查就不用关掉
+        assert_eq!(Rc::strong_count(&drops), 3);
+        for _ in 0..2 {
+            // SAFETY: 这两份计数属于已经泄漏、永远不会再 drop 的元素
+            unsafe { Rc::decrement_strong_count(Rc::as_ptr(&drops)) };
+        }
+    }
+
+    #[test]
+    fn drain_restores_tail_when_drop_panics() {
+        let (mut vec, drops) = tracked::<4>(0..6);
+        vec[2].panic_on_drop = true;
+        catch(|| drop(vec.drain(1..4)));
+        assert_eq!(drops.get(), 3);
+        assert_eq!(ids(&vec), [0, 4, 5]);
+        drop(vec);
+        assert_eq!(drops.get(), 6);
+    }
+
+    #[test]
+    fn retain_keeps_order_and_drops_each_once() {
+        let (mut vec, drops) = tracked::<3>(0..10);
+        vec.retain(|t| t.id % 3 != 0);
+        assert_eq!(ids(&vec), [1, 2, 4, 5, 7, 8]);
+        assert_eq!(drops.get(), 4);
+        vec.retain_mut(|t| {
+            t.id *= 10;
+            t.id < 50
+        });
+        assert_eq!(ids(&vec), [10, 20, 40]);
+        drop(vec);
+        assert_eq!(drops.get(), 10);
+    }
+
+    #[test]
+    fn retain_is_consistent_when_predicate_panics() {
+        let (mut vec, drops) = tracked::<2>(0..8);
+        catch(|| {
+            vec.retain(|t| {
+                assert_ne!(t.id, 5, "predicate failed");
+                t.id % 2 == 0
+            })
+        });
+        // 1 和 3 已经删掉，5 之后的原样保留
+        assert_eq!(ids(&vec), [0, 2, 4, 5, 6, 7]);
+        assert_eq!(drops.get(), 2);
+        drop(vec);
+        assert_eq!(drops.get(), 8);
+    }
+
+    #[test]
+    fn retain_is_consistent_when_drop_panics() {
+        let (mut vec, drops) = tracked::<2>(0..6);
+        vec[1].panic_on_drop = true;
+        catch(|| vec.retain(|t| t.id >= 4 || t.id == 0));
+        assert_eq!(ids(&vec), [0, 2, 3, 4, 5]);
+        drop(vec);
+        assert_eq!(drops.get(), 6);
+    }
+
+    #[test]
+    fn drop_panic_still_drops_the_rest() {
+        for inline in [true, false] {
+            let (mut vec, drops) = tracked::<8>(0..if inline { 5 } else { 20 });
+            assert_eq!(vec.is_inline(), inline);
+            vec[2].panic_on_drop = true;
+            let total = vec.len();
+            catch(move || drop(vec));
+            assert_eq!(drops.get(), total);
+        }
+
+        let (mut vec, drops) = tracked::<2>(0..6);
+        vec[4].panic_on_drop = true;
+        catch(|| vec.truncate(1));
+        assert_eq!(ids(&vec), [0]);
+        assert_eq!(drops.get(), 5);
+    }
+
+    #[test]
+    fn clone_panic_drops_partial_copy() {
+        let (mut vec, drops) = tracked::<2>(0..6);
+        let copy = vec.clone();
+        assert_eq!(ids(&copy), ids(&vec));
+        drop(copy);
+        assert_eq!(drops.get(), 6);
+
+        vec[3].id = 99;
+        catch(|| {
+            let _ = vec.clone();
+        });
+        // 前三个 clone 出来的已经被 drop
+        assert_eq!(drops.get(), 9);
+        drop(vec);
+        assert_eq!(drops.get(), 15);
+    }
+
+    #[test]
+    fn extend_panic_keeps_pushed_items() {
+        let drops = Rc::new(Cell::new(0));
+        let mut vec: SmallVec<Tracked, 2> = SmallVec::new();
+        catch(|| {
+            vec.extend((0..5).map(|id| {
+                assert_ne!(id, 3, "iterator failed");
+                Tracked::new(id, &drops)
+            }))
+        });
+        assert_eq!(ids(&vec), [0, 1, 2]);
+        drop(vec);
+        assert_eq!(drops.get(), 3);
+    }
+
+    #[test]
+    fn into_iter_drops_remaining() {
+        let (vec, drops) = tracked::<4>(0..7);
+        let mut iter = vec.into_iter();
+        assert_eq!(iter.next().map(|t| t.id), Some(0));
+        assert_eq!(iter.next_back().map(|t| t.id), Some(6));
+        assert_eq!(iter.as_slice().len(), 5);
+        assert_eq!(drops.get(), 2);
+        drop(iter);
+        assert_eq!(drops.get(), 7);
+
+        let vec: SmallVec<&str, 2> = SmallVec::from(["a", "b"]);
+        assert!(vec.into_iter().rev().eq(["b", "a"]));
+    }
+
+    #[test]
+    fn into_vec_reuses_heap_buffer() {
+        let vec: SmallVec<String, 2> = ["a", "b", "c"].map(String::from).into();
+        let ptr = vec.as_ptr();
+        let out = vec.into_vec();
+        assert_eq!(out.as_ptr(), ptr);
+        assert_eq!(out, ["a", "b", "c"]);
+
+        let vec: SmallVec<String, 4> = ["x".to_string()].into();
+        assert_eq!(Vec::from(vec), ["x"]);
+    }
+
+    #[test]
+    fn zero_sized_elements_never_allocate() {
+        let mut vec: SmallVec<(), 2> = SmallVec::new();
+        for _ in 0..100 {
+            vec.push(());
+        }
+        assert_eq!(vec.len(), 100);
+        assert_eq!(vec.capacity(), usize::MAX);
+        vec.insert(50, ());
+        vec.remove(0);
+        vec.retain(|_| false);
+        assert!(vec.is_empty());
+        assert!(vec.into_vec().is_empty());
+    }
+}
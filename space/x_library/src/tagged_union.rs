@@ -0,0 +1,235 @@
+// 给 union.md 里的裸 union 套一层安全外壳
+//
+// union 本身不记录哪个字段是活的，所以每次读都要 unsafe。`tagged_union!` 根据字段列表生成：
+//
+// - `#[repr(u32)]` 的标签枚举 `XxxTag`
+// - `#[repr(C)]` 的 union `XxxData`
+// - `#[repr(C)]` 的 `Xxx { tag, data }`，布局等价于 C 里的 `struct { uint32_t tag; union {...} data; }`
+// - 与之等价的 Rust 枚举，以及两者之间的 From 转换
+// - 每个字段一个返回 Option 的 getter 和一个会同时更新标签的 setter
+//
+// 所有 unsafe 都集中在宏生成的代码里：只有标签与字段一致时才会读 union。
+//
+// union 字段不能有 Drop，这里进一步要求字段都是 Copy（与 union.md 的说明一致）。
+
+#[doc(hidden)]
+pub use paste;
+
+#[macro_export]
+macro_rules! tagged_union {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident as $enum_name:ident {
+            $($variant:ident($field:ident: $ty:ty)),+ $(,)?
+        }
+    ) => {
+        $crate::tagged_union::paste::paste! {
+            #[repr(u32)]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            $vis enum [<$name Tag>] {
+                $($variant,)+
+            }
+
+            // 生成的 API 不一定全用得上，私有类型时不要报 dead_code
+            #[allow(dead_code)]
+            impl [<$name Tag>] {
+                // 从 C 那边传来的原始标签，越界返回 None
+                $vis fn from_raw(raw: u32) -> Option<Self> {
+                    [$([<$name Tag>]::$variant),+]
+                        .into_iter()
+                        .find(|tag| *tag as u32 == raw)
+                }
+            }
+
+            #[repr(C)]
+            #[derive(Clone, Copy)]
+            #[allow(non_snake_case)]
+            $vis union [<$name Data>] {
+                $(pub $field: $ty,)+
+            }
+
+            $(#[$meta])*
+            #[repr(C)]
+            #[derive(Clone, Copy)]
+            $vis struct $name {
+                tag: [<$name Tag>],
+                data: [<$name Data>],
+            }
+
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            $vis enum $enum_name {
+                $($variant($ty),)+
+            }
+
+            #[allow(dead_code)]
+            impl $name {
+                $vis fn tag(&self) -> [<$name Tag>] {
+                    self.tag
+                }
+
+                $(
+                    $vis fn $field(&self) -> Option<$ty> {
+                        match self.tag {
+                            // SAFETY: 标签说明当前活着的就是这个字段
+                            [<$name Tag>]::$variant => Some(unsafe { self.data.$field }),
+                            #[allow(unreachable_patterns)]
+                            _ => None,
+                        }
+                    }
+
+                    $vis fn [<set_ $field>](&mut self, value: $ty) {
+                        self.data = [<$name Data>] { $field: value };
+                        self.tag = [<$name Tag>]::$variant;
+                    }
+                )+
+
+                // 拆成可以直接交给 C 的两部分
+                $vis fn into_raw(self) -> (u32, [<$name Data>]) {
+                    (self.tag as u32, self.data)
+                }
+
+                /// # Safety
+                ///
+                /// `data` 中与 `tag` 对应的字段必须已经初始化为合法的值。
+                /// 标签本身会被检查，越界时返回 None。
+                $vis unsafe fn from_raw(tag: u32, data: [<$name Data>]) -> Option<Self> {
+                    [<$name Tag>]::from_raw(tag).map(|tag| $name { tag, data })
+                }
+            }
+
+            impl From<$enum_name> for $name {
+                fn from(value: $enum_name) -> Self {
+                    match value {
+                        $($enum_name::$variant(v) => $name {
+                            tag: [<$name Tag>]::$variant,
+                            data: [<$name Data>] { $field: v },
+                        },)+
+                    }
+                }
+            }
+
+            impl From<$name> for $enum_name {
+                fn from(value: $name) -> Self {
+                    match value.tag {
+                        // SAFETY: 同 getter，按标签读对应字段
+                        $([<$name Tag>]::$variant => $enum_name::$variant(unsafe { value.data.$field }),)+
+                    }
+                }
+            }
+
+            impl ::std::fmt::Debug for $name {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    ::std::fmt::Debug::fmt(&$enum_name::from(*self), f)
+                }
+            }
+
+            impl PartialEq for $name {
+                fn eq(&self, other: &Self) -> bool {
+                    $enum_name::from(*self) == $enum_name::from(*other)
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::{align_of, offset_of, size_of};
+
+    // union.md 里的 MyUnion { int_val: u32, float_val: f32 }
+    tagged_union! {
+        pub struct MyUnion as MyValue {
+            Int(int_val: u32),
+            Float(float_val: f32),
+        }
+    }
+
+    tagged_union! {
+        struct Wide as WideValue {
+            Byte(byte: u8),
+            Long(long: u64),
+            Pair(pair: [u16; 2]),
+        }
+    }
+
+    #[test]
+    fn getters_follow_the_tag() {
+        let mut u = MyUnion::from(MyValue::Int(42));
+        assert_eq!(u.tag(), MyUnionTag::Int);
+        assert_eq!(u.int_val(), Some(42));
+        assert_eq!(u.float_val(), None);
+
+        u.set_float_val(2.5);
+        assert_eq!(u.tag(), MyUnionTag::Float);
+        assert_eq!(u.int_val(), None);
+        assert_eq!(u.float_val(), Some(2.5));
+    }
+
+    #[test]
+    fn enum_round_trip() {
+        for v in [
+            WideValue::Byte(7),
+            WideValue::Long(u64::MAX),
+            WideValue::Pair([1, 2]),
+        ] {
+            let w = Wide::from(v);
+            assert_eq!(WideValue::from(w), v);
+        }
+        assert_eq!(
+            format!("{:?}", MyUnion::from(MyValue::Float(1.5))),
+            "Float(1.5)"
+        );
+        assert_eq!(
+            MyUnion::from(MyValue::Int(1)),
+            MyUnion::from(MyValue::Int(1))
+        );
+        assert_ne!(
+            MyUnion::from(MyValue::Int(1)),
+            MyUnion::from(MyValue::Float(1.0))
+        );
+    }
+
+    #[test]
+    fn c_compatible_layout() {
+        // struct { uint32_t tag; union { uint32_t; float; } data; }
+        assert_eq!(size_of::<MyUnionTag>(), 4);
+        assert_eq!(size_of::<MyUnionData>(), 4);
+        assert_eq!(size_of::<MyUnion>(), 8);
+        assert_eq!(offset_of!(MyUnion, data), 4);
+
+        // union 按最大、对齐最严格的字段来排布
+        assert_eq!(size_of::<WideData>(), 8);
+        assert_eq!(align_of::<Wide>(), 8);
+        assert_eq!(offset_of!(Wide, data), 8);
+        assert_eq!(size_of::<Wide>(), 16);
+    }
+
+    #[test]
+    fn raw_parts_for_ffi() {
+        let (tag, data) = MyUnion::from(MyValue::Float(2.0)).into_raw();
+        assert_eq!(tag, MyUnionTag::Float as u32);
+        // 同一块内存按 u32 解读就是 f32 的比特位，和 union.md 里 unsafe 读的效果一样
+        assert_eq!(unsafe { data.int_val }, 2.0f32.to_bits());
+
+        let back = unsafe { MyUnion::from_raw(tag, data) }.unwrap();
+        assert_eq!(back.float_val(), Some(2.0));
+        assert!(unsafe { MyUnion::from_raw(7, data) }.is_none());
+        assert_eq!(MyUnionTag::from_raw(1), Some(MyUnionTag::Float));
+    }
+
+    // 模拟 C 侧的函数：只认 repr(C) 布局，通过指针读写
+    extern "C" fn c_side_double(v: *mut MyUnion) {
+        let v = unsafe { &mut *v };
+        match MyValue::from(*v) {
+            MyValue::Int(i) => v.set_int_val(i * 2),
+            MyValue::Float(f) => v.set_float_val(f * 2.0),
+        }
+    }
+
+    #[test]
+    fn passes_through_extern_c() {
+        let mut v = MyUnion::from(MyValue::Int(21));
+        c_side_double(&mut v);
+        assert_eq!(v.int_val(), Some(42));
+    }
+}
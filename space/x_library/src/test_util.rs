@@ -0,0 +1,9 @@
+// 各模块测试共用的小工具
+
+// 简单的线性同余，测试可复现；返回高 31 位，低位的周期太短
+pub fn lcg(seed: &mut u64) -> u64 {
+    *seed = seed
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407);
+    *seed >> 33
+}
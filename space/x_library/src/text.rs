@@ -0,0 +1,439 @@
+// 介于 `&str` 和 `String` 之间的字符串（String.md、struct-field-lifetime.rs）
+//
+// 三种存储方式：
+// - Borrowed：借用别人的 `&'a str`，不分配
+// - Inline：不超过 INLINE_CAPACITY 字节时直接放在值里，不分配
+// - Heap：更长的内容放在 String 里
+//
+// 修改借用的内容时才拷贝一份（写时复制），拷贝时优先放内联；`into_owned` 得到 `Text<'static>`，
+// 可以比原来的字符串活得更久。Hash / Eq / Ord 都按 `str` 的语义实现，
+// 所以 `HashMap<Text, _>` 可以直接用 `&str` 查询。
+
+use std::borrow::{Borrow, Cow};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+pub const INLINE_CAPACITY: usize = 22;
+
+#[derive(Clone)]
+enum Repr<'a> {
+    Borrowed(&'a str),
+    Inline { len: u8, buf: [u8; INLINE_CAPACITY] },
+    Heap(String),
+}
+
+#[derive(Clone)]
+pub struct Text<'a>(Repr<'a>);
+
+impl<'a> Text<'a> {
+    pub const fn new() -> Self {
+        Text(Repr::Inline {
+            len: 0,
+            buf: [0; INLINE_CAPACITY],
+        })
+    }
+
+    pub const fn borrowed(s: &'a str) -> Self {
+        Text(Repr::Borrowed(s))
+    }
+
+    // 放得下就内联，否则返回 None
+    pub fn inline(s: &str) -> Option<Text<'static>> {
+        if s.len() > INLINE_CAPACITY {
+            return None;
+        }
+        let mut buf = [0; INLINE_CAPACITY];
+        buf[..s.len()].copy_from_slice(s.as_bytes());
+        Some(Text(Repr::Inline {
+            len: s.len() as u8,
+            buf,
+        }))
+    }
+
+    fn owned(s: &str) -> Text<'static> {
+        Text::inline(s).unwrap_or_else(|| Text(Repr::Heap(s.to_string())))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            Repr::Borrowed(s) => s,
+            // SAFETY: buf[..len] 只会从 &str 拷贝进来，按字符边界截断
+            Repr::Inline { len, buf } => unsafe {
+                std::str::from_utf8_unchecked(&buf[..*len as usize])
+            },
+            Repr::Heap(s) => s,
+        }
+    }
+
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.0, Repr::Borrowed(_))
+    }
+
+    pub fn is_inline(&self) -> bool {
+        matches!(self.0, Repr::Inline { .. })
+    }
+
+    pub fn is_heap(&self) -> bool {
+        matches!(self.0, Repr::Heap(_))
+    }
+
+    // 脱离原来的生命周期；已经拥有数据的直接搬过去，不会重新分配
+    pub fn into_owned(self) -> Text<'static> {
+        match self.0 {
+            Repr::Borrowed(s) => Text::owned(s),
+            Repr::Inline { len, buf } => Text(Repr::Inline { len, buf }),
+            Repr::Heap(s) => Text(Repr::Heap(s)),
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        match self.0 {
+            Repr::Heap(s) => s,
+            _ => self.as_str().to_string(),
+        }
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        let new_len = self.len() + s.len();
+        match &mut self.0 {
+            Repr::Heap(heap) => heap.push_str(s),
+            Repr::Inline { len, buf } if new_len <= INLINE_CAPACITY => {
+                buf[*len as usize..new_len].copy_from_slice(s.as_bytes());
+                *len = new_len as u8;
+            }
+            // 借用的内容第一次被修改，或内联放不下了：换成自己拥有的存储
+            _ => {
+                let mut owned = String::with_capacity(new_len);
+                owned.push_str(self.as_str());
+                owned.push_str(s);
+                *self = if new_len <= INLINE_CAPACITY {
+                    Text::owned(&owned)
+                } else {
+                    Text(Repr::Heap(owned))
+                };
+            }
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    // 借用的内容只需要缩短切片，不用拷贝
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len >= self.len() {
+            return;
+        }
+        assert!(self.is_char_boundary(new_len), "not a char boundary");
+        match &mut self.0 {
+            Repr::Borrowed(s) => *s = &s[..new_len],
+            Repr::Inline { len, .. } => *len = new_len as u8,
+            Repr::Heap(s) => s.truncate(new_len),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    // 需要 String 的全部 API 时用：内容会搬到堆上
+    pub fn to_mut(&mut self) -> &mut String {
+        if !self.is_heap() {
+            *self = Text(Repr::Heap(self.as_str().to_string()));
+        }
+        match &mut self.0 {
+            Repr::Heap(s) => s,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Default for Text<'_> {
+    fn default() -> Self {
+        Text::new()
+    }
+}
+
+// 收集出来的 Text 总是自己拥有内容，短的放内联缓冲区
+impl Extend<char> for Text<'_> {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|c| self.push(c));
+    }
+}
+
+impl<'s> Extend<&'s str> for Text<'_> {
+    fn extend<I: IntoIterator<Item = &'s str>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|s| self.push_str(s));
+    }
+}
+
+impl Extend<String> for Text<'_> {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|s| self.push_str(&s));
+    }
+}
+
+impl FromIterator<char> for Text<'_> {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut text = Text::new();
+        text.extend(iter);
+        text
+    }
+}
+
+impl<'s> FromIterator<&'s str> for Text<'_> {
+    fn from_iter<I: IntoIterator<Item = &'s str>>(iter: I) -> Self {
+        let mut text = Text::new();
+        text.extend(iter);
+        text
+    }
+}
+
+impl FromIterator<String> for Text<'_> {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        let mut text = Text::new();
+        text.extend(iter);
+        text
+    }
+}
+
+impl Deref for Text<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Text<'_> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for Text<'_> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<'a> From<&'a str> for Text<'a> {
+    fn from(s: &'a str) -> Self {
+        Text::borrowed(s)
+    }
+}
+
+impl<'a> From<&'a String> for Text<'a> {
+    fn from(s: &'a String) -> Self {
+        Text::borrowed(s)
+    }
+}
+
+impl From<String> for Text<'_> {
+    fn from(s: String) -> Self {
+        Text(Repr::Heap(s))
+    }
+}
+
+impl From<char> for Text<'_> {
+    fn from(c: char) -> Self {
+        Text::owned(c.encode_utf8(&mut [0; 4]))
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for Text<'a> {
+    fn from(s: Cow<'a, str>) -> Self {
+        match s {
+            Cow::Borrowed(s) => Text::borrowed(s),
+            Cow::Owned(s) => Text::from(s),
+        }
+    }
+}
+
+impl From<Text<'_>> for String {
+    fn from(t: Text<'_>) -> Self {
+        t.into_string()
+    }
+}
+
+impl fmt::Display for Text<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Debug for Text<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+// 下面都只看内容，不看存储方式
+impl Hash for Text<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl PartialEq for Text<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Text<'_> {}
+
+impl PartialOrd for Text<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Text<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl PartialEq<str> for Text<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Text<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Text<'_> {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::{BTreeSet, HashMap};
+
+    fn hash_of<T: Hash + ?Sized>(v: &T) -> u64 {
+        let mut h = DefaultHasher::new();
+        v.hash(&mut h);
+        h.finish()
+    }
+
+    // iterator.rs 里的 first_word
+    fn first_word(s: &str) -> &str {
+        s.split(' ').next().unwrap_or("")
+    }
+
+    #[test]
+    fn storage_kinds() {
+        assert!(Text::new().is_inline());
+        assert!(Text::from("abc").is_borrowed());
+        assert!(Text::inline("short").unwrap().is_inline());
+        assert!(Text::inline(&"x".repeat(INLINE_CAPACITY + 1)).is_none());
+        assert!(Text::from(String::from("s")).is_heap());
+        assert!(Text::from('中').into_owned().is_inline());
+        assert!(std::mem::size_of::<Text>() <= 32);
+    }
+
+    #[test]
+    fn borrowed_upgrades_on_mutation() {
+        let source = String::from("good morning");
+        let mut t = Text::from(first_word(&source));
+        assert!(t.is_borrowed());
+
+        t.truncate(2);
+        assert!(t.is_borrowed());
+        assert_eq!(t, "go");
+
+        t.push('!');
+        assert!(t.is_inline());
+        assert_eq!(t, "go!");
+        assert_eq!(source, "good morning");
+
+        // 超出内联容量就搬到堆上
+        t.push_str(" and a very long tail");
+        assert!(t.is_heap());
+        assert_eq!(t, "go! and a very long tail");
+
+        let mut b = Text::from("a long borrowed string, longer than inline");
+        b.push('.');
+        assert!(b.is_heap());
+    }
+
+    #[test]
+    fn outlives_its_source() {
+        struct Ex<'a> {
+            part: Text<'a>,
+        }
+
+        let ex: Ex<'static> = {
+            let string1 = String::from("abcd efgh");
+            let borrowed = Ex {
+                part: Text::from(first_word(&string1)),
+            };
+            Ex {
+                part: borrowed.part.into_owned(),
+            }
+        };
+        assert_eq!(ex.part, "abcd");
+        assert!(ex.part.is_inline());
+    }
+
+    #[test]
+    fn deref_and_conversions() {
+        let t = Text::from("Hello World");
+        assert_eq!(t.len(), 11);
+        assert!(t.starts_with("Hello"));
+        assert_eq!(t.to_uppercase(), "HELLO WORLD");
+        assert_eq!(format!("{t}|{t:?}"), "Hello World|\"Hello World\"");
+
+        let s: String = Text::from("abc").into();
+        assert_eq!(s, "abc");
+        let heap = String::from("keep the allocation");
+        let ptr = heap.as_ptr();
+        let back = Text::from(heap).into_string();
+        assert_eq!(back.as_ptr(), ptr);
+
+        assert!(Text::from(Cow::Borrowed("x")).is_borrowed());
+        assert!(Text::from(Cow::<str>::Owned("x".into())).is_heap());
+
+        let mut m = Text::from("abc");
+        m.to_mut().insert(0, '>');
+        assert_eq!(m, ">abc");
+        m.clear();
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn hash_and_order_match_str() {
+        let words = ["pear", "apple", "a much longer word than inline allows", ""];
+        for w in words {
+            for t in [
+                Text::from(w),
+                Text::from(w).into_owned(),
+                Text::from(w.to_string()),
+            ] {
+                assert_eq!(hash_of(&t), hash_of(w));
+            }
+        }
+
+        let mut counts: HashMap<Text, usize> = HashMap::new();
+        *counts.entry(Text::from("apple")).or_default() += 1;
+        *counts.entry(Text::from(String::from("apple"))).or_default() += 1;
+        assert_eq!(counts.get("apple"), Some(&2));
+
+        let texts: BTreeSet<Text> = words.iter().map(|w| Text::from(*w)).collect();
+        let strs: BTreeSet<&str> = words.iter().copied().collect();
+        assert!(texts.iter().map(|t| t.as_str()).eq(strs.iter().copied()));
+        let (a, b) = (Text::from("a"), Text::from(String::from("b")));
+        assert!(a < b);
+    }
+}
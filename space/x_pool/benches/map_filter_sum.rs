@@ -0,0 +1,51 @@
+// iterator_chain_iter_map_filter_sum.rs 的 map/filter/sum，放大数据量后比较顺序和并行版本
+//
+// cargo bench -p x_pool --bench map_filter_sum
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use x_pool::ThreadPool;
+
+const LEN: i64 = 20_000_000;
+const CHUNK: usize = 64 * 1024;
+const ROUNDS: u32 = 10;
+
+fn sequential(numbers: &[i64]) -> i64 {
+    numbers.iter().map(|x| x * 2).filter(|x| x > &5).sum()
+}
+
+fn parallel(pool: &ThreadPool, numbers: &[i64]) -> i64 {
+    pool.par_chunks(numbers, CHUNK, sequential)
+        .into_iter()
+        .sum()
+}
+
+fn measure(name: &str, expected: i64, mut round: impl FnMut() -> i64) {
+    // 预热一轮，顺便检查结果
+    assert_eq!(round(), expected);
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(round());
+    }
+    println!("{name:<24} {:>10.2?}/round", start.elapsed() / ROUNDS);
+}
+
+fn main() {
+    let numbers: Vec<i64> = (1..=LEN).collect();
+    let expected = sequential(&numbers);
+    let threads = std::thread::available_parallelism().map_or(4, |n| n.get());
+    println!("map/filter/sum over {LEN} numbers, {threads} threads, {ROUNDS} rounds");
+
+    measure("sequential", expected, || sequential(black_box(&numbers)));
+
+    let fixed = ThreadPool::new(threads);
+    measure("fixed pool", expected, || {
+        parallel(&fixed, black_box(&numbers))
+    });
+
+    let stealing = ThreadPool::work_stealing(threads);
+    measure("work-stealing pool", expected, || {
+        parallel(&stealing, black_box(&numbers))
+    });
+}
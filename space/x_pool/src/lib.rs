@@ -0,0 +1,16 @@
+// 线程池（学习大纲「并发编程」一节：std::thread、Mutex、Condvar、原子类型）
+//
+// - `ThreadPool::new(n)`：固定线程数，共享一个任务队列
+// - `ThreadPool::work_stealing(n)`：每个线程一个本地队列，空闲时从别的线程偷任务
+// - `spawn` 返回 `JoinHandle`，`join` 拿到任务的返回值，任务 panic 时在调用方重新 panic
+// - `scope` 里的任务可以借用调用方的数据
+// - drop 或 `shutdown` 时等队列里的任务全部执行完
+//
+// 注意：在工作线程里对同一个池的 `JoinHandle` 调用 `join` 会占住这个线程，线程数太少时可能互相等待；
+// 需要在任务里等子任务时用 `scope`，等待期间会帮忙执行队列里的任务。
+
+mod pool;
+mod scope;
+
+pub use pool::{Builder, JoinHandle, Mode, ThreadPool};
+pub use scope::Scope;
@@ -0,0 +1,463 @@
+// 线程池本体
+//
+// 两种调度方式：
+// - Fixed：所有任务进同一个全局队列，空闲的线程按 FIFO 取
+// - WorkStealing：每个工作线程有自己的本地队列，工作线程里 spawn 的任务放进本地队列（LIFO 执行，缓存友好），
+//   自己的队列空了再去全局队列取，最后从别的线程队列的另一端偷
+//
+// 计数 `queued` 和条件变量只用来让空闲线程睡觉，任务本身放在各个队列里。
+// 提交时先加计数再入队，所以工作线程偶尔会看到计数大于 0 但还没拿到任务，这时让出一下再试。
+
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+pub(crate) type Job = Box<dyn FnOnce() + Send + 'static>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Fixed,
+    WorkStealing,
+}
+
+static NEXT_POOL_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // 当前线程属于哪个池的第几个工作线程
+    static WORKER: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+}
+
+#[derive(Default)]
+struct Signal {
+    queued: usize,
+    shutdown: bool,
+}
+
+pub(crate) struct Shared {
+    id: usize,
+    mode: Mode,
+    injector: Mutex<VecDeque<Job>>,
+    locals: Vec<Mutex<VecDeque<Job>>>,
+    signal: Mutex<Signal>,
+    cond: Condvar,
+    steals: AtomicUsize,
+}
+
+impl Shared {
+    fn current_worker(&self) -> Option<usize> {
+        match WORKER.get() {
+            Some((pool, index)) if pool == self.id => Some(index),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn push(&self, job: Job) {
+        {
+            let mut signal = self.signal.lock().unwrap();
+            // 关闭过程中正在收尾的任务还可以继续派生子任务
+            assert!(
+                !signal.shutdown || self.current_worker().is_some(),
+                "spawn on a thread pool that is shutting down"
+            );
+            signal.queued += 1;
+        }
+        match (self.mode, self.current_worker()) {
+            (Mode::WorkStealing, Some(index)) => self.locals[index].lock().unwrap().push_back(job),
+            _ => self.injector.lock().unwrap().push_back(job),
+        }
+        self.cond.notify_one();
+    }
+
+    fn find_job(&self, me: Option<usize>) -> Option<Job> {
+        // Fixed 模式没有本地队列
+        if let Some(local) = me.and_then(|i| self.locals.get(i)) {
+            if let Some(job) = local.lock().unwrap().pop_back() {
+                return Some(job);
+            }
+        }
+        if let Some(job) = self.injector.lock().unwrap().pop_front() {
+            return Some(job);
+        }
+        // 从下一个线程开始轮流偷，避免大家都盯着 0 号
+        let n = self.locals.len();
+        let start = me.map_or(0, |i| i + 1);
+        for k in 0..n {
+            let victim = (start + k) % n;
+            if Some(victim) == me {
+                continue;
+            }
+            if let Some(job) = self.locals[victim].lock().unwrap().pop_front() {
+                self.steals.fetch_add(1, Ordering::Relaxed);
+                return Some(job);
+            }
+        }
+        None
+    }
+
+    // 拿到一个任务就把计数减掉；拿不到返回 None，不阻塞
+    pub(crate) fn try_take(&self) -> Option<Job> {
+        let job = self.find_job(self.current_worker())?;
+        self.signal.lock().unwrap().queued -= 1;
+        Some(job)
+    }
+
+    pub(crate) fn run(&self, job: Job) {
+        // 任务自己负责把 panic 交给 JoinHandle，这里兜底，保证工作线程不死
+        let _ = panic::catch_unwind(AssertUnwindSafe(job));
+    }
+
+    fn worker_loop(&self, index: usize) {
+        WORKER.set(Some((self.id, index)));
+        loop {
+            {
+                let mut signal = self.signal.lock().unwrap();
+                while signal.queued == 0 && !signal.shutdown {
+                    signal = self.cond.wait(signal).unwrap();
+                }
+                // 优雅关闭：队列里的任务全部跑完才退出
+                if signal.queued == 0 && signal.shutdown {
+                    return;
+                }
+            }
+            match self.try_take() {
+                Some(job) => self.run(job),
+                None => thread::yield_now(),
+            }
+        }
+    }
+}
+
+pub struct Builder {
+    threads: usize,
+    mode: Mode,
+    name: String,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Builder {
+            threads: thread::available_parallelism().map_or(4, |n| n.get()),
+            mode: Mode::Fixed,
+            name: "x-pool".to_string(),
+        }
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        assert!(threads > 0, "a thread pool needs at least one thread");
+        self.threads = threads;
+        self
+    }
+
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    // 工作线程命名为 `{name}-{index}`
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn build(self) -> ThreadPool {
+        let locals = match self.mode {
+            Mode::Fixed => Vec::new(),
+            Mode::WorkStealing => (0..self.threads).map(|_| Mutex::default()).collect(),
+        };
+        let shared = Arc::new(Shared {
+            id: NEXT_POOL_ID.fetch_add(1, Ordering::Relaxed),
+            mode: self.mode,
+            injector: Mutex::default(),
+            locals,
+            signal: Mutex::default(),
+            cond: Condvar::new(),
+            steals: AtomicUsize::new(0),
+        });
+        let workers = (0..self.threads)
+            .map(|index| {
+                let shared = shared.clone();
+                thread::Builder::new()
+                    .name(format!("{}-{index}", self.name))
+                    .spawn(move || shared.worker_loop(index))
+                    .expect("failed to spawn worker thread")
+            })
+            .collect();
+        ThreadPool { shared, workers }
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Builder::new()
+    }
+}
+
+pub struct ThreadPool {
+    pub(crate) shared: Arc<Shared>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl ThreadPool {
+    // 固定大小、共享队列
+    pub fn new(threads: usize) -> Self {
+        Builder::new().threads(threads).build()
+    }
+
+    pub fn work_stealing(threads: usize) -> Self {
+        Builder::new()
+            .threads(threads)
+            .mode(Mode::WorkStealing)
+            .build()
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.shared.mode
+    }
+
+    // 从别的线程队列里偷到的任务数，只有 WorkStealing 模式会增长
+    pub fn steals(&self) -> usize {
+        self.shared.steals.load(Ordering::Relaxed)
+    }
+
+    pub fn spawn<F, T>(&self, f: F) -> JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (job, handle) = JoinHandle::wrap(f);
+        self.shared.push(job);
+        handle
+    }
+
+    // 等队列里的任务都跑完再退出，drop 时也会这么做
+    pub fn shutdown(mut self) {
+        self.shutdown_inner();
+    }
+
+    fn shutdown_inner(&mut self) {
+        self.shared.signal.lock().unwrap().shutdown = true;
+        self.shared.cond.notify_all();
+        // 池子在自己的工作线程里被 drop 时（比如 Arc 的最后一个引用在任务里），不能 join 自己
+        let me = thread::current().id();
+        for worker in self.workers.drain(..) {
+            if worker.thread().id() != me {
+                let _ = worker.join();
+            }
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.shutdown_inner();
+    }
+}
+
+struct Slot<T> {
+    result: Mutex<Option<thread::Result<T>>>,
+    done: Condvar,
+}
+
+type Reclaim = Box<dyn FnOnce() -> Option<Box<dyn Any + Send>> + Send + Sync>;
+
+// 任务的结果；任务 panic 时 join 会在调用方重新 panic
+pub struct JoinHandle<T> {
+    slot: Arc<Slot<T>>,
+    // 作用域任务用：join 时从 scope 那里把原始的 panic payload 拿回来，scope 就不再重新抛出
+    reclaim: Option<Reclaim>,
+}
+
+impl<T: Send> JoinHandle<T> {
+    // 把闭包包装成往 slot 里写结果的任务；闭包的生命周期由调用方保证
+    pub(crate) fn wrap<'a, F>(f: F) -> (Box<dyn FnOnce() + Send + 'a>, JoinHandle<T>)
+    where
+        F: FnOnce() -> T + Send + 'a,
+        T: 'a,
+    {
+        JoinHandle::wrap_with(f, |_| {}, || {})
+    }
+
+    // on_result 在结果写进 slot 之前调用，可以改写结果（比如拿走 panic 的 payload）；
+    // on_released 在任务放掉自己那份 slot 之后调用：handle 已经被丢掉时，结果此时已经 drop 了。
+    // 作用域任务靠它保证 'scope 的数据在 scope 返回前不会再被碰到（和 std 的 Packet::drop 一样）
+    pub(crate) fn wrap_with<'a, F, R, D>(
+        f: F,
+        on_result: R,
+        on_released: D,
+    ) -> (Box<dyn FnOnce() + Send + 'a>, JoinHandle<T>)
+    where
+        F: FnOnce() -> T + Send + 'a,
+        R: FnOnce(&mut thread::Result<T>) + Send + 'a,
+        D: FnOnce() + Send + 'a,
+        T: 'a,
+    {
+        let slot = Arc::new(Slot {
+            result: Mutex::new(None),
+            done: Condvar::new(),
+        });
+        let handle = JoinHandle {
+            slot: slot.clone(),
+            reclaim: None,
+        };
+        let job = Box::new(move || {
+            // 局部变量按声明的相反顺序 drop：先放掉 slot，再调用 on_released；
+            // 结果的 drop panic 时 on_released 也照样会在展开时调用
+            let _released = OnDrop(Some(on_released));
+            let slot = slot;
+            let mut result = panic::catch_unwind(AssertUnwindSafe(f));
+            on_result(&mut result);
+            *slot.result.lock().unwrap() = Some(result);
+            slot.done.notify_all();
+        });
+        (job, handle)
+    }
+
+    pub(crate) fn with_reclaim(mut self, reclaim: Reclaim) -> Self {
+        self.reclaim = Some(reclaim);
+        self
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.slot.result.lock().unwrap().is_some()
+    }
+
+    // 任务 panic 时返回 Err(payload)
+    pub fn try_join(self) -> thread::Result<T> {
+        let result = {
+            let mut result = self.slot.result.lock().unwrap();
+            loop {
+                if let Some(result) = result.take() {
+                    break result;
+                }
+                result = self.slot.done.wait(result).unwrap();
+            }
+        };
+        match (result, self.reclaim) {
+            (Err(message), Some(reclaim)) => Err(reclaim().unwrap_or(message)),
+            (result, _) => result,
+        }
+    }
+
+    pub fn join(self) -> T {
+        match self.try_join() {
+            Ok(value) => value,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+struct OnDrop<D: FnOnce()>(Option<D>);
+
+impl<D: FnOnce()> Drop for OnDrop<D> {
+    fn drop(&mut self) {
+        if let Some(f) = self.0.take() {
+            f();
+        }
+    }
+}
+
+pub(crate) fn payload_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn spawn_and_join() {
+        for pool in [ThreadPool::new(3), ThreadPool::work_stealing(3)] {
+            let handles: Vec<_> = (0..20).map(|i| pool.spawn(move || i * i)).collect();
+            let results: Vec<i32> = handles.into_iter().map(JoinHandle::join).collect();
+            assert_eq!(results, (0..20).map(|i| i * i).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn worker_names() {
+        let pool = Builder::new().threads(2).name("worker").build();
+        let name = pool
+            .spawn(|| thread::current().name().unwrap().to_string())
+            .join();
+        assert!(name.starts_with("worker-"));
+        assert_eq!(pool.threads(), 2);
+    }
+
+    #[test]
+    fn panics_propagate_and_pool_survives() {
+        let pool = ThreadPool::new(1);
+        let err = pool.spawn(|| panic!("task failed")).try_join().unwrap_err();
+        assert_eq!(payload_message(&*err), "task failed");
+
+        let caught = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.spawn(|| -> i32 { panic!("again") }).join()
+        }));
+        assert!(caught.is_err());
+
+        // 唯一的工作线程还活着
+        assert_eq!(pool.spawn(|| 7).join(), 7);
+    }
+
+    #[test]
+    fn graceful_shutdown_drains_queue() {
+        let pool = ThreadPool::new(2);
+        let counter = Arc::new(AtomicUsize::new(0));
+        for _ in 0..50 {
+            let counter = counter.clone();
+            pool.spawn(move || {
+                thread::sleep(Duration::from_micros(200));
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        pool.shutdown();
+        assert_eq!(counter.load(Ordering::SeqCst), 50);
+    }
+
+    #[test]
+    fn idle_workers_steal_local_tasks() {
+        let pool = Arc::new(ThreadPool::work_stealing(4));
+        let inner = pool.clone();
+        // 在工作线程里 spawn 的子任务都进了这个线程的本地队列，其他线程只能偷
+        let children = pool
+            .spawn(move || {
+                (0..16)
+                    .map(|i| {
+                        inner.spawn(move || {
+                            thread::sleep(Duration::from_millis(5));
+                            i
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .join();
+        let sum: i32 = children.into_iter().map(JoinHandle::join).sum();
+        assert_eq!(sum, (0..16).sum());
+        assert!(pool.steals() > 0);
+    }
+
+    #[test]
+    fn fixed_mode_never_steals() {
+        let pool = ThreadPool::new(4);
+        let handles: Vec<_> = (0..32).map(|i| pool.spawn(move || i)).collect();
+        handles.into_iter().for_each(|h| {
+            h.join();
+        });
+        assert_eq!(pool.steals(), 0);
+        assert_eq!(pool.mode(), Mode::Fixed);
+    }
+}
@@ -0,0 +1,283 @@
+// 作用域任务：可以借用调用方栈上的数据，`scope` 返回前保证所有任务都已结束
+//
+// 和 std::thread::scope 一样的思路：任务闭包的生命周期只有 'scope，入队前把它当成 'static，
+// 安全性靠 `scope` 在返回（包括 panic 展开）之前一直等到计数归零。
+// 等待的线程不闲着，会帮忙执行池里的任务，所以在工作线程里嵌套 scope 也不会把池子卡死。
+
+use std::any::Any;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use crate::pool::{payload_message, Job, JoinHandle, ThreadPool};
+
+struct State {
+    running: Mutex<usize>,
+    done: Condvar,
+    spawned: AtomicUsize,
+    // 按 panic 先后排列的 (任务编号, payload)，scope 结束时在调用方重新抛出第一个。
+    // 和 std 一样，join 过的任务已经把 panic 交给了调用方，join 时从这里撤掉
+    panics: Mutex<Vec<(usize, Box<dyn Any + Send>)>>,
+}
+
+pub struct Scope<'scope, 'env: 'scope> {
+    pool: &'scope ThreadPool,
+    state: Arc<State>,
+    // 'scope 不变，'env 协变，与 std::thread::Scope 相同
+    _scope: PhantomData<&'scope mut &'scope ()>,
+    _env: PhantomData<&'env ()>,
+}
+
+impl<'scope> Scope<'scope, '_> {
+    pub fn spawn<F, T>(&'scope self, f: F) -> JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        *self.state.running.lock().unwrap() += 1;
+        let id = self.state.spawned.fetch_add(1, Ordering::Relaxed);
+        let state = self.state.clone();
+        let released = self.state.clone();
+        let reclaimed = self.state.clone();
+        let (job, handle) = JoinHandle::wrap_with(
+            f,
+            move |result| {
+                // 原始 payload 先交给 scope，slot 里只留一份文字说明；join 时再换回来
+                if let Err(payload) = result {
+                    let message = Box::new(payload_message(&**payload).to_string());
+                    let payload = std::mem::replace(payload, message);
+                    state.panics.lock().unwrap().push((id, payload));
+                }
+            },
+            // 结果（handle 被丢掉时）已经 drop 完才减计数，scope 返回之后不会再碰 'scope 的数据
+            move || {
+                *released.running.lock().unwrap() -= 1;
+                released.done.notify_all();
+            },
+        );
+        // SAFETY: scope() 返回前会等所有任务结束，任务借用的 'scope 数据在此之前一直有效
+        let job: Job =
+            unsafe { std::mem::transmute::<Box<dyn FnOnce() + Send + 'scope>, Job>(job) };
+        self.pool.shared.push(job);
+        handle.with_reclaim(Box::new(move || {
+            let mut panics = reclaimed.panics.lock().unwrap();
+            let index = panics.iter().position(|&(task, _)| task == id)?;
+            Some(panics.remove(index).1)
+        }))
+    }
+}
+
+impl ThreadPool {
+    pub fn scope<'env, F, R>(&self, f: F) -> R
+    where
+        F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> R,
+    {
+        let scope = Scope {
+            pool: self,
+            state: Arc::new(State {
+                running: Mutex::new(0),
+                done: Condvar::new(),
+                spawned: AtomicUsize::new(0),
+                panics: Mutex::new(Vec::new()),
+            }),
+            _scope: PhantomData,
+            _env: PhantomData,
+        };
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+
+        // 无论 f 是否 panic 都要等任务结束
+        loop {
+            if *scope.state.running.lock().unwrap() == 0 {
+                break;
+            }
+            match self.shared.try_take() {
+                Some(job) => self.shared.run(job),
+                None => {
+                    let running = scope.state.running.lock().unwrap();
+                    if *running > 0 {
+                        let _ = scope
+                            .state
+                            .done
+                            .wait_timeout(running, Duration::from_millis(1))
+                            .unwrap();
+                    }
+                }
+            }
+        }
+
+        match result {
+            Err(payload) => panic::resume_unwind(payload),
+            Ok(value) => {
+                let unjoined = std::mem::take(&mut *scope.state.panics.lock().unwrap());
+                if let Some((_, payload)) = unjoined.into_iter().next() {
+                    panic::resume_unwind(payload);
+                }
+                value
+            }
+        }
+    }
+
+    // 把切片按 chunk 大小切开并行处理，结果按块的顺序返回
+    pub fn par_chunks<T, R, F>(&self, data: &[T], chunk: usize, f: F) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&[T]) -> R + Sync,
+    {
+        assert!(chunk > 0);
+        let f = &f;
+        // 在 scope 外面再 join：scope 里阻塞在 join 上的工作线程帮不了忙，单线程的池子会卡死
+        let handles: Vec<_> =
+            self.scope(|s| data.chunks(chunk).map(|c| s.spawn(move || f(c))).collect());
+        handles.into_iter().map(JoinHandle::join).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn tasks_borrow_from_the_caller() {
+        let pool = ThreadPool::work_stealing(3);
+        let numbers: Vec<i64> = (1..=1000).collect();
+        let total = AtomicUsize::new(0);
+
+        let halves = pool.scope(|s| {
+            let (a, b) = numbers.split_at(500);
+            let ha = s.spawn(|| a.iter().sum::<i64>());
+            let hb = s.spawn(|| b.iter().sum::<i64>());
+            for _ in 0..10 {
+                s.spawn(|| total.fetch_add(1, Ordering::SeqCst));
+            }
+            (ha.join(), hb.join())
+        });
+
+        assert_eq!(halves.0 + halves.1, 500_500);
+        // 没 join 的任务在 scope 返回前也都跑完了
+        assert_eq!(total.load(Ordering::SeqCst), 10);
+    }
+
+    #[test]
+    fn mutable_borrows_of_disjoint_parts() {
+        let pool = ThreadPool::new(2);
+        let mut data = vec![1, 2, 3, 4, 5, 6];
+        pool.scope(|s| {
+            for chunk in data.chunks_mut(2) {
+                s.spawn(move || chunk.iter_mut().for_each(|x| *x *= 10));
+            }
+        });
+        assert_eq!(data, [10, 20, 30, 40, 50, 60]);
+    }
+
+    #[test]
+    fn panic_in_scoped_task_reaches_the_caller() {
+        let pool = ThreadPool::new(2);
+        let finished = AtomicUsize::new(0);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.scope(|s| {
+                s.spawn(|| panic!("scoped failure"));
+                s.spawn(|| {
+                    std::thread::sleep(Duration::from_millis(10));
+                    finished.fetch_add(1, Ordering::SeqCst);
+                });
+            })
+        }));
+        let payload = result.unwrap_err();
+        assert_eq!(payload_message(&*payload), "scoped failure");
+        // 另一个任务没有被丢下
+        assert_eq!(finished.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn original_panic_payload_is_rethrown() {
+        #[derive(Debug, PartialEq)]
+        struct Code(u32);
+
+        let pool = ThreadPool::new(2);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.scope(|s| {
+                s.spawn(|| panic::panic_any(Code(7)));
+            })
+        }));
+        assert_eq!(result.unwrap_err().downcast_ref::<Code>(), Some(&Code(7)));
+    }
+
+    // 和 std::thread::scope 一样，只重新抛出没被 join 的任务的 panic
+    #[test]
+    fn joined_panic_is_not_rethrown() {
+        #[derive(Debug, PartialEq)]
+        struct Code(u32);
+
+        let pool = ThreadPool::new(2);
+        let code = pool.scope(|s| {
+            let h = s.spawn(|| panic::panic_any(Code(7)));
+            // join 拿到的也是原始 payload
+            h.try_join().unwrap_err().downcast::<Code>().unwrap()
+        });
+        assert_eq!(*code, Code(7));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.scope(|s| {
+                let joined = s.spawn(|| panic!("joined"));
+                assert!(joined.try_join().is_err());
+                s.spawn(|| panic!("not joined"));
+            })
+        }));
+        assert_eq!(payload_message(&*result.unwrap_err()), "not joined");
+    }
+
+    // 没人 join 的结果要在 scope 返回之前 drop 掉，drop 里还会读借用的数据
+    #[test]
+    fn discarded_results_are_dropped_before_scope_returns() {
+        struct Late<'a>(&'a Mutex<Vec<u32>>, u32);
+
+        impl Drop for Late<'_> {
+            fn drop(&mut self) {
+                // 让 drop 慢一点，放大竞争窗口
+                std::thread::sleep(Duration::from_millis(1));
+                self.0.lock().unwrap().push(self.1);
+            }
+        }
+
+        let pool = ThreadPool::work_stealing(4);
+        for _ in 0..20 {
+            let dropped = Mutex::new(Vec::new());
+            pool.scope(|s| {
+                for i in 0..4 {
+                    let dropped = &dropped;
+                    s.spawn(move || Late(dropped, i));
+                }
+            });
+            // scope 返回时四个结果都已经 drop 完
+            let mut seen = dropped.into_inner().unwrap();
+            seen.sort();
+            assert_eq!(seen, [0, 1, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn nested_scope_on_single_worker() {
+        // 唯一的工作线程在 scope 里等待时会自己执行子任务，不会死锁
+        let pool = Arc::new(ThreadPool::new(1));
+        let inner = pool.clone();
+        let sum = pool
+            .spawn(move || {
+                let data = [1, 2, 3, 4];
+                inner.par_chunks(&data, 1, |c| c[0] * 2).iter().sum::<i32>()
+            })
+            .join();
+        assert_eq!(sum, 20);
+    }
+
+    #[test]
+    fn par_chunks_keeps_order() {
+        let pool = ThreadPool::work_stealing(4);
+        let data: Vec<u32> = (0..100).collect();
+        let firsts = pool.par_chunks(&data, 10, |c| c[0]);
+        assert_eq!(firsts, (0..100).step_by(10).collect::<Vec<_>>());
+    }
+}
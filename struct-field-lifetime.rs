@@ -10,3 +10,24 @@ fn main() {
 
     println!("part is {}", s.part);
 }
+
+
+
+// 字段换成 space/x_library 的 Text：先借用，需要时 into_owned，结构体就能比 string1 活得更久
+use x_library::text::Text;
+
+struct OwnedEx {
+    part: Text<'static>,
+}
+
+fn main() {
+    let s = {
+        let string1 = String::from("abcd");
+        let borrowed = Text::from(&string1);
+        OwnedEx {
+            part: borrowed.into_owned(),
+        }
+    };
+
+    println!("part is {}", s.part);
+}
@@ -12,3 +12,22 @@ fn main() {
     assert_eq!(vec![0, 1], v);
     assert_eq!(2, v.len());
 }
+
+
+
+// 同样的 push / from_iter，换成 x_library 里手写的 SmallVec：前 4 个元素不分配
+use x_library::smallvec::{Growth, SmallVec};
+
+fn main() {
+    let mut vec: SmallVec<u8, 4> = SmallVec::new().growth_factor(Growth::ONE_AND_HALF);
+    vec.push(5u8);
+    assert!(vec.is_inline());
+
+    vec.extend(6..10);
+    assert!(!vec.is_inline());
+    println!("{:?} len={} capacity={}", vec, vec.len(), vec.capacity());
+
+    let v: SmallVec<i32, 4> = SmallVec::from_iter(0..2);
+    assert_eq!(v, [0, 1]);
+    assert_eq!(v.into_vec(), vec![0, 1]);
+}